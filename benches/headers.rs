@@ -0,0 +1,38 @@
+//! Compares the header parsers on a typical request head, without a benchmark
+//! framework: `cargo bench`.
+
+use rust_parser_example::parse;
+use std::hint::black_box;
+use std::time::Instant;
+
+const HEAD: &str = "Host: example.org\r\n\
+User-Agent: Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/115.0\r\n\
+Accept: text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8\r\n\
+Accept-Language: en-US,en;q=0.5\r\n\
+Accept-Encoding: gzip, deflate, br\r\n\
+Connection: keep-alive\r\n\
+Cookie: session=38afes7a8; theme=dark\r\n\
+Upgrade-Insecure-Requests: 1\r\n\
+\r\n";
+
+const ITERATIONS: u32 = 200_000;
+
+fn bench<T>(name: &str, mut parse: impl FnMut(&str) -> T) {
+    // warm up the caches and the allocator
+    for _ in 0..ITERATIONS / 10 {
+        black_box(parse(black_box(HEAD)));
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(parse(black_box(HEAD)));
+    }
+    let per_iter = start.elapsed().as_nanos() / u128::from(ITERATIONS);
+    println!("{:<20} {:>6} ns/iter", name, per_iter);
+}
+
+fn main() {
+    bench("headers", |head| parse::headers(head).map(|(_, h)| h.len()));
+    bench("header_slices", |head| {
+        parse::header_slices(head).map(|(_, h)| h.len())
+    });
+}
@@ -0,0 +1,16 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_parser_example::{parse, BodyReader, Headers, ParseOptions};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse::chunked_body(&String::from_utf8_lossy(data));
+
+    let headers = Headers::from(vec![("Transfer-Encoding".to_string(), "chunked".to_string())]);
+    if let Ok(mut reader) = BodyReader::new(&headers, &ParseOptions::default()) {
+        // in two pieces, so that state carried between pushes is exercised
+        let (first, second) = data.split_at(data.len() / 2);
+        if reader.push(first).is_ok() {
+            let _ = reader.push(second);
+        }
+    }
+});
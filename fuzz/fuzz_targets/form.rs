@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_parser_example::{parse_form_body, parse_nested, URI};
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    if let Ok(pairs) = parse_form_body(&input) {
+        let _ = parse_nested(&pairs);
+    }
+    if let Ok(uri) = URI::parse(&input) {
+        let _ = uri.query_structured();
+    }
+});
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_parser_example::parse;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    if let Ok((_, headers)) = parse::headers(&input) {
+        let _ = (headers.content_length(), headers.host(), headers.is_chunked());
+    }
+    let _ = parse::header_slices(&input);
+    let _ = parse::headers_skipping_malformed(&input);
+});
@@ -0,0 +1,12 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_parser_example::{parse_byteranges, parse_multipart};
+
+// the first line is the boundary, the rest the body
+fuzz_target!(|data: &[u8]| {
+    let split = data.iter().position(|&b| b == b'\n').unwrap_or(0);
+    let boundary = String::from_utf8_lossy(&data[..split]);
+    let body = data.get(split + 1..).unwrap_or_default();
+    let _ = parse_multipart(body, &boundary);
+    let _ = parse_byteranges(body, &boundary);
+});
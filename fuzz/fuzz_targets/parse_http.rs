@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_parser_example::{parse_http, parse_http_verbose, parse_request, ParseOptions};
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    let _ = parse_http(&input);
+    let _ = parse_request(&input, &ParseOptions::strict());
+    let _ = parse_http_verbose(&input);
+});
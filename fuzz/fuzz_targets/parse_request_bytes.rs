@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_parser_example::{parse_request_bytes, parse_request_partial, ParseOptions};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = parse_request_bytes(data, &ParseOptions::default());
+    let _ = parse_request_partial(data, &ParseOptions::default());
+});
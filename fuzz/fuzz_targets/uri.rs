@@ -0,0 +1,11 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_parser_example::parse;
+
+fuzz_target!(|data: &[u8]| {
+    let input = String::from_utf8_lossy(data);
+    if let Ok(parsed) = parse::uri(&input) {
+        let _ = parsed.resolve(&parsed);
+        let _ = parsed.path_segments().count();
+    }
+});
@@ -0,0 +1,138 @@
+use crate::cache::delta_seconds;
+use crate::headers::parse_value;
+use crate::percent;
+use crate::{list, parameters, quoted_string, token, Headers, Parameters, ParseError};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{all_consuming, map, map_opt},
+    sequence::{separated_pair, tuple},
+    IResult,
+};
+use std::time::Duration;
+
+/// Alt-Svc: https://tools.ietf.org/html/rfc7838#section-3
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AltSvc {
+    /// `clear`: all alternatives for the origin are invalidated.
+    Clear,
+    Services(Vec<AltService>),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AltService {
+    /// The percent-decoded ALPN protocol id, like `h3`.
+    pub protocol_id: String,
+    /// None when the alternative is on the same host as the origin.
+    pub host: Option<String>,
+    pub port: u16,
+    pub params: Parameters,
+}
+
+impl AltService {
+    /// The `ma` parameter, 24 hours if it is missing or invalid.
+    pub fn max_age(&self) -> Duration {
+        self.param("ma")
+            .and_then(delta_seconds)
+            .unwrap_or_else(|| Duration::from_secs(24 * 60 * 60))
+    }
+
+    /// The `persist` parameter: only the value `1` has a meaning.
+    pub fn persist(&self) -> bool {
+        self.param("persist") == Some("1")
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// alt-authority = quoted-string containing [ uri-host ] ":" port
+fn alt_authority(input: &str) -> IResult<&str, (Option<String>, u16)> {
+    map_opt(quoted_string, |authority| {
+        let colon = authority.rfind(':')?;
+        let port = authority[colon + 1..].parse().ok()?;
+        let host = &authority[..colon];
+        Some((Some(host.to_owned()).filter(|host| !host.is_empty()), port))
+    })(input)
+}
+
+// alt-value = alternative *( OWS ";" OWS parameter )
+fn alt_service(input: &str) -> IResult<&str, AltService> {
+    tuple((separated_pair(token, tag("="), alt_authority), parameters))(input).map(
+        |(next_input, ((protocol_id, (host, port)), params))| {
+            (
+                next_input,
+                AltService {
+                    protocol_id: percent::decode_lossy(protocol_id),
+                    host,
+                    port,
+                    params,
+                },
+            )
+        },
+    )
+}
+
+pub(crate) fn alt_svc(input: &str) -> IResult<&str, AltSvc> {
+    alt((
+        map(all_consuming(tag("clear")), |_| AltSvc::Clear),
+        map(list(alt_service), AltSvc::Services),
+    ))(input)
+}
+
+impl Headers {
+    /// Alt-Svc: https://tools.ietf.org/html/rfc7838#section-3
+    pub fn alt_svc(&self) -> Option<Result<AltSvc, ParseError>> {
+        self.get("Alt-Svc").map(|value| parse_value(value, alt_svc))
+    }
+}
+
+#[test]
+fn test_alt_svc() {
+    let alt_svc =
+        |value: &str| Headers::from(vec![("Alt-Svc".to_string(), value.to_string())]).alt_svc();
+
+    let h3 = alt_svc("h3=\":443\"; ma=86400, h2=\"alt.example.org:8000\"; persist=1")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        h3,
+        AltSvc::Services(vec![
+            AltService {
+                protocol_id: "h3".to_string(),
+                host: None,
+                port: 443,
+                params: vec![("ma".to_string(), "86400".to_string())],
+            },
+            AltService {
+                protocol_id: "h2".to_string(),
+                host: Some("alt.example.org".to_string()),
+                port: 8000,
+                params: vec![("persist".to_string(), "1".to_string())],
+            },
+        ])
+    );
+    if let AltSvc::Services(services) = h3 {
+        assert_eq!(services[0].max_age(), Duration::from_secs(86400));
+        assert!(!services[0].persist());
+        assert_eq!(services[1].max_age(), Duration::from_secs(86400));
+        assert!(services[1].persist());
+    }
+
+    assert_eq!(
+        alt_svc("w%3D%3Dx=\"example.org:443\""),
+        Some(Ok(AltSvc::Services(vec![AltService {
+            protocol_id: "w==x".to_string(),
+            host: Some("example.org".to_string()),
+            port: 443,
+            params: vec![],
+        }])))
+    );
+    assert_eq!(alt_svc(" clear "), Some(Ok(AltSvc::Clear)));
+    assert!(alt_svc("h3=:443").unwrap().is_err());
+    assert!(alt_svc("h3=\"example.org\"").unwrap().is_err());
+}
@@ -0,0 +1,300 @@
+use crate::base64;
+use crate::headers::parse_value;
+use crate::{list, ows, token, token_or_quoted_string, Headers, Parameter, Parameters, ParseError};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while, take_while1},
+    character::complete::space1,
+    combinator::{all_consuming, map, opt, recognize},
+    error::{Error, ErrorKind},
+    sequence::{pair, preceded, separated_pair, tuple},
+    Err as NomErr, IResult,
+};
+use std::fmt;
+
+/// credentials: https://tools.ietf.org/html/rfc7235#section-2.1
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Credentials {
+    /// https://tools.ietf.org/html/rfc7617
+    Basic { username: String, password: String },
+    /// https://tools.ietf.org/html/rfc6750#section-2.1
+    Bearer(String),
+    Other {
+        scheme: String,
+        token68: Option<String>,
+        params: Parameters,
+    },
+}
+
+impl fmt::Display for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Credentials::Basic { username, password } => write!(
+                f,
+                "Basic {}",
+                base64::encode(format!("{}:{}", username, password).as_bytes())
+            ),
+            Credentials::Bearer(token) => write!(f, "Bearer {}", token),
+            Credentials::Other {
+                scheme,
+                token68,
+                params,
+            } => {
+                write!(f, "{}", scheme)?;
+                if let Some(token68) = token68 {
+                    write!(f, " {}", token68)?;
+                }
+                for (i, (name, value)) in params.iter().enumerate() {
+                    let separator = if i == 0 { " " } else { ", " };
+                    write!(
+                        f,
+                        "{}{}=\"{}\"",
+                        separator,
+                        name,
+                        value.replace('"', "\\\"")
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// challenge: https://tools.ietf.org/html/rfc7235#section-2.1
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Challenge {
+    pub scheme: String,
+    pub token68: Option<String>,
+    pub params: Parameters,
+}
+
+// token68 = 1*( ALPHA / DIGIT / "-" / "." / "_" / "~" / "+" / "/" ) *"="
+fn token68(input: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        take_while1(|chr: char| chr.is_ascii_alphanumeric() || "-._~+/".contains(chr)),
+        take_while(|chr| chr == '='),
+    ))(input)
+}
+
+// auth-param = token BWS "=" BWS ( token / quoted-string )
+fn auth_param(input: &str) -> IResult<&str, Parameter> {
+    separated_pair(token, tuple((ows, tag("="), ows)), token_or_quoted_string)(input)
+        .map(|(next_input, (name, value))| (next_input, (name.to_owned(), value)))
+}
+
+enum AuthData {
+    Token68(String),
+    Params(Parameters),
+}
+
+// the whole value after the auth-scheme, as credentials have no list around them
+pub(crate) fn credentials(input: &str) -> IResult<&str, Credentials> {
+    let (next_input, (scheme, data)) = pair(
+        token,
+        opt(preceded(
+            space1,
+            alt((
+                map(all_consuming(token68), |t| AuthData::Token68(t.to_owned())),
+                map(all_consuming(list(auth_param)), AuthData::Params),
+            )),
+        )),
+    )(input)?;
+    let credentials = match (scheme.to_ascii_lowercase().as_str(), data) {
+        ("basic", Some(AuthData::Token68(token68))) => {
+            basic(&token68).ok_or_else(|| NomErr::Error(Error::new(input, ErrorKind::Verify)))?
+        }
+        ("bearer", Some(AuthData::Token68(token68))) => Credentials::Bearer(token68),
+        (_, data) => {
+            let (token68, params) = match data {
+                Some(AuthData::Token68(token68)) => (Some(token68), vec![]),
+                Some(AuthData::Params(params)) => (None, params),
+                None => (None, vec![]),
+            };
+            Credentials::Other {
+                scheme: scheme.to_owned(),
+                token68,
+                params,
+            }
+        }
+    };
+    Ok((next_input, credentials))
+}
+
+// basic-credentials = base64 of user-id ":" password
+fn basic(token68: &str) -> Option<Credentials> {
+    let decoded = String::from_utf8(base64::decode(token68)?).ok()?;
+    let (username, password) = decoded.split_at(decoded.find(':')?);
+    Some(Credentials::Basic {
+        username: username.to_owned(),
+        password: password[1..].to_owned(),
+    })
+}
+
+enum ChallengePart {
+    Start(String, Option<AuthData>),
+    Param(Parameter),
+}
+
+// the auth-scheme starts a new challenge, it is followed by a token68 or the
+// first auth-param, further auth-params are separated by commas like challenges
+fn challenge_part(input: &str) -> IResult<&str, ChallengePart> {
+    alt((
+        map(auth_param, ChallengePart::Param),
+        map(
+            pair(
+                token,
+                opt(preceded(
+                    space1,
+                    alt((
+                        map(auth_param, |p| AuthData::Params(vec![p])),
+                        map(token68, |t| AuthData::Token68(t.to_owned())),
+                    )),
+                )),
+            ),
+            |(scheme, data)| ChallengePart::Start(scheme.to_owned(), data),
+        ),
+    ))(input)
+}
+
+fn challenges(parts: Vec<ChallengePart>) -> Option<Vec<Challenge>> {
+    let mut challenges: Vec<Challenge> = vec![];
+    for part in parts {
+        match part {
+            ChallengePart::Start(scheme, data) => {
+                let (token68, params) = match data {
+                    Some(AuthData::Token68(token68)) => (Some(token68), vec![]),
+                    Some(AuthData::Params(params)) => (None, params),
+                    None => (None, vec![]),
+                };
+                challenges.push(Challenge {
+                    scheme,
+                    token68,
+                    params,
+                });
+            }
+            // a token68 cannot be followed by auth-params
+            ChallengePart::Param(param) => match challenges.last_mut() {
+                Some(challenge) if challenge.token68.is_none() => challenge.params.push(param),
+                _ => return None,
+            },
+        }
+    }
+    Some(challenges)
+}
+
+impl Headers {
+    /// Authorization: https://tools.ietf.org/html/rfc7235#section-4.2
+    pub fn authorization(&self) -> Option<Result<Credentials, ParseError>> {
+        self.get("Authorization")
+            .map(|value| parse_value(value, credentials))
+    }
+
+    /// Proxy-Authorization: https://tools.ietf.org/html/rfc7235#section-4.4
+    pub fn proxy_authorization(&self) -> Option<Result<Credentials, ParseError>> {
+        self.get("Proxy-Authorization")
+            .map(|value| parse_value(value, credentials))
+    }
+
+    /// WWW-Authenticate: https://tools.ietf.org/html/rfc7235#section-4.1
+    /// Field lines that fail to parse are ignored.
+    pub fn www_authenticate(&self) -> Option<Vec<Challenge>> {
+        self.challenges("WWW-Authenticate")
+    }
+
+    /// Proxy-Authenticate: https://tools.ietf.org/html/rfc7235#section-4.3
+    pub fn proxy_authenticate(&self) -> Option<Vec<Challenge>> {
+        self.challenges("Proxy-Authenticate")
+    }
+
+    fn challenges(&self, name: &str) -> Option<Vec<Challenge>> {
+        if !self.contains(name) {
+            return None;
+        }
+        Some(
+            self.get_all(name)
+                .filter_map(|value| all_consuming(list(challenge_part))(value).ok())
+                .filter_map(|(_, parts)| challenges(parts))
+                .flatten()
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_proxy_authorization() {
+    use crate::HeaderValue;
+
+    let credentials = Credentials::Basic {
+        username: "aladdin".to_string(),
+        password: "open:sesame".to_string(),
+    };
+    let mut headers = Headers::new();
+    headers.set(
+        crate::StandardHeader::ProxyAuthorization.into(),
+        HeaderValue::new(&credentials.to_string()).unwrap(),
+    );
+    assert_eq!(
+        headers.get("Proxy-Authorization"),
+        Some("Basic YWxhZGRpbjpvcGVuOnNlc2FtZQ==")
+    );
+    assert_eq!(headers.proxy_authorization(), Some(Ok(credentials)));
+    assert_eq!(headers.authorization(), None);
+
+    let headers = Headers::from(vec![
+        (
+            "Authorization".to_string(),
+            "Bearer mF_9.B5f-4.1JqM".to_string(),
+        ),
+        (
+            "Proxy-Authorization".to_string(),
+            "Digest username=\"Mufasa\", realm=\"http-auth@example.org\"".to_string(),
+        ),
+    ]);
+    assert_eq!(
+        headers.authorization(),
+        Some(Ok(Credentials::Bearer("mF_9.B5f-4.1JqM".to_string())))
+    );
+    assert_eq!(
+        headers.proxy_authorization(),
+        Some(Ok(Credentials::Other {
+            scheme: "Digest".to_string(),
+            token68: None,
+            params: vec![
+                ("username".to_string(), "Mufasa".to_string()),
+                ("realm".to_string(), "http-auth@example.org".to_string()),
+            ],
+        }))
+    );
+}
+
+#[test]
+fn test_proxy_authenticate() {
+    let headers = Headers::from(vec![(
+        "Proxy-Authenticate".to_string(),
+        "Basic realm=\"proxy\", charset=\"UTF-8\", Bearer, Negotiate abc==".to_string(),
+    )]);
+    assert_eq!(
+        headers.proxy_authenticate(),
+        Some(vec![
+            Challenge {
+                scheme: "Basic".to_string(),
+                token68: None,
+                params: vec![
+                    ("realm".to_string(), "proxy".to_string()),
+                    ("charset".to_string(), "UTF-8".to_string()),
+                ],
+            },
+            Challenge {
+                scheme: "Bearer".to_string(),
+                token68: None,
+                params: vec![],
+            },
+            Challenge {
+                scheme: "Negotiate".to_string(),
+                token68: Some("abc==".to_string()),
+                params: vec![],
+            },
+        ])
+    );
+    assert_eq!(headers.www_authenticate(), None);
+}
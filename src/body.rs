@@ -0,0 +1,382 @@
+use crate::chunked::validate_transfer_encoding;
+use crate::{Headers, ParseError, ParseOptions, Request, Version};
+use std::convert::TryFrom;
+use std::fmt;
+
+/// The result of feeding input to a `BodyReader`.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct BodyProgress {
+    /// How many bytes of the input were consumed. Bytes after the end of the
+    /// body are not consumed and belong to the next message.
+    pub consumed: usize,
+    /// The body bytes decoded from the consumed input.
+    pub data: Vec<u8>,
+    /// Whether the end of the body was reached.
+    pub done: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BodyError {
+    /// The chunked framing is malformed at the given byte.
+    InvalidChunk(u8),
+    /// A chunk size does not fit into 64 bits.
+    ChunkTooLarge,
+    /// The extensions of a chunk are longer than `ParseOptions::max_chunk_extension_len`.
+    ExtensionTooLong,
+    /// The chunks add up to more than `ParseOptions::max_body_len`, `len` is the
+    /// length up to and including the chunk that crossed it.
+    TooLarge { limit: u64, len: u64 },
+}
+
+impl fmt::Display for BodyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BodyError::InvalidChunk(byte) => write!(f, "unexpected byte {:#04x} in a chunk", byte),
+            BodyError::ChunkTooLarge => write!(f, "chunk size does not fit into 64 bits"),
+            BodyError::ExtensionTooLong => write!(f, "chunk extensions are too long"),
+            BodyError::TooLarge { limit, len } => write!(
+                f,
+                "body of {} bytes exceeds the limit of {} bytes",
+                len, limit
+            ),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum State {
+    Length(u64),
+    ChunkSize { size: u64, digits: bool },
+    ChunkExtension { size: u64, len: usize },
+    ChunkSizeLf(u64),
+    ChunkData(u64),
+    ChunkDataCr,
+    ChunkDataLf,
+    Trailer { empty_line: bool },
+    TrailerLf { empty_line: bool },
+    Done,
+}
+
+/// Decodes a body incrementally from input fed in arbitrary pieces, framed by
+/// the chunked transfer coding or Content-Length like in `parse_http`. The
+/// trailer section of a chunked body is skipped.
+#[derive(Debug, Clone)]
+pub struct BodyReader {
+    state: State,
+    max_chunk_extension_len: usize,
+    max_body_len: Option<u64>,
+    // the sum of the chunk sizes so far
+    chunked_len: u64,
+    awaiting_continue: bool,
+    // the bytes consumed so far, and where the trailer section started
+    consumed: u64,
+    trailer_start: Option<u64>,
+}
+
+impl BodyReader {
+    /// A reader for the body of a message with the given headers, usually
+    /// returned by `parse_request_head`.
+    pub fn new(headers: &Headers, options: &ParseOptions) -> Result<BodyReader, ParseError> {
+        validate_transfer_encoding(headers)?;
+        let state = if headers.is_chunked() {
+            State::ChunkSize {
+                size: 0,
+                digits: false,
+            }
+        } else {
+            match headers.content_length() {
+                Some(Ok(0)) | None => State::Done,
+                Some(Ok(len)) => match options.max_body_len {
+                    Some(limit) if len > limit => {
+                        return Err(ParseError::BodyTooLarge { limit, len })
+                    }
+                    _ => State::Length(len),
+                },
+                Some(Err(e)) => return Err(e),
+            }
+        };
+        Ok(BodyReader {
+            state,
+            max_chunk_extension_len: options.max_chunk_extension_len,
+            max_body_len: options.max_body_len,
+            chunked_len: 0,
+            awaiting_continue: false,
+            consumed: 0,
+            trailer_start: None,
+        })
+    }
+
+    /// A reader for the body of the given request. If the client waits for
+    /// `100 Continue` before sending the body, see `Request::expects_continue`,
+    /// the reader starts out paused: nothing is consumed until `continue_body`
+    /// is called. The caller can instead send a final response and skip the body.
+    pub fn for_request(
+        request: &Request,
+        options: &ParseOptions,
+    ) -> Result<BodyReader, ParseError> {
+        let mut reader = BodyReader::new(&request.headers, options)?;
+        reader.awaiting_continue = request.expects_continue() && !reader.is_done();
+        Ok(reader)
+    }
+
+    /// Whether the headers are complete and the body is held back until the
+    /// caller decides to read it.
+    pub fn is_awaiting_continue(&self) -> bool {
+        self.awaiting_continue
+    }
+
+    /// Resumes a paused reader, after `100 Continue` was sent.
+    pub fn continue_body(&mut self) {
+        self.awaiting_continue = false;
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.state == State::Done
+    }
+
+    /// Feeds the next piece of input and returns the body bytes decoded from it.
+    pub fn push(&mut self, input: &[u8]) -> Result<BodyProgress, BodyError> {
+        let mut progress = BodyProgress::default();
+        if self.awaiting_continue {
+            return Ok(progress);
+        }
+        while progress.consumed < input.len() && self.state != State::Done {
+            let rest = &input[progress.consumed..];
+            match self.state {
+                State::Length(remaining) | State::ChunkData(remaining) => {
+                    let n = rest
+                        .len()
+                        .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+                    progress.data.extend_from_slice(&rest[..n]);
+                    progress.consumed += n;
+                    let remaining = remaining - n as u64;
+                    self.state = match (self.state, remaining) {
+                        (State::Length(_), 0) => State::Done,
+                        (State::Length(_), _) => State::Length(remaining),
+                        (_, 0) => State::ChunkDataCr,
+                        (_, _) => State::ChunkData(remaining),
+                    };
+                }
+                _ => {
+                    self.state = self.next_state(rest[0])?;
+                    match self.state {
+                        State::ChunkData(size) => self.chunked_len += size,
+                        State::Trailer { .. } if self.trailer_start.is_none() => {
+                            self.trailer_start = Some(self.consumed + progress.consumed as u64 + 1);
+                        }
+                        _ => {}
+                    }
+                    progress.consumed += 1;
+                }
+            }
+        }
+        self.consumed += progress.consumed as u64;
+        progress.done = self.is_done();
+        Ok(progress)
+    }
+
+    // the offset of the trailer section of a chunked body, counted from the
+    // first byte pushed, once the last chunk was read
+    pub(crate) fn trailer_start(&self) -> Option<u64> {
+        self.trailer_start
+    }
+
+    // the framing of a chunked body, one byte at a time
+    fn next_state(&self, byte: u8) -> Result<State, BodyError> {
+        let after_size_line = |size| match size {
+            0 => Ok(State::Trailer { empty_line: true }),
+            size => {
+                let len = self.chunked_len.saturating_add(size);
+                match self.max_body_len {
+                    Some(limit) if len > limit => Err(BodyError::TooLarge { limit, len }),
+                    _ => Ok(State::ChunkData(size)),
+                }
+            }
+        };
+        let state = match (self.state, byte) {
+            (State::ChunkSize { size, .. }, _) if byte.is_ascii_hexdigit() => {
+                let digit = (byte as char).to_digit(16).unwrap_or_default();
+                let size = size
+                    .checked_mul(16)
+                    .and_then(|size| size.checked_add(u64::from(digit)))
+                    .ok_or(BodyError::ChunkTooLarge)?;
+                State::ChunkSize { size, digits: true }
+            }
+            (State::ChunkSize { size, digits: true }, b';')
+            | (State::ChunkSize { size, digits: true }, b' ')
+            | (State::ChunkSize { size, digits: true }, b'\t') => {
+                State::ChunkExtension { size, len: 1 }
+            }
+            (State::ChunkSize { size, digits: true }, b'\r')
+            | (State::ChunkExtension { size, .. }, b'\r') => State::ChunkSizeLf(size),
+            (State::ChunkSize { size, digits: true }, b'\n')
+            | (State::ChunkExtension { size, .. }, b'\n')
+            | (State::ChunkSizeLf(size), b'\n') => after_size_line(size)?,
+            (State::ChunkExtension { size, len }, _) => {
+                if len >= self.max_chunk_extension_len {
+                    return Err(BodyError::ExtensionTooLong);
+                }
+                State::ChunkExtension { size, len: len + 1 }
+            }
+            (State::ChunkDataCr, b'\r') => State::ChunkDataLf,
+            (State::ChunkDataCr, b'\n') | (State::ChunkDataLf, b'\n') => State::ChunkSize {
+                size: 0,
+                digits: false,
+            },
+            (State::Trailer { empty_line }, b'\r') => State::TrailerLf { empty_line },
+            (State::Trailer { empty_line: true }, b'\n')
+            | (State::TrailerLf { empty_line: true }, b'\n') => State::Done,
+            (State::Trailer { empty_line: false }, b'\n')
+            | (State::TrailerLf { empty_line: false }, b'\n') => {
+                State::Trailer { empty_line: true }
+            }
+            (State::Trailer { .. }, _) => State::Trailer { empty_line: false },
+            _ => return Err(BodyError::InvalidChunk(byte)),
+        };
+        Ok(state)
+    }
+}
+
+impl Request {
+    /// Whether the client sent `Expect: 100-continue` and waits for an interim
+    /// response before sending the body: https://tools.ietf.org/html/rfc7231#section-5.1.1
+    /// The expectation is ignored for HTTP/1.0 requests.
+    pub fn expects_continue(&self) -> bool {
+        self.request_line.version != Version::HTTP10
+            && self
+                .headers
+                .get_all("Expect")
+                .any(|value| value.trim().eq_ignore_ascii_case("100-continue"))
+    }
+}
+
+#[cfg(test)]
+fn read_bytewise(reader: &mut BodyReader, input: &[u8]) -> (Vec<u8>, usize) {
+    let mut body = Vec::new();
+    let mut consumed = 0;
+    for byte in input.chunks(1) {
+        let progress = reader.push(byte).unwrap();
+        body.extend(progress.data);
+        consumed += progress.consumed;
+        if progress.done {
+            break;
+        }
+    }
+    (body, consumed)
+}
+
+#[test]
+fn test_body_reader_content_length() {
+    let input = "POST / HTTP/1.1\r\nContent-Length: 11\r\n\r\nhello worldGET / HTTP/1.1\r\n\r\n";
+    let options = ParseOptions::default();
+    let (rest, request) = crate::parse_request_head(input, &options).unwrap();
+    let mut reader = BodyReader::new(&request.headers, &options).unwrap();
+
+    let (body, consumed) = read_bytewise(&mut reader, rest.as_bytes());
+    assert_eq!(body, b"hello world".to_vec());
+    assert_eq!(&rest[consumed..], "GET / HTTP/1.1\r\n\r\n");
+    assert!(reader.is_done());
+
+    let mut reader = BodyReader::new(&request.headers, &options).unwrap();
+    let progress = reader.push(rest.as_bytes()).unwrap();
+    assert_eq!(progress.consumed, 11);
+    assert!(progress.done);
+
+    let none = crate::parse_http("GET / HTTP/1.1\r\n\r\n").unwrap().1;
+    let mut reader = BodyReader::new(&none.headers, &options).unwrap();
+    assert_eq!(
+        reader.push(b"next").unwrap(),
+        BodyProgress {
+            consumed: 0,
+            data: vec![],
+            done: true,
+        }
+    );
+}
+
+#[test]
+fn test_body_reader_chunked() {
+    let body =
+        b"4;ext=\"a b\"\r\nWiki\r\n6\r\npedia \r\nB\r\nin\r\nchunks.\r\n0\r\nX-Sum: 1\r\n\r\nnext";
+    let headers = Headers::from(vec![(
+        "Transfer-Encoding".to_string(),
+        "chunked".to_string(),
+    )]);
+    let options = ParseOptions::default();
+
+    let mut reader = BodyReader::new(&headers, &options).unwrap();
+    let (data, consumed) = read_bytewise(&mut reader, body);
+    assert_eq!(data, b"Wikipedia in\r\nchunks.".to_vec());
+    assert_eq!(&body[consumed..], b"next");
+
+    let mut reader = BodyReader::new(&headers, &options).unwrap();
+    let progress = reader.push(body).unwrap();
+    assert_eq!(progress.data, data);
+    assert_eq!(progress.consumed, consumed);
+    assert!(progress.done);
+
+    let mut reader = BodyReader::new(&headers, &options).unwrap();
+    assert_eq!(
+        reader.push(b"3\r\nabcX"),
+        Err(BodyError::InvalidChunk(b'X'))
+    );
+    let mut reader = BodyReader::new(&headers, &options).unwrap();
+    assert_eq!(
+        reader.push(b"fffffffffffffffff\r\n"),
+        Err(BodyError::ChunkTooLarge)
+    );
+}
+
+#[test]
+fn test_body_reader_max_body_len() {
+    let options = ParseOptions {
+        max_body_len: Some(4),
+        ..ParseOptions::default()
+    };
+    let headers = Headers::from(vec![("Content-Length".to_string(), "5".to_string())]);
+    assert_eq!(
+        BodyReader::new(&headers, &options).err(),
+        Some(ParseError::BodyTooLarge { limit: 4, len: 5 })
+    );
+
+    let headers = Headers::from(vec![(
+        "Transfer-Encoding".to_string(),
+        "chunked".to_string(),
+    )]);
+    let mut reader = BodyReader::new(&headers, &options).unwrap();
+    assert_eq!(
+        reader.push(b"3\r\nabc\r\n").map(|p| p.data),
+        Ok(b"abc".to_vec())
+    );
+    assert_eq!(
+        reader.push(b"2\r\nde\r\n"),
+        Err(BodyError::TooLarge { limit: 4, len: 5 })
+    );
+    let mut reader = BodyReader::new(&headers, &options).unwrap();
+    let progress = reader.push(b"3\r\nabc\r\n1\r\nd\r\n0\r\n\r\n").unwrap();
+    assert_eq!(progress.data, b"abcd".to_vec());
+    assert!(progress.done);
+}
+
+#[test]
+fn test_body_reader_continue() {
+    let input = "PUT /file HTTP/1.1\r\nExpect: 100-Continue\r\nContent-Length: 4\r\n\r\ndata";
+    let options = ParseOptions::default();
+    let (rest, request) = crate::parse_request_head(input, &options).unwrap();
+    assert!(request.expects_continue());
+
+    let mut reader = BodyReader::for_request(&request, &options).unwrap();
+    assert!(reader.is_awaiting_continue());
+    assert_eq!(reader.push(rest.as_bytes()), Ok(BodyProgress::default()));
+    reader.continue_body();
+    let progress = reader.push(rest.as_bytes()).unwrap();
+    assert_eq!(progress.data, b"data".to_vec());
+    assert!(progress.done);
+
+    let old = "PUT /file HTTP/1.0\r\nExpect: 100-continue\r\nContent-Length: 4\r\n\r\n";
+    let (_, request) = crate::parse_request_head(old, &options).unwrap();
+    assert!(!request.expects_continue());
+    assert!(!BodyReader::for_request(&request, &options)
+        .unwrap()
+        .is_awaiting_continue());
+}
@@ -0,0 +1,212 @@
+use crate::{
+    HeaderName, HeaderValue, Headers, Method, Request, RequestLine, Response, StatusLine, Version,
+    URI,
+};
+
+/// Builds a request to send, with header names and values that are validated
+/// on construction so they cannot inject further header lines.
+#[derive(Debug)]
+pub struct RequestBuilder {
+    request_line: RequestLine,
+    headers: Headers,
+    body: Vec<u8>,
+}
+
+impl RequestBuilder {
+    pub fn new(method: Method, uri: URI) -> Self {
+        RequestBuilder {
+            request_line: RequestLine {
+                method,
+                uri,
+                version: Version::HTTP11,
+            },
+            headers: Headers::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn version(mut self, version: Version) -> Self {
+        self.request_line.version = version;
+        self
+    }
+
+    /// Appends a header field, keeping any others with the same name.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.push(name, value);
+        self
+    }
+
+    pub fn body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
+
+    pub fn build(self) -> Request {
+        Request {
+            request_line: self.request_line,
+            headers: self.headers,
+            body: self.body,
+            trailers: Headers::new(),
+            malformed_headers: Vec::new(),
+        }
+    }
+}
+
+/// Builds a response to send, see `RequestBuilder`.
+#[derive(Debug)]
+pub struct ResponseBuilder {
+    status_line: StatusLine,
+    headers: Headers,
+    body: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StatusLineError {
+    /// The status code does not have three digits.
+    InvalidStatus(u16),
+    /// The reason phrase contains CR, LF or NUL.
+    InvalidReason(String),
+}
+
+impl ResponseBuilder {
+    /// status-line: https://tools.ietf.org/html/rfc7230#section-3.1.2
+    /// The status and reason are validated so they cannot inject header lines.
+    pub fn new(status: u16, reason: &str) -> Result<Self, StatusLineError> {
+        if !(100..=999).contains(&status) {
+            return Err(StatusLineError::InvalidStatus(status));
+        }
+        if reason.contains(['\r', '\n', '\0']) {
+            return Err(StatusLineError::InvalidReason(reason.to_owned()));
+        }
+        Ok(ResponseBuilder {
+            status_line: StatusLine {
+                version: Version::HTTP11,
+                status,
+                reason: reason.to_owned(),
+            },
+            headers: Headers::new(),
+            body: Vec::new(),
+        })
+    }
+
+    pub fn version(mut self, version: Version) -> Self {
+        self.status_line.version = version;
+        self
+    }
+
+    /// Appends a header field, keeping any others with the same name.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.push(name, value);
+        self
+    }
+
+    pub fn body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
+
+    pub fn build(self) -> Response {
+        Response {
+            status_line: self.status_line,
+            headers: self.headers,
+            body: self.body,
+            trailers: Headers::new(),
+        }
+    }
+}
+
+impl Request {
+    /// The request as sent on the wire: request line, header fields, an empty
+    /// line and the body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let line = &self.request_line;
+        let mut out = format!(
+            "{} {} {}\r\n{}\r\n",
+            line.method,
+            line.uri,
+            line.version,
+            self.headers.serialize(false)
+        )
+        .into_bytes();
+        out.extend_from_slice(&self.body);
+        out
+    }
+}
+
+impl Response {
+    /// The response as sent on the wire, see `Request::to_bytes`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let line = &self.status_line;
+        let mut out = format!(
+            "{} {:03} {}\r\n{}\r\n",
+            line.version,
+            line.status,
+            line.reason,
+            self.headers.serialize(false)
+        )
+        .into_bytes();
+        out.extend_from_slice(&self.body);
+        out
+    }
+}
+
+#[test]
+fn test_request_builder() {
+    let uri = crate::URI::parse("/submit").unwrap();
+    let request = RequestBuilder::new(Method::POST, uri.clone())
+        .header(
+            HeaderName::new("Host").unwrap(),
+            HeaderValue::new("example.org").unwrap(),
+        )
+        .body(b"hello".to_vec())
+        .build();
+    assert_eq!(
+        request.to_bytes(),
+        b"POST /submit HTTP/1.1\r\nHost: example.org\r\n\r\nhello".to_vec()
+    );
+
+    // the smuggled field can only reach the wire through an unchecked name or
+    // value
+    let injected = "1\r\nX-Admin: true";
+    assert!(HeaderValue::new(injected).is_err());
+    assert!(HeaderName::new("X-Id\r\nX-Admin").is_err());
+    let mut builder = RequestBuilder::new(Method::GET, uri);
+    if let Ok(value) = injected.parse() {
+        builder = builder.header(HeaderName::from(crate::StandardHeader::Host), value);
+    }
+    if let Ok(name) = "X-Admin: true\r\nX-Id".parse() {
+        builder = builder.header(name, HeaderValue::new("1").unwrap());
+    }
+    let wire = String::from_utf8(builder.build().to_bytes()).unwrap();
+    assert_eq!(wire, "GET /submit HTTP/1.1\r\n\r\n");
+    assert!(!crate::parse_http(&wire)
+        .unwrap()
+        .1
+        .headers
+        .contains("X-Admin"));
+}
+
+#[test]
+fn test_response_builder() {
+    let response = ResponseBuilder::new(404, "Not Found")
+        .unwrap()
+        .header(
+            crate::StandardHeader::ContentLength.into(),
+            HeaderValue::new("0").unwrap(),
+        )
+        .build();
+    assert_eq!(
+        response.to_bytes(),
+        b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_vec()
+    );
+
+    let injected = "OK\r\nSet-Cookie: pwn=1";
+    assert_eq!(
+        ResponseBuilder::new(200, injected).map(|_| ()),
+        Err(StatusLineError::InvalidReason(injected.to_string()))
+    );
+    assert_eq!(
+        ResponseBuilder::new(2000, "OK").map(|_| ()),
+        Err(StatusLineError::InvalidStatus(2000))
+    );
+}
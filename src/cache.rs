@@ -0,0 +1,338 @@
+use crate::{
+    fmt_http_date, list, ows, parse_http_date, quoted_string, token, token_or_quoted_string,
+    HeaderValue, Headers, StandardHeader,
+};
+use nom::{
+    bytes::complete::{tag, take_while1, take_while_m_n},
+    combinator::{all_consuming, map_opt, map_res, opt},
+    sequence::{pair, preceded, tuple},
+    IResult,
+};
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+/// A Cache-Control or Pragma directive with its optional argument. Names are
+/// case-insensitive and stored in lowercase.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CacheDirective {
+    pub name: String,
+    pub value: Option<String>,
+}
+
+// cache-directive: https://tools.ietf.org/html/rfc7234#section-5.2
+fn cache_directive(input: &str) -> IResult<&str, CacheDirective> {
+    pair(
+        token,
+        opt(preceded(
+            tuple((ows, tag("="), ows)),
+            token_or_quoted_string,
+        )),
+    )(input)
+    .map(|(next_input, res)| {
+        (
+            next_input,
+            CacheDirective {
+                name: res.0.to_lowercase(),
+                value: res.1,
+            },
+        )
+    })
+}
+
+/// Pragma: https://tools.ietf.org/html/rfc7234#section-5.4
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Pragma {
+    pub directives: Vec<CacheDirective>,
+}
+
+impl Pragma {
+    pub fn has_no_cache(&self) -> bool {
+        self.directives.iter().any(|d| d.name == "no-cache")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AgeError {
+    /// The value is not a non-negative decimal integer.
+    Invalid(String),
+}
+
+/// Expires: https://tools.ietf.org/html/rfc7234#section-5.3
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expires {
+    At(SystemTime),
+    /// An invalid date, most commonly `0` or `-1`, which caches have to treat as
+    /// a time in the past.
+    AlreadyExpired,
+}
+
+// delta-seconds: https://tools.ietf.org/html/rfc7234#section-1.2.1
+// values too large to represent saturate, as the RFC allows
+pub(crate) fn delta_seconds(value: &str) -> Option<Duration> {
+    if value.is_empty() || !value.chars().all(|chr| chr.is_ascii_digit()) {
+        return None;
+    }
+    Some(Duration::from_secs(value.parse().unwrap_or(u64::MAX)))
+}
+
+impl Headers {
+    /// Age: https://tools.ietf.org/html/rfc7234#section-5.1
+    /// Signs and fractions are rejected, values beyond `u64::MAX` seconds saturate.
+    pub fn age(&self) -> Option<Result<Duration, AgeError>> {
+        self.get("Age").map(|value| {
+            let value = value.trim();
+            delta_seconds(value).ok_or_else(|| AgeError::Invalid(value.to_owned()))
+        })
+    }
+}
+
+/// A warning-value of the Warning header: https://tools.ietf.org/html/rfc7234#section-5.5
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CacheWarning {
+    pub code: u16,
+    /// The host (with optional port) or pseudonym of the agent adding the warning.
+    pub agent: String,
+    pub text: String,
+    pub date: Option<SystemTime>,
+}
+
+impl fmt::Display for CacheWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:03} {} \"{}\"",
+            self.code,
+            self.agent,
+            self.text.replace('\\', "\\\\").replace('"', "\\\"")
+        )?;
+        if let Some(date) = self.date {
+            write!(f, " \"{}\"", fmt_http_date(date))?;
+        }
+        Ok(())
+    }
+}
+
+// warning-value = warn-code SP warn-agent SP warn-text [ SP warn-date ]
+fn cache_warning(input: &str) -> IResult<&str, CacheWarning> {
+    tuple((
+        map_res(
+            take_while_m_n(3, 3, |chr: char| chr.is_ascii_digit()),
+            str::parse,
+        ),
+        preceded(tag(" "), take_while1(|chr: char| chr != ' ' && chr != ',')),
+        preceded(tag(" "), quoted_string),
+        opt(preceded(
+            tag(" "),
+            map_opt(quoted_string, |date| parse_http_date(&date).ok()),
+        )),
+    ))(input)
+    .map(|(next_input, (code, agent, text, date))| {
+        (
+            next_input,
+            CacheWarning {
+                code,
+                agent: agent.to_owned(),
+                text,
+                date,
+            },
+        )
+    })
+}
+
+impl Headers {
+    /// Cache-Control: https://tools.ietf.org/html/rfc7234#section-5.2
+    /// Field lines that fail to parse are ignored.
+    pub fn cache_control(&self) -> Option<Vec<CacheDirective>> {
+        self.directives("Cache-Control")
+    }
+
+    pub fn pragma(&self) -> Option<Pragma> {
+        self.directives("Pragma")
+            .map(|directives| Pragma { directives })
+    }
+
+    /// Whether the message asks caches not to use a stored response without
+    /// revalidation. `Pragma: no-cache` only counts when there is no Cache-Control
+    /// header, as required by RFC 7234 section 5.4.
+    pub fn no_cache(&self) -> bool {
+        match self.cache_control() {
+            Some(directives) => directives.iter().any(|d| d.name == "no-cache"),
+            None => self.pragma().is_some_and(|pragma| pragma.has_no_cache()),
+        }
+    }
+
+    fn directives(&self, name: &str) -> Option<Vec<CacheDirective>> {
+        if !self.contains(name) {
+            return None;
+        }
+        Some(
+            self.get_all(name)
+                .filter_map(|value| all_consuming(list(cache_directive))(value).ok())
+                .flat_map(|(_, res)| res)
+                .collect(),
+        )
+    }
+
+    /// Warning: https://tools.ietf.org/html/rfc7234#section-5.5
+    /// Field lines that fail to parse are ignored.
+    pub fn warnings(&self) -> Option<Vec<CacheWarning>> {
+        if !self.contains("Warning") {
+            return None;
+        }
+        Some(
+            self.get_all("Warning")
+                .filter_map(|value| all_consuming(list(cache_warning))(value).ok())
+                .flat_map(|(_, res)| res)
+                .collect(),
+        )
+    }
+
+    /// Removes the warnings of a code class, like 1 for all 1xx warnings that a
+    /// cache has to delete after revalidation. The remaining warnings are kept
+    /// in a single Warning field; lines that fail to parse are dropped as well.
+    pub fn remove_warnings(&mut self, class: u16) {
+        let kept = match self.warnings() {
+            Some(warnings) => warnings
+                .iter()
+                .filter(|warning| warning.code / 100 != class)
+                .map(|warning| warning.to_string())
+                .collect::<Vec<String>>(),
+            None => return,
+        };
+        self.remove("Warning");
+        if !kept.is_empty() {
+            self.set(
+                StandardHeader::Warning.into(),
+                HeaderValue::new_unchecked(kept.join(", ")),
+            );
+        }
+    }
+
+    pub fn expires(&self) -> Option<Expires> {
+        self.get("Expires")
+            .map(|value| match parse_http_date(value) {
+                Ok(time) => Expires::At(time),
+                Err(_) => Expires::AlreadyExpired,
+            })
+    }
+}
+
+#[test]
+fn test_pragma() {
+    let headers = |fields: &[(&str, &str)]| {
+        Headers::from(
+            fields
+                .iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect::<Vec<(String, String)>>(),
+        )
+    };
+
+    let no_cache = headers(&[("Pragma", "no-cache")]);
+    assert_eq!(
+        no_cache.pragma(),
+        Some(Pragma {
+            directives: vec![CacheDirective {
+                name: "no-cache".to_string(),
+                value: None
+            }]
+        })
+    );
+    assert!(no_cache.pragma().unwrap().has_no_cache());
+    assert!(no_cache.no_cache());
+
+    let extension = headers(&[("Pragma", "X-Debug=\"a b\"")]);
+    assert_eq!(
+        extension.pragma().unwrap().directives,
+        vec![CacheDirective {
+            name: "x-debug".to_string(),
+            value: Some("a b".to_string())
+        }]
+    );
+    assert!(!extension.no_cache());
+
+    let both = headers(&[("Pragma", "no-cache"), ("Cache-Control", "max-age=60")]);
+    assert!(!both.no_cache());
+    let both = headers(&[("Pragma", "no-cache"), ("Cache-Control", "No-Cache")]);
+    assert!(both.no_cache());
+}
+
+#[test]
+fn test_expires() {
+    use std::time::UNIX_EPOCH;
+
+    let headers = |v: &str| Headers::from(vec![("Expires".to_string(), v.to_string())]);
+
+    assert_eq!(
+        headers("Thu, 01 Dec 2994 16:00:00 GMT").expires(),
+        Some(Expires::At(UNIX_EPOCH + Duration::from_secs(32343292800)))
+    );
+    assert_eq!(headers("0").expires(), Some(Expires::AlreadyExpired));
+    assert_eq!(headers("-1").expires(), Some(Expires::AlreadyExpired));
+    assert_eq!(headers("").expires(), Some(Expires::AlreadyExpired));
+    assert_eq!(Headers::new().expires(), None);
+}
+
+#[test]
+fn test_age() {
+    let headers = |v: &str| Headers::from(vec![("Age".to_string(), v.to_string())]);
+
+    assert_eq!(headers("0").age(), Some(Ok(Duration::from_secs(0))));
+    assert_eq!(
+        headers(" 12345 ").age(),
+        Some(Ok(Duration::from_secs(12345)))
+    );
+    assert_eq!(
+        headers("-1").age(),
+        Some(Err(AgeError::Invalid("-1".to_string())))
+    );
+    assert_eq!(
+        headers("1.5").age(),
+        Some(Err(AgeError::Invalid("1.5".to_string())))
+    );
+    assert_eq!(
+        headers("99999999999999999999999").age(),
+        Some(Ok(Duration::from_secs(u64::MAX)))
+    );
+    assert_eq!(Headers::new().age(), None);
+}
+
+#[test]
+fn test_warnings() {
+    use std::time::UNIX_EPOCH;
+
+    let headers = |v: &str| Headers::from(vec![("Warning".to_string(), v.to_string())]);
+
+    let stale = CacheWarning {
+        code: 110,
+        agent: "proxy.example.org".to_string(),
+        text: "Response is stale".to_string(),
+        date: Some(UNIX_EPOCH + Duration::from_secs(1445412480)),
+    };
+    assert_eq!(
+        headers("110 proxy.example.org \"Response is stale\" \"Wed, 21 Oct 2015 07:28:00 GMT\"")
+            .warnings(),
+        Some(vec![stale.clone()])
+    );
+
+    let transformed = CacheWarning {
+        code: 214,
+        agent: "cache:8080".to_string(),
+        text: "Transformation \"applied\"".to_string(),
+        date: None,
+    };
+    assert_eq!(
+        headers("214 cache:8080 \"Transformation \\\"applied\\\"\"").warnings(),
+        Some(vec![transformed.clone()])
+    );
+
+    let mut both = headers(&format!("{}, {}", stale, transformed));
+    assert_eq!(
+        both.warnings(),
+        Some(vec![stale.clone(), transformed.clone()])
+    );
+    both.remove_warnings(1);
+    assert_eq!(both.warnings(), Some(vec![transformed]));
+    both.remove_warnings(2);
+    assert_eq!(both.warnings(), None);
+}
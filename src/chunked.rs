@@ -0,0 +1,466 @@
+use crate::headers::split_list;
+use crate::{
+    headers, line_ending, parameters, Headers, Parameters, ParseError, ParseMode, ParseOptions,
+    Request,
+};
+use nom::{
+    bytes::complete::{take_while, take_while1},
+    combinator::{all_consuming, map_res},
+    error::{Error, ErrorKind},
+    Err as NomErr, IResult, Needed,
+};
+
+/// Fields a sender must not put in a trailer section, as they are needed before the
+/// body is processed: https://tools.ietf.org/html/rfc7230#section-4.1.2
+const FORBIDDEN_TRAILERS: [&str; 32] = [
+    // message framing
+    "Transfer-Encoding",
+    "Content-Length",
+    // routing
+    "Host",
+    // request modifiers
+    "Cache-Control",
+    "Expect",
+    "Max-Forwards",
+    "Pragma",
+    "Range",
+    "TE",
+    "If-Match",
+    "If-None-Match",
+    "If-Modified-Since",
+    "If-Unmodified-Since",
+    "If-Range",
+    // authentication
+    "Authorization",
+    "Proxy-Authorization",
+    "WWW-Authenticate",
+    "Proxy-Authenticate",
+    "Set-Cookie",
+    // response control data
+    "Age",
+    "Expires",
+    "Date",
+    "Location",
+    "Retry-After",
+    "Vary",
+    "Warning",
+    // payload processing
+    "Content-Encoding",
+    "Content-Type",
+    "Content-Range",
+    "Trailer",
+    // connection management
+    "Connection",
+    "Keep-Alive",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TrailerError {
+    /// The field was not announced in the Trailer header.
+    Unannounced(String),
+    /// The field is not allowed in a trailer section at all.
+    Forbidden(String),
+}
+
+pub fn is_forbidden_trailer(name: &str) -> bool {
+    FORBIDDEN_TRAILERS
+        .iter()
+        .any(|forbidden| forbidden.eq_ignore_ascii_case(name))
+}
+
+// chunk-size = 1*HEXDIG, which has to fit into memory
+fn chunk_size(input: &str) -> IResult<&str, usize> {
+    map_res(take_while1(|chr: char| chr.is_ascii_hexdigit()), |hex| {
+        usize::from_str_radix(hex, 16)
+    })(input)
+}
+
+/// A chunk of a chunked body: https://tools.ietf.org/html/rfc7230#section-4.1
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Chunk {
+    pub data: Vec<u8>,
+    /// chunk-ext: https://tools.ietf.org/html/rfc7230#section-4.1.1
+    /// Extensions without a value have an empty one.
+    pub extensions: Parameters,
+}
+
+// chunk-size [ chunk-ext ] CRLF
+// The extensions are attacker-controlled, so their length is limited. Outside
+// of strict mode, extensions that fail to parse are skipped.
+fn chunk_line<'a>(input: &'a str, options: &ParseOptions) -> IResult<&'a str, (usize, Parameters)> {
+    let (rest, size) = chunk_size(input)?;
+    let (rest, extensions) = take_while(|chr| chr != '\r' && chr != '\n')(rest)?;
+    if extensions.len() > options.max_chunk_extension_len {
+        return Err(NomErr::Error(Error::new(rest, ErrorKind::TooLarge)));
+    }
+    let extensions = match all_consuming(parameters)(extensions) {
+        Ok((_, extensions)) => extensions,
+        Err(e) if options.mode == ParseMode::Strict => return Err(e),
+        Err(_) => parameters(extensions)
+            .map(|(_, extensions)| extensions)
+            .unwrap_or_default(),
+    };
+    let (rest, _) = line_ending(rest)?;
+    Ok((rest, (size, extensions)))
+}
+
+/// A decoded chunked body: https://tools.ietf.org/html/rfc7230#section-4.1
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ChunkedBody {
+    /// The concatenated chunk data.
+    pub data: Vec<u8>,
+    /// The trailer section, empty if there is none.
+    pub trailers: Headers,
+}
+
+/// The chunks of a chunked body, ending with the zero-sized last chunk, and the
+/// trailer section. A chunk shorter than its size yields `Err::Incomplete`.
+pub(crate) fn chunks<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> IResult<&'a str, (Vec<Chunk>, Headers)> {
+    let mut chunks = Vec::new();
+    let mut input = input;
+    loop {
+        let (rest, (size, extensions)) = chunk_line(input, options)?;
+        if size == 0 {
+            // trailer-part CRLF
+            let (rest, trailers) = headers(rest)?;
+            let (rest, _) = line_ending(rest)?;
+            chunks.push(Chunk {
+                data: Vec::new(),
+                extensions,
+            });
+            return Ok((rest, (chunks, trailers)));
+        }
+        if rest.len() < size {
+            return Err(NomErr::Incomplete(Needed::new(size - rest.len())));
+        }
+        if !rest.is_char_boundary(size) {
+            return Err(NomErr::Error(Error::new(rest, ErrorKind::Char)));
+        }
+        chunks.push(Chunk {
+            data: rest.as_bytes()[..size].to_vec(),
+            extensions,
+        });
+        let (rest, _) = line_ending(&rest[size..])?;
+        input = rest;
+    }
+}
+
+// walks the chunk-size lines without copying any data and returns the decoded
+// length once it exceeds `limit`, `None` if it stays within it or the framing
+// is broken, which is left for `chunks` to report
+pub(crate) fn exceeded_len(input: &str, options: &ParseOptions, limit: u64) -> Option<u64> {
+    let mut len: u64 = 0;
+    let mut input = input;
+    loop {
+        let (rest, (size, _)) = chunk_line(input, options).ok()?;
+        if size == 0 {
+            return None;
+        }
+        len = len.saturating_add(size as u64);
+        if len > limit {
+            return Some(len);
+        }
+        let (rest, _) = line_ending::<()>(rest.get(size..)?).ok()?;
+        input = rest;
+    }
+}
+
+/// chunked-body: https://tools.ietf.org/html/rfc7230#section-4.1
+/// See `chunks`.
+pub(crate) fn chunked_body(input: &str) -> IResult<&str, ChunkedBody> {
+    chunked_body_with(input, &ParseOptions::default())
+}
+
+pub(crate) fn chunked_body_with<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> IResult<&'a str, ChunkedBody> {
+    chunks(input, options).map(|(next_input, (chunks, trailers))| {
+        (
+            next_input,
+            ChunkedBody {
+                data: chunks.into_iter().flat_map(|chunk| chunk.data).collect(),
+                trailers,
+            },
+        )
+    })
+}
+
+impl Request {
+    /// Checks the trailer section of a chunked body, see `Headers::validate_trailers`.
+    pub fn validate_trailers(&self) -> Result<(), Vec<TrailerError>> {
+        self.headers.validate_trailers(&self.trailers)
+    }
+}
+
+impl Headers {
+    /// Transfer-Encoding: https://tools.ietf.org/html/rfc7230#section-3.3.1
+    /// The codings are returned in the order they were applied.
+    pub fn transfer_encoding(&self) -> Option<Vec<String>> {
+        if !self.contains("Transfer-Encoding") {
+            return None;
+        }
+        Some(
+            self.get_all("Transfer-Encoding")
+                .flat_map(split_list)
+                .map(|coding| coding.to_owned())
+                .collect(),
+        )
+    }
+
+    /// Whether the body is framed by the chunked coding, which has to be the
+    /// final one applied: https://tools.ietf.org/html/rfc7230#section-3.3.3
+    pub fn is_chunked(&self) -> bool {
+        self.transfer_encoding()
+            .and_then(|codings| codings.last().cloned())
+            .is_some_and(|coding| coding.eq_ignore_ascii_case("chunked"))
+    }
+
+    /// Trailer: https://tools.ietf.org/html/rfc7230#section-4.4
+    pub fn trailer(&self) -> Option<Vec<String>> {
+        if !self.contains("Trailer") {
+            return None;
+        }
+        Some(
+            self.get_all("Trailer")
+                .flat_map(split_list)
+                .map(|name| name.to_owned())
+                .collect(),
+        )
+    }
+
+    /// Checks a received trailer section against the fields announced in these
+    /// headers and against the fields that are forbidden in trailers.
+    pub fn validate_trailers(&self, trailers: &Headers) -> Result<(), Vec<TrailerError>> {
+        let announced = self.trailer().unwrap_or_default();
+        let errors: Vec<TrailerError> = trailers
+            .iter()
+            .filter_map(|(name, _)| {
+                let name = name.as_str();
+                if is_forbidden_trailer(name) {
+                    Some(TrailerError::Forbidden(name.to_owned()))
+                } else if !announced.iter().any(|a| a.eq_ignore_ascii_case(name)) {
+                    Some(TrailerError::Unannounced(name.to_owned()))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+// A request whose final coding is not chunked has no way to tell where its body
+// ends, and chunking twice is not allowed:
+// https://tools.ietf.org/html/rfc7230#section-3.3.3 item 3
+pub(crate) fn validate_transfer_encoding(headers: &Headers) -> Result<(), ParseError> {
+    let codings = match headers.transfer_encoding() {
+        Some(codings) => codings,
+        None => return Ok(()),
+    };
+    let chunked: Vec<usize> = codings
+        .iter()
+        .enumerate()
+        .filter(|(_, coding)| coding.eq_ignore_ascii_case("chunked"))
+        .map(|(i, _)| i)
+        .collect();
+    match chunked.as_slice() {
+        [i] if *i == codings.len() - 1 => Ok(()),
+        _ => Err(ParseError::InvalidTransferEncoding),
+    }
+}
+
+#[test]
+fn test_chunked_body() {
+    let data = |input| chunked_body(input).map(|(next_input, body)| (next_input, body.data));
+
+    assert_eq!(
+        data("4\r\nWiki\r\n6\r\npedia \r\n0\r\n\r\nnext"),
+        Ok(("next", b"Wikipedia ".to_vec()))
+    );
+    assert_eq!(
+        chunked_body("0\r\n\r\n"),
+        Ok((
+            "",
+            ChunkedBody {
+                data: vec![],
+                trailers: Headers::new(),
+            }
+        ))
+    );
+    assert_eq!(
+        data("1A\r\nabcdefghijklmnopqrstuvwxyz\r\n0\r\n\r\n").map(|(_, body)| body.len()),
+        Ok(26)
+    );
+    assert!(matches!(
+        chunked_body("a\r\nabc"),
+        Err(NomErr::Incomplete(_))
+    ));
+    assert!(chunked_body("3\r\nabc\r\n0\r\n").is_err());
+    assert!(chunked_body("3\r\nabcd\r\n0\r\n\r\n").is_err());
+    assert!(chunked_body("fffffffffffffffff\r\n").is_err());
+}
+
+#[test]
+fn test_chunk_extensions() {
+    let options = ParseOptions::strict();
+    let (_, (parsed, _)) = chunks(
+        "3;name=\"a \\\"b\\\"\"\r\nabc\r\n0 ; last\r\n\r\n",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        parsed,
+        vec![
+            Chunk {
+                data: b"abc".to_vec(),
+                extensions: vec![("name".to_string(), "a \"b\"".to_string())],
+            },
+            Chunk {
+                data: vec![],
+                extensions: vec![("last".to_string(), "".to_string())],
+            },
+        ]
+    );
+
+    let malformed = "3;=x\r\nabc\r\n0\r\n\r\n";
+    assert!(chunks(malformed, &options).is_err());
+    assert_eq!(
+        chunked_body(malformed).map(|(_, body)| body.data),
+        Ok(b"abc".to_vec())
+    );
+
+    let long = format!("3;x={}\r\nabc\r\n0\r\n\r\n", "a".repeat(2000));
+    assert!(matches!(
+        chunked_body(&long),
+        Err(NomErr::Error(Error {
+            code: ErrorKind::TooLarge,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn test_chunked_request() {
+    let input =
+        "POST /upload HTTP/1.1\r\nTransfer-Encoding: gzip, Chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n";
+    let (rest, request) = crate::parse_http(input).unwrap();
+    assert_eq!(request.body, b"hello".to_vec());
+    assert_eq!(rest, "");
+    assert!(request.headers.is_chunked());
+}
+
+#[test]
+fn test_invalid_transfer_encoding() {
+    let request = |codings: &str| {
+        let input = format!(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: {}\r\n\r\n0\r\n\r\n",
+            codings
+        );
+        crate::parse_request(&input, &ParseOptions::default()).map(|request| request.body)
+    };
+    assert_eq!(request("gzip, chunked"), Ok(Vec::new()));
+    assert_eq!(
+        request("chunked, gzip"),
+        Err(ParseError::InvalidTransferEncoding)
+    );
+    assert_eq!(
+        request("chunked, chunked"),
+        Err(ParseError::InvalidTransferEncoding)
+    );
+    assert_eq!(request("gzip"), Err(ParseError::InvalidTransferEncoding));
+
+    let input =
+        "POST / HTTP/1.1\r\nTransfer-Encoding: chunked, gzip\r\nContent-Length: 5\r\n\r\nhello";
+    assert_eq!(
+        crate::parse_http(input).map(|(_, request)| request.body),
+        Err(ParseError::InvalidTransferEncoding)
+    );
+    assert!(crate::parse_http_verbose(input).is_err());
+    assert_eq!(
+        crate::parse_response(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked, chunked\r\n\r\n0\r\n\r\n",
+            &ParseOptions::default()
+        )
+        .map(|response| response.body),
+        Err(ParseError::InvalidTransferEncoding)
+    );
+
+    let headers = Headers::from(vec![
+        ("Transfer-Encoding".to_string(), "chunked".to_string()),
+        ("Transfer-Encoding".to_string(), "Chunked".to_string()),
+    ]);
+    assert_eq!(
+        crate::BodyReader::new(&headers, &ParseOptions::default()).err(),
+        Some(ParseError::InvalidTransferEncoding)
+    );
+}
+
+#[test]
+fn test_trailer_section() {
+    let request = |trailers: &str| {
+        let input = format!(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nTrailer: X-Checksum, Server-Timing\r\n\r\n2\r\nok\r\n0\r\n{}\r\n",
+            trailers
+        );
+        let (rest, request) = crate::parse_http(&input).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(request.body, b"ok".to_vec());
+        request
+    };
+
+    let one = request("X-Checksum: abc\r\n");
+    assert_eq!(one.trailers.get("x-checksum"), Some("abc"));
+    assert!(!one.headers.contains("X-Checksum"));
+    assert_eq!(one.validate_trailers(), Ok(()));
+
+    let multiple = request("X-Checksum: abc\r\nServer-Timing: db;dur=53\r\n");
+    assert_eq!(multiple.trailers.len(), 2);
+    assert_eq!(multiple.validate_trailers(), Ok(()));
+
+    let forbidden = request("Content-Length: 2\r\n");
+    assert_eq!(
+        forbidden.validate_trailers(),
+        Err(vec![TrailerError::Forbidden("Content-Length".to_string())])
+    );
+
+    let none = request("");
+    assert!(none.trailers.is_empty());
+    assert_eq!(none.validate_trailers(), Ok(()));
+}
+
+#[test]
+fn test_trailer() {
+    let headers = Headers::from(vec![(
+        "Trailer".to_string(),
+        "Expires, X-Checksum".to_string(),
+    )]);
+    let trailers = |name: &str| Headers::from(vec![(name.to_string(), "1".to_string())]);
+
+    assert_eq!(
+        headers.trailer(),
+        Some(vec!["Expires".to_string(), "X-Checksum".to_string()])
+    );
+    assert_eq!(Headers::new().trailer(), None);
+
+    assert_eq!(headers.validate_trailers(&trailers("x-checksum")), Ok(()));
+    assert_eq!(
+        headers.validate_trailers(&trailers("X-Other")),
+        Err(vec![TrailerError::Unannounced("X-Other".to_string())])
+    );
+    assert_eq!(
+        headers.validate_trailers(&trailers("Content-Length")),
+        Err(vec![TrailerError::Forbidden("Content-Length".to_string())])
+    );
+    assert_eq!(
+        headers.validate_trailers(&trailers("Expires")),
+        Err(vec![TrailerError::Forbidden("Expires".to_string())])
+    );
+}
@@ -0,0 +1,328 @@
+use crate::{list, parse_http_date, DateError, Headers};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while},
+    combinator::{all_consuming, map, opt},
+    sequence::{delimited, pair},
+    IResult,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// entity-tag: https://tools.ietf.org/html/rfc7232#section-2.3
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EntityTag {
+    pub weak: bool,
+    pub tag: String,
+}
+
+impl EntityTag {
+    pub fn strong(tag: &str) -> Self {
+        EntityTag {
+            weak: false,
+            tag: tag.to_owned(),
+        }
+    }
+
+    pub fn weak(tag: &str) -> Self {
+        EntityTag {
+            weak: true,
+            tag: tag.to_owned(),
+        }
+    }
+
+    /// Strong comparison: both tags have to be strong and identical.
+    /// https://tools.ietf.org/html/rfc7232#section-2.3.2
+    pub fn strong_eq(&self, other: &EntityTag) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Weak comparison: the opaque tags have to be identical.
+    pub fn weak_eq(&self, other: &EntityTag) -> bool {
+        self.tag == other.tag
+    }
+}
+
+pub(crate) fn entity_tag(input: &str) -> IResult<&str, EntityTag> {
+    pair(
+        opt(tag("W/")),
+        delimited(tag("\""), take_while(is_etagc), tag("\"")),
+    )(input)
+    .map(|(next_input, res)| {
+        (
+            next_input,
+            EntityTag {
+                weak: res.0.is_some(),
+                tag: res.1.to_owned(),
+            },
+        )
+    })
+}
+
+fn is_etagc(chr: char) -> bool {
+    chr == '!' || (chr >= '#' && chr != '\u{7f}')
+}
+
+/// The value of If-Match and If-None-Match: `*` or a list of entity-tags.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum EtagCondition {
+    Any,
+    Tags(Vec<EntityTag>),
+}
+
+impl EtagCondition {
+    /// If-Match semantics: `*` matches any current representation, otherwise one of
+    /// the tags has to match strongly, so weak tags never match.
+    pub fn matches(&self, current: &EntityTag) -> bool {
+        match self {
+            EtagCondition::Any => true,
+            EtagCondition::Tags(tags) => tags.iter().any(|t| t.strong_eq(current)),
+        }
+    }
+
+    /// If-None-Match semantics, which use the weak comparison.
+    pub fn matches_weak(&self, current: &EntityTag) -> bool {
+        match self {
+            EtagCondition::Any => true,
+            EtagCondition::Tags(tags) => tags.iter().any(|t| t.weak_eq(current)),
+        }
+    }
+}
+
+fn etag_condition(input: &str) -> IResult<&str, EtagCondition> {
+    alt((
+        map(tag("*"), |_| EtagCondition::Any),
+        map(list(entity_tag), EtagCondition::Tags),
+    ))(input)
+}
+
+/// If-Range: https://tools.ietf.org/html/rfc7233#section-3.2
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum IfRange {
+    Etag(EntityTag),
+    Date(SystemTime),
+}
+
+/// The outcome of evaluating request preconditions.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Precondition {
+    /// The request can be performed.
+    Proceed,
+    /// The request must not be performed, which is a 412 (Precondition Failed).
+    Failed,
+}
+
+// HTTP-dates have a resolution of one second
+fn whole_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl Headers {
+    /// If-Match: https://tools.ietf.org/html/rfc7232#section-3.1
+    /// An unparseable value yields None.
+    pub fn if_match(&self) -> Option<EtagCondition> {
+        self.etag_condition("If-Match")
+    }
+
+    /// If-None-Match: https://tools.ietf.org/html/rfc7232#section-3.2
+    pub fn if_none_match(&self) -> Option<EtagCondition> {
+        self.etag_condition("If-None-Match")
+    }
+
+    /// If-Unmodified-Since: https://tools.ietf.org/html/rfc7232#section-3.4
+    /// An invalid date means the header is ignored, so it yields None.
+    pub fn if_unmodified_since(&self) -> Option<SystemTime> {
+        self.get("If-Unmodified-Since")
+            .and_then(|value| parse_http_date(value).ok())
+    }
+
+    /// Fails if the resource was modified after the If-Unmodified-Since date.
+    pub fn evaluate_if_unmodified_since(&self, last_modified: SystemTime) -> Precondition {
+        match self.if_unmodified_since() {
+            Some(date) if whole_seconds(last_modified) > whole_seconds(date) => {
+                Precondition::Failed
+            }
+            _ => Precondition::Proceed,
+        }
+    }
+
+    /// Evaluates the write preconditions in the order of
+    /// https://tools.ietf.org/html/rfc7232#section-6: If-Match when present,
+    /// otherwise If-Unmodified-Since. `etag` and `last_modified` describe the
+    /// current representation, with None for an unknown validator.
+    pub fn evaluate_preconditions(
+        &self,
+        etag: Option<&EntityTag>,
+        last_modified: Option<SystemTime>,
+    ) -> Precondition {
+        if let Some(condition) = self.if_match() {
+            return match etag {
+                Some(etag) if condition.matches(etag) => Precondition::Proceed,
+                _ => Precondition::Failed,
+            };
+        }
+        match last_modified {
+            Some(last_modified) => self.evaluate_if_unmodified_since(last_modified),
+            None => Precondition::Proceed,
+        }
+    }
+
+    /// Entity-tags start with `"` or `W/`, anything else has to be an HTTP-date.
+    pub fn if_range(&self) -> Option<Result<IfRange, DateError>> {
+        self.get("If-Range").map(|value| {
+            let value = value.trim();
+            if value.starts_with('"') || value.starts_with("W/") {
+                all_consuming(entity_tag)(value)
+                    .map(|(_, etag)| IfRange::Etag(etag))
+                    .map_err(|_| DateError::Malformed(value.to_owned()))
+            } else {
+                parse_http_date(value).map(IfRange::Date)
+            }
+        })
+    }
+
+    /// Whether a Range header should be honored, given the validators of the current
+    /// representation. Without If-Range any Range is honored; with it, the validator
+    /// has to match strongly (a weak entity-tag never does) or else the whole
+    /// representation is sent.
+    pub fn honor_range(&self, etag: Option<&EntityTag>, last_modified: Option<SystemTime>) -> bool {
+        if !self.contains("Range") {
+            return false;
+        }
+        match self.if_range() {
+            None => true,
+            Some(Ok(IfRange::Etag(tag))) => etag.is_some_and(|etag| tag.strong_eq(etag)),
+            Some(Ok(IfRange::Date(date))) => last_modified
+                .is_some_and(|last_modified| whole_seconds(last_modified) == whole_seconds(date)),
+            Some(Err(_)) => false,
+        }
+    }
+
+    fn etag_condition(&self, name: &str) -> Option<EtagCondition> {
+        self.get(name)
+            .and_then(|value| all_consuming(etag_condition)(value.trim()).ok())
+            .map(|(_, res)| res)
+    }
+}
+
+#[test]
+fn test_if_match() {
+    let headers = |v: &str| Headers::from(vec![("If-Match".to_string(), v.to_string())]);
+    let current = EntityTag::strong("v2");
+
+    let condition = headers("\"v1\", \"v2\"").if_match().unwrap();
+    assert_eq!(
+        condition,
+        EtagCondition::Tags(vec![EntityTag::strong("v1"), EntityTag::strong("v2")])
+    );
+    assert!(condition.matches(&current));
+    assert!(!condition.matches(&EntityTag::weak("v2")));
+
+    let weak = headers("W/\"v2\"").if_match().unwrap();
+    assert_eq!(weak, EtagCondition::Tags(vec![EntityTag::weak("v2")]));
+    assert!(!weak.matches(&current));
+    assert!(weak.matches_weak(&current));
+
+    let any = headers("*").if_match().unwrap();
+    assert_eq!(any, EtagCondition::Any);
+    assert!(any.matches(&current));
+
+    assert_eq!(headers("v1").if_match(), None);
+    assert_eq!(Headers::new().if_match(), None);
+}
+
+#[test]
+fn test_if_unmodified_since() {
+    use std::time::Duration;
+
+    let date = "Sun, 06 Nov 1994 08:49:37 GMT";
+    let at = UNIX_EPOCH + Duration::from_secs(784111777);
+    let headers = Headers::from(vec![("If-Unmodified-Since".to_string(), date.to_string())]);
+    assert_eq!(headers.if_unmodified_since(), Some(at));
+
+    let later = at + Duration::from_secs(60);
+    let earlier = at - Duration::from_secs(60);
+    assert_eq!(
+        headers.evaluate_if_unmodified_since(later),
+        Precondition::Failed
+    );
+    assert_eq!(
+        headers.evaluate_if_unmodified_since(earlier),
+        Precondition::Proceed
+    );
+    assert_eq!(
+        headers.evaluate_preconditions(None, Some(later)),
+        Precondition::Failed
+    );
+    assert_eq!(
+        headers.evaluate_preconditions(None, None),
+        Precondition::Proceed
+    );
+
+    let with_if_match = Headers::from(vec![
+        ("If-Match".to_string(), "\"v1\"".to_string()),
+        ("If-Unmodified-Since".to_string(), date.to_string()),
+    ]);
+    let etag = EntityTag::strong("v1");
+    assert_eq!(
+        with_if_match.evaluate_preconditions(Some(&etag), Some(later)),
+        Precondition::Proceed
+    );
+    assert_eq!(
+        with_if_match.evaluate_preconditions(Some(&EntityTag::strong("v2")), Some(earlier)),
+        Precondition::Failed
+    );
+
+    let invalid = Headers::from(vec![(
+        "If-Unmodified-Since".to_string(),
+        "yesterday".to_string(),
+    )]);
+    assert_eq!(invalid.if_unmodified_since(), None);
+    assert_eq!(
+        invalid.evaluate_preconditions(None, Some(later)),
+        Precondition::Proceed
+    );
+}
+
+#[test]
+fn test_if_range() {
+    use std::time::Duration;
+
+    let headers = |v: &str| {
+        Headers::from(vec![
+            ("Range".to_string(), "bytes=0-99".to_string()),
+            ("If-Range".to_string(), v.to_string()),
+        ])
+    };
+    let etag = EntityTag::strong("v1");
+    let at = UNIX_EPOCH + Duration::from_secs(784111777);
+
+    let strong = headers("\"v1\"");
+    assert_eq!(strong.if_range(), Some(Ok(IfRange::Etag(etag.clone()))));
+    assert!(strong.honor_range(Some(&etag), None));
+    assert!(!strong.honor_range(Some(&EntityTag::strong("v2")), None));
+
+    let date = headers("Sun, 06 Nov 1994 08:49:37 GMT");
+    assert_eq!(date.if_range(), Some(Ok(IfRange::Date(at))));
+    assert!(date.honor_range(None, Some(at)));
+    assert!(!date.honor_range(None, Some(at + Duration::from_secs(1))));
+
+    let weak = headers("W/\"v1\"");
+    assert_eq!(
+        weak.if_range(),
+        Some(Ok(IfRange::Etag(EntityTag::weak("v1"))))
+    );
+    assert!(!weak.honor_range(Some(&etag), None));
+    assert!(!weak.honor_range(Some(&EntityTag::weak("v1")), None));
+
+    let malformed = headers("\"v1");
+    assert_eq!(
+        malformed.if_range(),
+        Some(Err(DateError::Malformed("\"v1".to_string())))
+    );
+    assert!(!malformed.honor_range(Some(&etag), None));
+
+    let range_only = Headers::from(vec![("Range".to_string(), "bytes=0-99".to_string())]);
+    assert!(range_only.honor_range(None, None));
+}
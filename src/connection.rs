@@ -0,0 +1,184 @@
+use crate::{list, parameter, Headers, Parameters, Request, Version};
+use nom::combinator::all_consuming;
+use std::time::Duration;
+
+/// Fields that only concern a single connection and must not be forwarded by
+/// proxies: https://tools.ietf.org/html/rfc7230#section-6.1
+const HOP_BY_HOP: [&str; 9] = [
+    "Connection",
+    "Keep-Alive",
+    "Proxy-Authenticate",
+    "Proxy-Authorization",
+    "Proxy-Connection",
+    "TE",
+    "Trailer",
+    "Transfer-Encoding",
+    "Upgrade",
+];
+
+/// Keep-Alive: https://tools.ietf.org/html/draft-thomson-hybi-http-timeout-03#section-2
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct KeepAlive {
+    /// How long an idle connection is kept open.
+    pub timeout: Option<Duration>,
+    /// How many more requests the connection will accept.
+    pub max: Option<u32>,
+    /// Parameters other than `timeout` and `max`.
+    pub extensions: Parameters,
+}
+
+impl Headers {
+    /// Connection: https://tools.ietf.org/html/rfc7230#section-6.1
+    pub fn connection(&self) -> Option<Vec<String>> {
+        self.names_list("Connection")
+    }
+
+    /// Whether the Connection header lists the given option, case-insensitively.
+    pub fn has_connection_option(&self, option: &str) -> bool {
+        self.connection()
+            .is_some_and(|options| options.iter().any(|o| o.eq_ignore_ascii_case(option)))
+    }
+
+    /// Removes the hop-by-hop fields before forwarding a message, including the
+    /// ones named as options in the Connection header.
+    pub fn strip_hop_by_hop(&mut self) {
+        for option in self.connection().unwrap_or_default() {
+            self.remove(&option);
+        }
+        for name in HOP_BY_HOP.iter() {
+            self.remove(name);
+        }
+    }
+
+    /// Keep-Alive parameters in any order. Values that are not a number are
+    /// ignored for `timeout` and `max`, and field lines that fail to parse are
+    /// skipped.
+    pub fn keep_alive(&self) -> Option<KeepAlive> {
+        if !self.contains("Keep-Alive") {
+            return None;
+        }
+        let mut keep_alive = KeepAlive::default();
+        let params = self
+            .get_all("Keep-Alive")
+            .filter_map(|value| all_consuming(list(parameter))(value).ok())
+            .flat_map(|(_, res)| res);
+        for (name, value) in params {
+            match name.to_ascii_lowercase().as_str() {
+                "timeout" => keep_alive.timeout = value.parse().ok().map(Duration::from_secs),
+                "max" => keep_alive.max = value.parse().ok(),
+                _ => keep_alive.extensions.push((name, value)),
+            }
+        }
+        Some(keep_alive)
+    }
+}
+
+impl Request {
+    /// Whether the connection stays open after this request:
+    /// https://tools.ietf.org/html/rfc7230#section-6.3
+    /// HTTP/1.1 connections persist unless the client sends `Connection: close`,
+    /// HTTP/1.0 ones only with `Connection: keep-alive`. The Keep-Alive parameters
+    /// are returned for a persistent connection, empty if the header is absent.
+    pub fn keep_alive(&self) -> Option<KeepAlive> {
+        let persistent = match self.request_line.version {
+            Version::HTTP11 => !self.headers.has_connection_option("close"),
+            Version::HTTP10 => self.headers.has_connection_option("keep-alive"),
+        };
+        if persistent {
+            Some(self.headers.keep_alive().unwrap_or_default())
+        } else {
+            None
+        }
+    }
+}
+
+#[test]
+fn test_keep_alive() {
+    let keep_alive = |value: &str| {
+        Headers::from(vec![("Keep-Alive".to_string(), value.to_string())]).keep_alive()
+    };
+
+    assert_eq!(
+        keep_alive("timeout=5, max=100"),
+        Some(KeepAlive {
+            timeout: Some(Duration::from_secs(5)),
+            max: Some(100),
+            extensions: vec![],
+        })
+    );
+    assert_eq!(
+        keep_alive("timeout=5"),
+        Some(KeepAlive {
+            timeout: Some(Duration::from_secs(5)),
+            max: None,
+            extensions: vec![],
+        })
+    );
+    assert_eq!(
+        keep_alive("max=10, foo=bar, timeout=2"),
+        Some(KeepAlive {
+            timeout: Some(Duration::from_secs(2)),
+            max: Some(10),
+            extensions: vec![("foo".to_string(), "bar".to_string())],
+        })
+    );
+    assert_eq!(
+        keep_alive("timeout=soon, max=3"),
+        Some(KeepAlive {
+            timeout: None,
+            max: Some(3),
+            extensions: vec![],
+        })
+    );
+    assert_eq!(Headers::new().keep_alive(), None);
+}
+
+#[test]
+fn test_strip_hop_by_hop() {
+    let mut headers = Headers::from(vec![
+        ("Host".to_string(), "example.org".to_string()),
+        (
+            "Connection".to_string(),
+            "keep-alive, X-Session".to_string(),
+        ),
+        ("Keep-Alive".to_string(), "timeout=5".to_string()),
+        ("X-Session".to_string(), "abc".to_string()),
+        ("Proxy-Authorization".to_string(), "Basic YTpi".to_string()),
+        (
+            "Proxy-Authenticate".to_string(),
+            "Basic realm=\"proxy\"".to_string(),
+        ),
+        ("Authorization".to_string(), "Bearer token".to_string()),
+    ]);
+    headers.strip_hop_by_hop();
+    assert_eq!(
+        headers,
+        Headers::from(vec![
+            ("Host".to_string(), "example.org".to_string()),
+            ("Authorization".to_string(), "Bearer token".to_string()),
+        ])
+    );
+}
+
+#[test]
+fn test_request_keep_alive() {
+    let request = |i| crate::parse_http(i).unwrap().1;
+
+    assert_eq!(
+        request("GET / HTTP/1.1\r\nKeep-Alive: timeout=5\r\n\r\n").keep_alive(),
+        Some(KeepAlive {
+            timeout: Some(Duration::from_secs(5)),
+            max: None,
+            extensions: vec![],
+        })
+    );
+    assert_eq!(
+        request("GET / HTTP/1.1\r\nConnection: Close\r\n\r\n").keep_alive(),
+        None
+    );
+    assert_eq!(request("GET / HTTP/1.0\r\n\r\n").keep_alive(), None);
+    assert_eq!(
+        request("GET / HTTP/1.0\r\nConnection: keep-alive\r\n\r\n").keep_alive(),
+        Some(KeepAlive::default())
+    );
+}
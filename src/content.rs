@@ -0,0 +1,466 @@
+use crate::headers::{parse_value, split_list};
+use crate::{
+    parameters, token, Headers, Parameters, ParseError, ParseMode, ParseOptions, Position, Request,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    combinator::{map, map_res, verify},
+    error::ErrorKind,
+    sequence::{pair, preceded, separated_pair, terminated, tuple},
+    IResult,
+};
+
+/// content-coding: https://tools.ietf.org/html/rfc7231#section-3.1.2.1
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ContentCoding {
+    Gzip,
+    Deflate,
+    Br,
+    Zstd,
+    Identity,
+    Other(String),
+}
+
+impl From<&str> for ContentCoding {
+    fn from(i: &str) -> Self {
+        match i.to_lowercase().as_str() {
+            "gzip" | "x-gzip" => ContentCoding::Gzip,
+            "deflate" => ContentCoding::Deflate,
+            "br" => ContentCoding::Br,
+            "zstd" => ContentCoding::Zstd,
+            "identity" => ContentCoding::Identity,
+            _ => ContentCoding::Other(i.to_owned()),
+        }
+    }
+}
+
+impl ContentCoding {
+    pub fn is_identity(&self) -> bool {
+        *self == ContentCoding::Identity
+    }
+}
+
+impl Headers {
+    /// Content-Encoding: https://tools.ietf.org/html/rfc7231#section-3.1.2.2
+    /// The codings are returned in the order they were applied.
+    pub fn content_encoding(&self) -> Option<Vec<ContentCoding>> {
+        if !self.contains("Content-Encoding") {
+            return None;
+        }
+        Some(
+            self.get_all("Content-Encoding")
+                .flat_map(split_list)
+                .map(ContentCoding::from)
+                .collect(),
+        )
+    }
+
+    /// True if the payload carries no content coding besides identity.
+    pub fn is_identity_encoded(&self) -> bool {
+        self.content_encoding()
+            .is_none_or(|codings| codings.iter().all(ContentCoding::is_identity))
+    }
+}
+
+/// media-type: https://tools.ietf.org/html/rfc7231#section-3.1.1.1
+/// Type, subtype and parameter names are case-insensitive and kept in lowercase.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MediaType {
+    pub type_: String,
+    pub subtype: String,
+    pub params: Parameters,
+}
+
+impl MediaType {
+    /// The value of the first parameter called `name`, like `charset` or `boundary`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether this is `type/subtype`, ignoring the parameters.
+    pub fn is(&self, type_: &str, subtype: &str) -> bool {
+        self.type_.eq_ignore_ascii_case(type_) && self.subtype.eq_ignore_ascii_case(subtype)
+    }
+}
+
+pub(crate) fn media_type(input: &str) -> IResult<&str, MediaType> {
+    tuple((separated_pair(token, tag("/"), token), parameters))(input).map(
+        |(next_input, ((type_, subtype), params))| {
+            (
+                next_input,
+                MediaType {
+                    type_: type_.to_ascii_lowercase(),
+                    subtype: subtype.to_ascii_lowercase(),
+                    params: params
+                        .into_iter()
+                        .map(|(name, value)| (name.to_ascii_lowercase(), value))
+                        .collect(),
+                },
+            )
+        },
+    )
+}
+
+/// Content-Disposition: https://tools.ietf.org/html/rfc6266#section-4.1
+/// The type and parameter names are case-insensitive and kept in lowercase.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ContentDisposition {
+    pub disposition_type: String,
+    pub params: Parameters,
+}
+
+impl ContentDisposition {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The field name of a multipart/form-data part.
+    pub fn name(&self) -> Option<&str> {
+        self.param("name")
+    }
+
+    pub fn filename(&self) -> Option<&str> {
+        self.param("filename")
+    }
+}
+
+pub(crate) fn content_disposition(input: &str) -> IResult<&str, ContentDisposition> {
+    pair(token, parameters)(input).map(|(next_input, (disposition_type, params))| {
+        (
+            next_input,
+            ContentDisposition {
+                disposition_type: disposition_type.to_ascii_lowercase(),
+                params: params
+                    .into_iter()
+                    .map(|(name, value)| (name.to_ascii_lowercase(), value))
+                    .collect(),
+            },
+        )
+    })
+}
+
+/// Content-Range: https://tools.ietf.org/html/rfc7233#section-4.2
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ContentRange {
+    /// `bytes 0-499/1234`, or `bytes 0-499/*` when the total length is unknown.
+    Satisfied {
+        unit: String,
+        first: u64,
+        last: u64,
+        total: Option<u64>,
+    },
+    /// `bytes */1234`, sent with a 416 response.
+    Unsatisfied { unit: String, total: u64 },
+}
+
+pub(crate) fn content_range(input: &str) -> IResult<&str, ContentRange> {
+    let (input, unit) = terminated(token, tag(" "))(input)?;
+    alt((
+        map(preceded(tag("*/"), number), move |total| {
+            ContentRange::Unsatisfied {
+                unit: unit.to_owned(),
+                total,
+            }
+        }),
+        map(
+            verify(
+                tuple((
+                    separated_pair(number, tag("-"), number),
+                    preceded(tag("/"), alt((map(number, Some), map(tag("*"), |_| None)))),
+                )),
+                |((first, last), total)| first <= last && total.is_none_or(|total| last < &total),
+            ),
+            move |((first, last), total)| ContentRange::Satisfied {
+                unit: unit.to_owned(),
+                first,
+                last,
+                total,
+            },
+        ),
+    ))(input)
+}
+
+fn number(input: &str) -> IResult<&str, u64> {
+    map_res(take_while1(|chr: char| chr.is_ascii_digit()), str::parse)(input)
+}
+
+/// An entry of Accept-Ranges: https://tools.ietf.org/html/rfc7233#section-2.3
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AcceptRanges {
+    Bytes,
+    /// `none`: the server does not support range requests.
+    None,
+    Other(String),
+}
+
+impl From<&str> for AcceptRanges {
+    fn from(i: &str) -> Self {
+        match i.to_lowercase().as_str() {
+            "bytes" => AcceptRanges::Bytes,
+            "none" => AcceptRanges::None,
+            _ => AcceptRanges::Other(i.to_owned()),
+        }
+    }
+}
+
+impl Headers {
+    /// Content-Type: https://tools.ietf.org/html/rfc7231#section-3.1.1.5
+    pub fn content_type(&self) -> Option<Result<MediaType, ParseError>> {
+        self.get("Content-Type")
+            .map(|value| parse_value(value, media_type))
+    }
+
+    /// Content-Length: https://tools.ietf.org/html/rfc7230#section-3.3.2
+    /// Repeated values, in several field lines or as a list in one, are
+    /// collapsed if they are all the same number, otherwise the length is
+    /// ambiguous and `ParseError::DuplicateContentLength` is returned.
+    pub fn content_length(&self) -> Option<Result<u64, ParseError>> {
+        if !self.contains("Content-Length") {
+            return None;
+        }
+        let mut length = None;
+        for value in self.get_all("Content-Length").flat_map(split_list) {
+            let value = match parse_value(value, number) {
+                Ok(value) => value,
+                Err(e) => return Some(Err(e)),
+            };
+            match length {
+                Some(length) if length != value => {
+                    return Some(Err(ParseError::DuplicateContentLength))
+                }
+                _ => length = Some(value),
+            }
+        }
+        Some(length.ok_or(ParseError::Syntax {
+            kind: ErrorKind::Digit,
+            position: Position::new("", 0),
+        }))
+    }
+
+    pub fn content_disposition(&self) -> Option<Result<ContentDisposition, ParseError>> {
+        self.get("Content-Disposition")
+            .map(|value| parse_value(value, content_disposition))
+    }
+
+    pub fn content_range(&self) -> Option<Result<ContentRange, ParseError>> {
+        self.get("Content-Range")
+            .map(|value| parse_value(value, content_range))
+    }
+
+    /// Accept-Ranges: https://tools.ietf.org/html/rfc7233#section-2.3
+    pub fn accept_ranges(&self) -> Option<Vec<AcceptRanges>> {
+        if !self.contains("Accept-Ranges") {
+            return None;
+        }
+        Some(
+            self.get_all("Accept-Ranges")
+                .flat_map(split_list)
+                .map(AcceptRanges::from)
+                .collect(),
+        )
+    }
+}
+
+// Request smuggling relies on recipients disagreeing about the length of a
+// body: https://tools.ietf.org/html/rfc7230#section-9.5
+// Strict mode rejects repeated values even if they are the same.
+pub(crate) fn validate_content_length(
+    headers: &Headers,
+    options: &ParseOptions,
+) -> Result<(), ParseError> {
+    if let Some(Err(e)) = headers.content_length() {
+        return Err(e);
+    }
+    if options.mode == ParseMode::Strict
+        && headers
+            .get_all("Content-Length")
+            .flat_map(split_list)
+            .count()
+            > 1
+    {
+        return Err(ParseError::DuplicateContentLength);
+    }
+    Ok(())
+}
+
+impl Request {
+    /// The parsed Content-Type of the body, see `Headers::content_type`.
+    pub fn content_type(&self) -> Option<Result<MediaType, ParseError>> {
+        self.headers.content_type()
+    }
+}
+
+#[test]
+fn test_content_type() {
+    let request = crate::parse_http(
+        "POST /api HTTP/1.1\r\ncontent-type: Application/JSON; Charset=\"utf-8\"\r\n\r\n",
+    )
+    .unwrap()
+    .1;
+    let media_type = request.content_type().unwrap().unwrap();
+    assert_eq!(
+        media_type,
+        MediaType {
+            type_: "application".to_string(),
+            subtype: "json".to_string(),
+            params: vec![("charset".to_string(), "utf-8".to_string())],
+        }
+    );
+    assert!(media_type.is("application", "json"));
+
+    let request = crate::parse_http(
+        "POST /upload HTTP/1.1\r\nContent-Type: multipart/form-data; boundary=----abc123\r\n\r\n",
+    )
+    .unwrap()
+    .1;
+    let media_type = request.content_type().unwrap().unwrap();
+    assert!(media_type.is("multipart", "form-data"));
+    assert_eq!(media_type.param("Boundary"), Some("----abc123"));
+
+    let request = crate::parse_http("GET / HTTP/1.1\r\n\r\n").unwrap().1;
+    assert_eq!(request.content_type(), None);
+    assert!(
+        Headers::from(vec![("Content-Type".to_string(), "json".to_string())])
+            .content_type()
+            .unwrap()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_duplicate_content_length() {
+    let parse = |content_length: &str, options: &ParseOptions| {
+        let input = format!("POST / HTTP/1.1\r\n{}\r\n\r\n0123456789", content_length);
+        crate::parse_request(&input, options).map(|request| request.body.len())
+    };
+    let lenient = ParseOptions::default();
+    let strict = ParseOptions::strict();
+
+    let identical = "Content-Length: 10\r\nContent-Length: 10";
+    assert_eq!(parse(identical, &lenient), Ok(10));
+    assert_eq!(
+        parse(identical, &strict),
+        Err(ParseError::DuplicateContentLength)
+    );
+
+    let differing = "Content-Length: 10\r\nContent-Length: 5";
+    assert_eq!(
+        parse(differing, &lenient),
+        Err(ParseError::DuplicateContentLength)
+    );
+    assert_eq!(
+        parse("Content-Length: 5, 10", &lenient),
+        Err(ParseError::DuplicateContentLength)
+    );
+    assert_eq!(parse("Content-Length: 10, 10", &lenient), Ok(10));
+    assert_eq!(
+        parse("Content-Length: 10, 10", &strict),
+        Err(ParseError::DuplicateContentLength)
+    );
+
+    assert_eq!(parse("Content-Length: 007", &strict), Ok(7));
+    assert_eq!(parse("Content-Length: 010, 10", &lenient), Ok(10));
+    assert!(parse("Content-Length: -1", &lenient).is_err());
+}
+
+#[test]
+fn test_content_range() {
+    let headers = |v: &str| Headers::from(vec![("Content-Range".to_string(), v.to_string())]);
+
+    assert_eq!(
+        headers("bytes 0-499/1234").content_range(),
+        Some(Ok(ContentRange::Satisfied {
+            unit: "bytes".to_string(),
+            first: 0,
+            last: 499,
+            total: Some(1234)
+        }))
+    );
+    assert_eq!(
+        headers("bytes */1234").content_range(),
+        Some(Ok(ContentRange::Unsatisfied {
+            unit: "bytes".to_string(),
+            total: 1234
+        }))
+    );
+    assert_eq!(
+        headers("bytes 0-499/*").content_range(),
+        Some(Ok(ContentRange::Satisfied {
+            unit: "bytes".to_string(),
+            first: 0,
+            last: 499,
+            total: None
+        }))
+    );
+    assert!(matches!(
+        headers("bytes 500-499/1234").content_range(),
+        Some(Err(ParseError::Syntax { .. }))
+    ));
+    assert!(matches!(
+        headers("bytes 0-1234/1234").content_range(),
+        Some(Err(ParseError::Syntax { .. }))
+    ));
+}
+
+#[test]
+fn test_content_encoding() {
+    let headers = |v: &str| Headers::from(vec![("content-encoding".to_string(), v.to_string())]);
+
+    assert_eq!(
+        headers("gzip").content_encoding(),
+        Some(vec![ContentCoding::Gzip])
+    );
+    assert_eq!(
+        headers("BR, gzip,zstd").content_encoding(),
+        Some(vec![
+            ContentCoding::Br,
+            ContentCoding::Gzip,
+            ContentCoding::Zstd
+        ])
+    );
+    assert_eq!(
+        headers("compress").content_encoding(),
+        Some(vec![ContentCoding::Other("compress".to_string())])
+    );
+    assert_eq!(
+        headers("identity").content_encoding(),
+        Some(vec![ContentCoding::Identity])
+    );
+    assert!(headers("identity").is_identity_encoded());
+    assert!(!headers("deflate").is_identity_encoded());
+    assert!(Headers::new().is_identity_encoded());
+    assert_eq!(Headers::new().content_encoding(), None);
+}
+
+#[test]
+fn test_accept_ranges() {
+    let headers = |v: &str| Headers::from(vec![("Accept-Ranges".to_string(), v.to_string())]);
+
+    assert_eq!(
+        headers("bytes").accept_ranges(),
+        Some(vec![AcceptRanges::Bytes])
+    );
+    assert_eq!(
+        headers("None").accept_ranges(),
+        Some(vec![AcceptRanges::None])
+    );
+    assert_eq!(
+        headers("pages").accept_ranges(),
+        Some(vec![AcceptRanges::Other("pages".to_string())])
+    );
+    assert_eq!(
+        headers("BYTES, pages").accept_ranges(),
+        Some(vec![
+            AcceptRanges::Bytes,
+            AcceptRanges::Other("pages".to_string())
+        ])
+    );
+    assert_eq!(Headers::new().accept_ranges(), None);
+}
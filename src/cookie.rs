@@ -0,0 +1,198 @@
+use crate::{parse_http_date, Headers};
+use std::time::SystemTime;
+
+/// The SameSite attribute: https://tools.ietf.org/html/draft-ietf-httpbis-rfc6265bis-11#section-4.1.2.7
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+/// A cookie set by a Set-Cookie header: https://tools.ietf.org/html/rfc6265#section-4.1
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct SetCookie {
+    pub name: String,
+    pub value: String,
+    pub path: Option<String>,
+    /// Without a leading dot and in lowercase.
+    pub domain: Option<String>,
+    /// In seconds, zero or less expires the cookie right away.
+    pub max_age: Option<i64>,
+    pub expires: Option<SystemTime>,
+    pub secure: bool,
+    pub http_only: bool,
+    pub same_site: Option<SameSite>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CookieError {
+    /// The value does not start with a `name=value` pair.
+    Invalid(String),
+}
+
+/// Parses a Set-Cookie value the way user agents do:
+/// https://tools.ietf.org/html/rfc6265#section-5.2
+/// Attribute names are case-insensitive, attributes with invalid values and
+/// unknown attributes are ignored, and the last occurrence of an attribute wins.
+pub fn parse_set_cookie(value: &str) -> Result<SetCookie, CookieError> {
+    let mut attributes = value.split(';');
+    let pair = attributes.next().unwrap_or_default();
+    let (name, cookie_value) = match pair.find('=') {
+        Some(i) if !pair[..i].trim().is_empty() => (pair[..i].trim(), pair[i + 1..].trim()),
+        _ => return Err(CookieError::Invalid(value.to_owned())),
+    };
+    let mut cookie = SetCookie {
+        name: name.to_owned(),
+        value: cookie_value.to_owned(),
+        ..SetCookie::default()
+    };
+    for attribute in attributes {
+        let (name, value) = match attribute.find('=') {
+            Some(i) => (attribute[..i].trim(), attribute[i + 1..].trim()),
+            None => (attribute.trim(), ""),
+        };
+        match name.to_ascii_lowercase().as_str() {
+            "expires" => {
+                if let Ok(expires) = parse_http_date(value) {
+                    cookie.expires = Some(expires);
+                }
+            }
+            "max-age" => {
+                let digits = value.strip_prefix('-').unwrap_or(value);
+                if !digits.is_empty() && digits.chars().all(|chr| chr.is_ascii_digit()) {
+                    // too many digits saturate, keeping the sign
+                    let saturated = if value.starts_with('-') {
+                        i64::MIN
+                    } else {
+                        i64::MAX
+                    };
+                    cookie.max_age = Some(value.parse().unwrap_or(saturated));
+                }
+            }
+            "domain" if !value.is_empty() => {
+                let domain = value.strip_prefix('.').unwrap_or(value);
+                cookie.domain = Some(domain.to_ascii_lowercase());
+            }
+            "path" => {
+                cookie.path = if value.starts_with('/') {
+                    Some(value.to_owned())
+                } else {
+                    None
+                }
+            }
+            "secure" => cookie.secure = true,
+            "httponly" => cookie.http_only = true,
+            "samesite" => {
+                cookie.same_site = match value.to_ascii_lowercase().as_str() {
+                    "strict" => Some(SameSite::Strict),
+                    "lax" => Some(SameSite::Lax),
+                    "none" => Some(SameSite::None),
+                    _ => cookie.same_site,
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(cookie)
+}
+
+impl Headers {
+    /// Cookie: https://tools.ietf.org/html/rfc6265#section-5.4
+    /// The `name=value` pairs sent by the client, pairs without `=` are skipped.
+    pub fn cookies(&self) -> Option<Vec<(String, String)>> {
+        if !self.contains("Cookie") {
+            return None;
+        }
+        Some(
+            self.get_all("Cookie")
+                .flat_map(|value| value.split(';'))
+                .filter_map(|pair| {
+                    let i = pair.find('=')?;
+                    Some((pair[..i].trim().to_owned(), pair[i + 1..].trim().to_owned()))
+                })
+                .collect(),
+        )
+    }
+
+    /// Set-Cookie: https://tools.ietf.org/html/rfc6265#section-4.1
+    /// Every field line sets one cookie, lines that fail to parse are skipped.
+    pub fn set_cookies(&self) -> Option<Vec<SetCookie>> {
+        if !self.contains("Set-Cookie") {
+            return None;
+        }
+        Some(
+            self.get_all("Set-Cookie")
+                .filter_map(|value| parse_set_cookie(value).ok())
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_parse_set_cookie() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    assert_eq!(
+        parse_set_cookie(
+            "sid=31d4d96e407aad42; path=/; Domain=.Example.com; Max-Age=3600; \
+             expires=Wed, 21 Oct 2015 07:28:00 GMT; Secure; HTTPONLY; SameSite=Lax; Foo=bar"
+        ),
+        Ok(SetCookie {
+            name: "sid".to_string(),
+            value: "31d4d96e407aad42".to_string(),
+            path: Some("/".to_string()),
+            domain: Some("example.com".to_string()),
+            max_age: Some(3600),
+            expires: Some(UNIX_EPOCH + Duration::from_secs(1445412480)),
+            secure: true,
+            http_only: true,
+            same_site: Some(SameSite::Lax),
+        })
+    );
+
+    let cookie = parse_set_cookie("lang=; Max-Age=-1; Max-Age=soon; Path=docs").unwrap();
+    assert_eq!(cookie.value, "");
+    assert_eq!(cookie.max_age, Some(-1));
+    assert_eq!(cookie.path, None);
+    let max_age = |value| parse_set_cookie(value).unwrap().max_age;
+    assert_eq!(
+        max_age("a=1; Max-Age=-99999999999999999999999"),
+        Some(i64::MIN)
+    );
+    assert_eq!(
+        max_age("a=1; Max-Age=99999999999999999999999"),
+        Some(i64::MAX)
+    );
+    assert_eq!(
+        parse_set_cookie("a=1; SameSite=none").unwrap().same_site,
+        Some(SameSite::None)
+    );
+    assert_eq!(
+        parse_set_cookie("=value; Secure"),
+        Err(CookieError::Invalid("=value; Secure".to_string()))
+    );
+
+    let headers = Headers::from(vec![
+        ("Set-Cookie".to_string(), "a=1; SameSite=Strict".to_string()),
+        ("set-cookie".to_string(), "invalid".to_string()),
+        ("Set-Cookie".to_string(), "b=2".to_string()),
+    ]);
+    let cookies = headers.set_cookies().unwrap();
+    assert_eq!(cookies.len(), 2);
+    assert_eq!(cookies[0].same_site, Some(SameSite::Strict));
+    assert_eq!(cookies[1].name, "b");
+
+    let headers = Headers::from(vec![(
+        "Cookie".to_string(),
+        "sid=abc; lang=en-US".to_string(),
+    )]);
+    assert_eq!(
+        headers.cookies(),
+        Some(vec![
+            ("sid".to_string(), "abc".to_string()),
+            ("lang".to_string(), "en-US".to_string()),
+        ])
+    );
+    assert_eq!(Headers::new().cookies(), None);
+}
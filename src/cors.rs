@@ -0,0 +1,328 @@
+use crate::cache::delta_seconds;
+use crate::headers::{parse_value, split_list};
+use crate::{
+    host_or_ip, port, request_method, scheme, Headers, Host, Method, ParseError, Request, Scheme,
+    URI,
+};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    combinator::{map, opt},
+    multi::separated_list1,
+    sequence::tuple,
+    IResult,
+};
+use std::fmt;
+use std::time::Duration;
+
+/// An origin as sent in the Origin header: https://tools.ietf.org/html/rfc6454#section-7
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Origin {
+    /// The opaque `null` origin, sent for example by sandboxed documents.
+    Null,
+    Tuple(Scheme, Host, Option<u16>),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Origin::Null => write!(f, "null"),
+            Origin::Tuple(scheme, host, None) => write!(f, "{}://{}", scheme, host),
+            Origin::Tuple(scheme, host, Some(port)) => write!(f, "{}://{}:{}", scheme, host, port),
+        }
+    }
+}
+
+impl Origin {
+    /// Compares against the origin of a URI: schemes have to be the same, hosts
+    /// are compared case-insensitively and a missing port means the scheme's
+    /// default port. The `null` origin is never the same as any other origin.
+    pub fn is_same_origin(&self, uri: &URI) -> bool {
+        match (self, uri.scheme.as_ref(), uri.host.as_ref()) {
+            (Origin::Tuple(scheme, host, port), Some(uri_scheme), Some(uri_host)) => {
+                let default_port = scheme.default_port();
+                scheme == uri_scheme
+                    && host.eq_ignore_ascii_case(uri_host)
+                    && port.or(default_port) == uri.port.or(default_port)
+            }
+            _ => false,
+        }
+    }
+}
+
+pub(crate) fn origin(input: &str) -> IResult<&str, Origin> {
+    alt((
+        map(tag("null"), |_| Origin::Null),
+        map(
+            tuple((scheme, host_or_ip, opt(port))),
+            |(scheme, host, port)| Origin::Tuple(scheme, host, port),
+        ),
+    ))(input)
+}
+
+/// Access-Control-Allow-Origin: https://fetch.spec.whatwg.org/#http-access-control-allow-origin
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AllowOrigin {
+    Any,
+    Origin(Origin),
+}
+
+pub(crate) fn allow_origin(input: &str) -> IResult<&str, AllowOrigin> {
+    alt((
+        map(tag("*"), |_| AllowOrigin::Any),
+        map(origin, AllowOrigin::Origin),
+    ))(input)
+}
+
+impl Headers {
+    /// Origin: https://tools.ietf.org/html/rfc6454#section-7
+    pub fn origin(&self) -> Option<Result<Origin, ParseError>> {
+        self.get("Origin").map(|value| parse_value(value, origin))
+    }
+
+    /// The origin-list of https://tools.ietf.org/html/rfc6454#section-7.1, which
+    /// allows several space-separated origins in one header.
+    pub fn origins(&self) -> Option<Result<Vec<Origin>, ParseError>> {
+        self.get("Origin")
+            .map(|value| parse_value(value, separated_list1(tag(" "), origin)))
+    }
+
+    pub fn access_control_request_method(&self) -> Option<Result<Method, ParseError>> {
+        self.get("Access-Control-Request-Method")
+            .map(|value| parse_value(value, request_method))
+    }
+
+    pub fn access_control_request_headers(&self) -> Option<Vec<String>> {
+        self.names_list("Access-Control-Request-Headers")
+    }
+
+    pub fn access_control_allow_origin(&self) -> Option<Result<AllowOrigin, ParseError>> {
+        self.get("Access-Control-Allow-Origin")
+            .map(|value| parse_value(value, allow_origin))
+    }
+
+    pub fn access_control_allow_methods(&self) -> Option<Vec<Method>> {
+        self.names_list("Access-Control-Allow-Methods")
+            .map(|names| {
+                names
+                    .iter()
+                    .map(|name| Method::from(name.as_str()))
+                    .collect()
+            })
+    }
+
+    pub fn access_control_allow_headers(&self) -> Option<Vec<String>> {
+        self.names_list("Access-Control-Allow-Headers")
+    }
+
+    /// An invalid value is ignored, so it yields None like a missing header.
+    pub fn access_control_max_age(&self) -> Option<Duration> {
+        self.get("Access-Control-Max-Age")
+            .and_then(|value| delta_seconds(value.trim()))
+    }
+
+    /// The only valid value is a case-sensitive `true`.
+    pub fn access_control_allow_credentials(&self) -> bool {
+        self.get("Access-Control-Allow-Credentials")
+            .is_some_and(|value| value.trim() == "true")
+    }
+
+    /// The CORS check of https://fetch.spec.whatwg.org/#cors-check on these response
+    /// headers: the wildcard only allows requests without credentials, credentialed
+    /// requests need the exact origin and `Access-Control-Allow-Credentials: true`.
+    pub fn cors_allows_origin(&self, origin: &Origin, with_credentials: bool) -> bool {
+        match self.access_control_allow_origin() {
+            Some(Ok(AllowOrigin::Any)) => !with_credentials,
+            Some(Ok(AllowOrigin::Origin(allowed))) => {
+                allowed.to_string() == origin.to_string()
+                    && (!with_credentials || self.access_control_allow_credentials())
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn names_list(&self, name: &str) -> Option<Vec<String>> {
+        if !self.contains(name) {
+            return None;
+        }
+        Some(
+            self.get_all(name)
+                .flat_map(split_list)
+                .map(|name| name.to_owned())
+                .collect(),
+        )
+    }
+}
+
+impl Request {
+    /// A CORS preflight is an OPTIONS request with Origin and
+    /// Access-Control-Request-Method headers.
+    pub fn is_cors_preflight(&self) -> bool {
+        self.request_line.method == Method::OPTIONS
+            && self.headers.contains("Origin")
+            && self.headers.contains("Access-Control-Request-Method")
+    }
+}
+
+#[test]
+fn test_cors_preflight() {
+    let (_, request) = crate::parse_http(
+        "OPTIONS /resource HTTP/1.1\r\n\
+         Host: api.example.org\r\n\
+         Origin: https://app.example.org\r\n\
+         Access-Control-Request-Method: PUT\r\n\
+         Access-Control-Request-Headers: content-type, x-request-id\r\n\r\n",
+    )
+    .unwrap();
+    let app = Origin::Tuple(
+        Scheme::HTTPS,
+        Host::HOST("app.example.org".to_string()),
+        None,
+    );
+
+    assert!(request.is_cors_preflight());
+    assert_eq!(request.headers.origin(), Some(Ok(app.clone())));
+    assert_eq!(
+        request.headers.access_control_request_method(),
+        Some(Ok(Method::PUT))
+    );
+    assert_eq!(
+        request.headers.access_control_request_headers(),
+        Some(vec!["content-type".to_string(), "x-request-id".to_string()])
+    );
+
+    let (_, response) = crate::headers::<nom::error::Error<&str>>(
+        "Access-Control-Allow-Origin: https://app.example.org\r\n\
+         Access-Control-Allow-Methods: GET, PUT\r\n\
+         Access-Control-Allow-Headers: Content-Type, X-Request-Id\r\n\
+         Access-Control-Max-Age: 600\r\n",
+    )
+    .unwrap();
+    assert_eq!(
+        response.access_control_allow_origin(),
+        Some(Ok(AllowOrigin::Origin(app.clone())))
+    );
+    assert_eq!(
+        response.access_control_allow_methods(),
+        Some(vec![Method::GET, Method::PUT])
+    );
+    assert_eq!(
+        response.access_control_allow_headers(),
+        Some(vec!["Content-Type".to_string(), "X-Request-Id".to_string()])
+    );
+    assert_eq!(
+        response.access_control_max_age(),
+        Some(Duration::from_secs(600))
+    );
+    assert!(!response.access_control_allow_credentials());
+
+    let (_, simple) = crate::parse_http("GET / HTTP/1.1\r\nOrigin: null\r\n\r\n").unwrap();
+    assert!(!simple.is_cors_preflight());
+    assert_eq!(simple.headers.origin(), Some(Ok(Origin::Null)));
+}
+
+#[test]
+fn test_origin() {
+    assert_eq!(
+        origin("https://example.com"),
+        Ok((
+            "",
+            Origin::Tuple(Scheme::HTTPS, Host::HOST("example.com".to_string()), None)
+        ))
+    );
+    assert_eq!(
+        origin("http://localhost:3000"),
+        Ok((
+            "",
+            Origin::Tuple(
+                Scheme::HTTP,
+                Host::HOST("localhost".to_string()),
+                Some(3000)
+            )
+        ))
+    );
+    assert_eq!(origin("null"), Ok(("", Origin::Null)));
+}
+
+#[test]
+fn test_same_origin() {
+    let origin = |i| parse_value(i, origin).unwrap();
+    let uri = |i| crate::URI::parse(i).unwrap();
+
+    let app = origin("https://app.example.org:8443");
+    assert_eq!(
+        app,
+        Origin::Tuple(
+            Scheme::HTTPS,
+            Host::HOST("app.example.org".to_string()),
+            Some(8443)
+        )
+    );
+    assert!(app.is_same_origin(&uri("https://APP.example.org:8443/path?q=1")));
+    assert!(!app.is_same_origin(&uri("https://app.example.org/path")));
+
+    assert_eq!(origin("null"), Origin::Null);
+    assert!(!Origin::Null.is_same_origin(&uri("https://app.example.org/")));
+    assert!(!Origin::Null.is_same_origin(&uri("/path")));
+
+    let default_port = origin("https://example.org");
+    assert!(default_port.is_same_origin(&uri("https://example.org:443/")));
+    assert!(origin("http://example.org:80").is_same_origin(&uri("http://example.org/")));
+
+    assert!(!origin("http://example.org").is_same_origin(&uri("https://example.org/")));
+
+    let headers = Headers::from(vec![(
+        "Origin".to_string(),
+        "https://a.example.org http://b.example.org:8080".to_string(),
+    )]);
+    assert_eq!(
+        headers.origins(),
+        Some(Ok(vec![
+            Origin::Tuple(Scheme::HTTPS, Host::HOST("a.example.org".to_string()), None),
+            Origin::Tuple(
+                Scheme::HTTP,
+                Host::HOST("b.example.org".to_string()),
+                Some(8080)
+            ),
+        ]))
+    );
+}
+
+#[test]
+fn test_cors_credentials() {
+    let headers = |fields: &[(&str, &str)]| {
+        Headers::from(
+            fields
+                .iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect::<Vec<(String, String)>>(),
+        )
+    };
+    let app = Origin::Tuple(
+        Scheme::HTTPS,
+        Host::HOST("app.example.org".to_string()),
+        None,
+    );
+
+    let wildcard = headers(&[
+        ("Access-Control-Allow-Origin", "*"),
+        ("Access-Control-Allow-Credentials", "true"),
+    ]);
+    assert_eq!(
+        wildcard.access_control_allow_origin(),
+        Some(Ok(AllowOrigin::Any))
+    );
+    assert!(wildcard.cors_allows_origin(&app, false));
+    assert!(!wildcard.cors_allows_origin(&app, true));
+
+    let exact = headers(&[("Access-Control-Allow-Origin", "https://app.example.org")]);
+    assert!(exact.cors_allows_origin(&app, false));
+    assert!(!exact.cors_allows_origin(&app, true));
+
+    let credentialed = headers(&[
+        ("Access-Control-Allow-Origin", "https://app.example.org"),
+        ("Access-Control-Allow-Credentials", "true"),
+    ]);
+    assert!(credentialed.cors_allows_origin(&app, true));
+    assert!(!credentialed.cors_allows_origin(&Origin::Null, true));
+}
@@ -0,0 +1,190 @@
+use crate::Headers;
+
+/// A policy from a Content-Security-Policy header: https://www.w3.org/TR/CSP3/#parse-serialized-policy
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ContentSecurityPolicy {
+    /// The policy as it was sent.
+    pub raw: String,
+    /// The directives in the order they were sent, without duplicates.
+    pub directives: Vec<CspDirective>,
+}
+
+impl ContentSecurityPolicy {
+    /// Parses a serialized policy. Directives with invalid names are ignored and
+    /// only the first of several directives with the same name is kept.
+    pub fn parse(raw: &str) -> ContentSecurityPolicy {
+        let mut directives: Vec<CspDirective> = vec![];
+        for token in raw.split(';') {
+            let token = token.trim();
+            let (name, value) = match token.find(is_ascii_whitespace) {
+                Some(i) => (&token[..i], token[i..].trim()),
+                None => (token, ""),
+            };
+            let name = name.to_ascii_lowercase();
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|chr| chr.is_ascii_alphanumeric() || chr == '-')
+                || directives.iter().any(|d| d.name == name)
+            {
+                continue;
+            }
+            let sources = if name.ends_with("-src") {
+                value
+                    .split(is_ascii_whitespace)
+                    .filter(|source| !source.is_empty())
+                    .map(CspSource::from)
+                    .collect()
+            } else {
+                vec![]
+            };
+            directives.push(CspDirective {
+                name,
+                value: value.to_owned(),
+                sources,
+            });
+        }
+        ContentSecurityPolicy {
+            raw: raw.to_owned(),
+            directives,
+        }
+    }
+
+    pub fn directive(&self, name: &str) -> Option<&CspDirective> {
+        self.directives
+            .iter()
+            .find(|d| d.name.eq_ignore_ascii_case(name))
+    }
+}
+
+fn is_ascii_whitespace(chr: char) -> bool {
+    chr.is_ascii_whitespace()
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CspDirective {
+    /// The directive name in lowercase.
+    pub name: String,
+    /// The directive value as it was sent.
+    pub value: String,
+    /// The source list of fetch directives like `script-src`, empty for others.
+    pub sources: Vec<CspSource>,
+}
+
+/// A source expression: https://www.w3.org/TR/CSP3/#grammardef-serialized-source-list
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum CspSource {
+    /// A quoted keyword like `'self'` or `'unsafe-inline'`, stored lowercase
+    /// without the quotes.
+    Keyword(String),
+    /// A scheme like `https:`, stored lowercase without the colon.
+    Scheme(String),
+    /// A host with optional scheme, port and path, possibly with wildcards like
+    /// `*.example.org` or `https://example.org:*`.
+    Host(String),
+    /// `'nonce-<value>'`
+    Nonce(String),
+    /// `'sha256-<value>'`, `'sha384-<value>'` or `'sha512-<value>'`
+    Hash { algorithm: String, value: String },
+}
+
+impl From<&str> for CspSource {
+    fn from(source: &str) -> Self {
+        if let Some(quoted) = source
+            .strip_prefix('\'')
+            .and_then(|source| source.strip_suffix('\''))
+        {
+            let lower = quoted.to_ascii_lowercase();
+            if lower.starts_with("nonce-") {
+                return CspSource::Nonce(quoted["nonce-".len()..].to_owned());
+            }
+            for algorithm in &["sha256", "sha384", "sha512"] {
+                if lower.starts_with(algorithm) && lower[algorithm.len()..].starts_with('-') {
+                    return CspSource::Hash {
+                        algorithm: algorithm.to_string(),
+                        value: quoted[algorithm.len() + 1..].to_owned(),
+                    };
+                }
+            }
+            return CspSource::Keyword(lower);
+        }
+        match source.strip_suffix(':') {
+            Some(scheme) if is_scheme(scheme) => CspSource::Scheme(scheme.to_ascii_lowercase()),
+            _ => CspSource::Host(source.to_owned()),
+        }
+    }
+}
+
+// scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )
+fn is_scheme(scheme: &str) -> bool {
+    scheme.starts_with(|chr: char| chr.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|chr| chr.is_ascii_alphanumeric() || "+-.".contains(chr))
+}
+
+impl Headers {
+    /// Content-Security-Policy: https://www.w3.org/TR/CSP3/#csp-header
+    /// Every field line, and every comma separated entry in it, is a policy of
+    /// its own.
+    pub fn content_security_policy(&self) -> Option<Vec<ContentSecurityPolicy>> {
+        if !self.contains("Content-Security-Policy") {
+            return None;
+        }
+        Some(
+            self.get_all("Content-Security-Policy")
+                .flat_map(|value| value.split(','))
+                .map(ContentSecurityPolicy::parse)
+                .collect(),
+        )
+    }
+}
+
+#[test]
+fn test_content_security_policy() {
+    let headers = Headers::from(vec![(
+        "Content-Security-Policy".to_string(),
+        "default-src 'self'; script-src 'self' https://cdn.example.org 'nonce-abc' \
+         'sha256-B2yPHKaXnvFWtRChIbabYmUBFZdVfKKXHbWtWidDVF8=' https: *.example.com; \
+         report-uri /csp-report; script-src 'none'"
+            .to_string(),
+    )]);
+    let policies = headers.content_security_policy().unwrap();
+    assert_eq!(policies.len(), 1);
+    let policy = &policies[0];
+    assert_eq!(
+        policy.directive("default-src").unwrap().sources,
+        vec![CspSource::Keyword("self".to_string())]
+    );
+    assert_eq!(
+        policy.directive("script-src").unwrap().sources,
+        vec![
+            CspSource::Keyword("self".to_string()),
+            CspSource::Host("https://cdn.example.org".to_string()),
+            CspSource::Nonce("abc".to_string()),
+            CspSource::Hash {
+                algorithm: "sha256".to_string(),
+                value: "B2yPHKaXnvFWtRChIbabYmUBFZdVfKKXHbWtWidDVF8=".to_string()
+            },
+            CspSource::Scheme("https".to_string()),
+            CspSource::Host("*.example.com".to_string()),
+        ]
+    );
+    assert_eq!(
+        policy.directive("report-uri"),
+        Some(&CspDirective {
+            name: "report-uri".to_string(),
+            value: "/csp-report".to_string(),
+            sources: vec![],
+        })
+    );
+    assert_eq!(
+        policy
+            .directives
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["default-src", "script-src", "report-uri"]
+    );
+    assert!(policy.raw.ends_with("script-src 'none'"));
+}
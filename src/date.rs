@@ -0,0 +1,352 @@
+use crate::{HeaderValue, Headers, StandardHeader};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while_m_n},
+    combinator::{all_consuming, map, map_res},
+    sequence::{preceded, tuple},
+    IResult,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DateError {
+    /// The value matches none of the HTTP-date formats.
+    Malformed(String),
+    /// A date or time field is out of range, like the 31st of April.
+    OutOfRange(String),
+}
+
+// (year, month, day, hour, minute, second) as written in the date
+type DateTime = (i64, u32, u32, u32, u32, u32);
+
+/// HTTP-date: https://tools.ietf.org/html/rfc7231#section-7.1.1.1
+/// Accepts the preferred IMF-fixdate as well as the obsolete RFC 850 and asctime
+/// formats.
+pub fn parse_http_date(value: &str) -> Result<SystemTime, DateError> {
+    let (_, date) = all_consuming(alt((imf_fixdate, rfc850_date, asctime_date)))(value.trim())
+        .map_err(|_| DateError::Malformed(value.to_owned()))?;
+    to_system_time(date).ok_or_else(|| DateError::OutOfRange(value.to_owned()))
+}
+
+// Sun, 06 Nov 1994 08:49:37 GMT
+fn imf_fixdate(input: &str) -> IResult<&str, DateTime> {
+    tuple((
+        day_name,
+        tag(", "),
+        digits(2),
+        tag(" "),
+        month,
+        tag(" "),
+        digits(4),
+        tag(" "),
+        time_of_day,
+        tag(" GMT"),
+    ))(input)
+    .map(|(next_input, res)| {
+        let (hour, minute, second) = res.8;
+        (
+            next_input,
+            (res.6 as i64, res.4, res.2, hour, minute, second),
+        )
+    })
+}
+
+// Sunday, 06-Nov-94 08:49:37 GMT
+fn rfc850_date(input: &str) -> IResult<&str, DateTime> {
+    tuple((
+        day_name_l,
+        tag(", "),
+        digits(2),
+        tag("-"),
+        month,
+        tag("-"),
+        digits(2),
+        tag(" "),
+        time_of_day,
+        tag(" GMT"),
+    ))(input)
+    .map(|(next_input, res)| {
+        let (hour, minute, second) = res.8;
+        // two digit years are taken to be in the range 1970 to 2069
+        let year = match res.6 {
+            year if year < 70 => 2000 + year,
+            year => 1900 + year,
+        };
+        (
+            next_input,
+            (year as i64, res.4, res.2, hour, minute, second),
+        )
+    })
+}
+
+// Sun Nov  6 08:49:37 1994
+fn asctime_date(input: &str) -> IResult<&str, DateTime> {
+    tuple((
+        day_name,
+        tag(" "),
+        month,
+        tag(" "),
+        alt((digits(2), preceded(tag(" "), digits(1)))),
+        tag(" "),
+        time_of_day,
+        tag(" "),
+        digits(4),
+    ))(input)
+    .map(|(next_input, res)| {
+        let (hour, minute, second) = res.6;
+        (
+            next_input,
+            (res.8 as i64, res.2, res.4, hour, minute, second),
+        )
+    })
+}
+
+fn time_of_day(input: &str) -> IResult<&str, (u32, u32, u32)> {
+    tuple((
+        digits(2),
+        preceded(tag(":"), digits(2)),
+        preceded(tag(":"), digits(2)),
+    ))(input)
+}
+
+fn digits<'a>(n: usize) -> impl FnMut(&'a str) -> IResult<&'a str, u32> {
+    map_res(
+        take_while_m_n(n, n, |chr: char| chr.is_ascii_digit()),
+        str::parse,
+    )
+}
+
+fn day_name(input: &str) -> IResult<&str, &str> {
+    alt((
+        tag("Mon"),
+        tag("Tue"),
+        tag("Wed"),
+        tag("Thu"),
+        tag("Fri"),
+        tag("Sat"),
+        tag("Sun"),
+    ))(input)
+}
+
+fn day_name_l(input: &str) -> IResult<&str, &str> {
+    alt((
+        tag("Monday"),
+        tag("Tuesday"),
+        tag("Wednesday"),
+        tag("Thursday"),
+        tag("Friday"),
+        tag("Saturday"),
+        tag("Sunday"),
+    ))(input)
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+fn month(input: &str) -> IResult<&str, u32> {
+    map(
+        alt((
+            tag("Jan"),
+            tag("Feb"),
+            tag("Mar"),
+            tag("Apr"),
+            tag("May"),
+            tag("Jun"),
+            tag("Jul"),
+            tag("Aug"),
+            tag("Sep"),
+            tag("Oct"),
+            tag("Nov"),
+            tag("Dec"),
+        )),
+        |name| MONTHS.iter().position(|m| *m == name).unwrap_or(0) as u32 + 1,
+    )(input)
+}
+
+fn to_system_time((year, month, day, hour, minute, second): DateTime) -> Option<SystemTime> {
+    if day == 0 || day > days_in_month(year, month) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    let secs = days * 86400 + i64::from(hour * 3600 + minute * 60 + second);
+    if secs < 0 {
+        return None;
+    }
+    Some(UNIX_EPOCH + Duration::from_secs(secs as u64))
+}
+
+const DAY_NAMES: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+
+/// Formats a time as IMF-fixdate, like `Sun, 06 Nov 1994 08:49:37 GMT`.
+/// Sub-second precision is dropped and times before 1970 are clamped to the epoch.
+pub fn fmt_http_date(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let days = (secs / 86400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    let secs_of_day = secs % 86400;
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        DAY_NAMES[(days % 7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// days since 1970-01-01: http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let yoe = year - era * 400;
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Retry-After: https://tools.ietf.org/html/rfc7231#section-7.1.3
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RetryAfter {
+    Seconds(u64),
+    Date(SystemTime),
+}
+
+impl Headers {
+    /// Last-Modified: https://tools.ietf.org/html/rfc7232#section-2.2
+    pub fn last_modified(&self) -> Option<Result<SystemTime, DateError>> {
+        self.get("Last-Modified").map(parse_http_date)
+    }
+
+    pub fn set_last_modified(&mut self, time: SystemTime) {
+        self.set(
+            StandardHeader::LastModified.into(),
+            HeaderValue::new_unchecked(fmt_http_date(time)),
+        );
+    }
+
+    pub fn retry_after(&self) -> Option<Result<RetryAfter, DateError>> {
+        self.get("Retry-After").map(|value| {
+            let value = value.trim();
+            if !value.is_empty() && value.chars().all(|chr| chr.is_ascii_digit()) {
+                value
+                    .parse()
+                    .map(RetryAfter::Seconds)
+                    .map_err(|_| DateError::OutOfRange(value.to_owned()))
+            } else {
+                parse_http_date(value).map(RetryAfter::Date)
+            }
+        })
+    }
+}
+
+#[test]
+fn test_http_date() {
+    let expected = Ok(UNIX_EPOCH + Duration::from_secs(784111777));
+    assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), expected);
+    assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), expected);
+    assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), expected);
+    assert_eq!(
+        parse_http_date("Sun, 29 Feb 2015 08:49:37 GMT"),
+        Err(DateError::OutOfRange(
+            "Sun, 29 Feb 2015 08:49:37 GMT".to_string()
+        ))
+    );
+    assert_eq!(
+        parse_http_date("2015-02-01"),
+        Err(DateError::Malformed("2015-02-01".to_string()))
+    );
+}
+
+#[test]
+fn test_fmt_http_date() {
+    assert_eq!(
+        fmt_http_date(UNIX_EPOCH + Duration::from_millis(784111777500)),
+        "Sun, 06 Nov 1994 08:49:37 GMT"
+    );
+    assert_eq!(fmt_http_date(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+    assert_eq!(
+        fmt_http_date(UNIX_EPOCH + Duration::from_secs(951782400)),
+        "Tue, 29 Feb 2000 00:00:00 GMT"
+    );
+}
+
+#[test]
+fn test_last_modified() {
+    let headers = |v: &str| Headers::from(vec![("Last-Modified".to_string(), v.to_string())]);
+    let at = UNIX_EPOCH + Duration::from_secs(1445412480);
+
+    assert_eq!(
+        headers("Wed, 21 Oct 2015 07:28:00 GMT").last_modified(),
+        Some(Ok(at))
+    );
+    assert_eq!(
+        headers("Wednesday, 21-Oct-15 07:28:00 GMT").last_modified(),
+        Some(Ok(at))
+    );
+    assert_eq!(
+        headers("21.10.2015").last_modified(),
+        Some(Err(DateError::Malformed("21.10.2015".to_string())))
+    );
+    assert_eq!(Headers::new().last_modified(), None);
+
+    let mut headers = headers("21.10.2015");
+    headers.set_last_modified(at);
+    assert_eq!(
+        headers.get("last-modified"),
+        Some("Wed, 21 Oct 2015 07:28:00 GMT")
+    );
+    assert_eq!(headers.last_modified(), Some(Ok(at)));
+    assert_eq!(headers.len(), 1);
+}
+
+#[test]
+fn test_retry_after() {
+    let headers = |v: &str| Headers::from(vec![("Retry-After".to_string(), v.to_string())]);
+
+    assert_eq!(
+        headers("120").retry_after(),
+        Some(Ok(RetryAfter::Seconds(120)))
+    );
+    assert_eq!(
+        headers("Wed, 21 Oct 2015 07:28:00 GMT").retry_after(),
+        Some(Ok(RetryAfter::Date(
+            UNIX_EPOCH + Duration::from_secs(1445412480)
+        )))
+    );
+    assert_eq!(
+        headers("soon").retry_after(),
+        Some(Err(DateError::Malformed("soon".to_string())))
+    );
+    assert_eq!(Headers::new().retry_after(), None);
+}
@@ -0,0 +1,96 @@
+use crate::percent;
+use crate::uri::split_pairs;
+use crate::Request;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormError {
+    /// The request's Content-Type is not application/x-www-form-urlencoded, with
+    /// the Content-Type that was sent, if any.
+    ContentType(Option<String>),
+    /// The body is not valid UTF-8 or contains a malformed `%` escape.
+    Invalid(String),
+}
+
+// `+` stands for a space, `%XX` for a byte: https://url.spec.whatwg.org/#urlencoded-parsing
+// An encoded `+` is decoded after the spaces, so it stays a plus.
+fn form_decode(input: &str) -> Option<String> {
+    percent::decode(&input.replace('+', " ")).ok()
+}
+
+/// Parses an application/x-www-form-urlencoded body into decoded pairs. Pairs
+/// are split like the ones of a query, so repeated keys are kept in order and
+/// a key without `=` has an empty value.
+pub fn parse_form_body(input: &str) -> Result<Vec<(String, String)>, FormError> {
+    split_pairs(input)
+        .into_iter()
+        .map(
+            |(key, value)| match (form_decode(key), form_decode(value)) {
+                (Some(key), Some(value)) => Ok((key, value)),
+                _ => Err(FormError::Invalid(input.to_owned())),
+            },
+        )
+        .collect()
+}
+
+impl Request {
+    /// The body of an HTML form post, see `parse_form_body`.
+    pub fn form(&self) -> Result<Vec<(String, String)>, FormError> {
+        match self.content_type() {
+            Some(Ok(media_type)) if media_type.is("application", "x-www-form-urlencoded") => (),
+            _ => {
+                let content_type = self.headers.get("Content-Type");
+                return Err(FormError::ContentType(content_type.map(|c| c.to_owned())));
+            }
+        }
+        let body = std::str::from_utf8(&self.body)
+            .map_err(|_| FormError::Invalid(String::from_utf8_lossy(&self.body).into_owned()))?;
+        parse_form_body(body)
+    }
+}
+
+#[test]
+fn test_parse_form_body() {
+    assert_eq!(
+        parse_form_body("name=Jo+Doe&note=fish+%26+chips&empty=&flag"),
+        Ok(vec![
+            ("name".to_string(), "Jo Doe".to_string()),
+            ("note".to_string(), "fish & chips".to_string()),
+            ("empty".to_string(), "".to_string()),
+            ("flag".to_string(), "".to_string()),
+        ])
+    );
+    assert_eq!(
+        parse_form_body("tag=a&&tag=b"),
+        Ok(vec![
+            ("tag".to_string(), "a".to_string()),
+            ("tag".to_string(), "b".to_string()),
+        ])
+    );
+    assert_eq!(
+        parse_form_body("a=%4"),
+        Err(FormError::Invalid("a=%4".to_string()))
+    );
+}
+
+#[test]
+fn test_request_form() {
+    let request = |content_type: &str| {
+        let input = format!(
+            "POST /f HTTP/1.1\r\nContent-Type: {}\r\nContent-Length: 9\r\n\r\nq=a+b&q=c",
+            content_type
+        );
+        crate::parse_http(&input).unwrap().1
+    };
+
+    assert_eq!(
+        request("application/x-www-form-urlencoded; charset=UTF-8").form(),
+        Ok(vec![
+            ("q".to_string(), "a b".to_string()),
+            ("q".to_string(), "c".to_string()),
+        ])
+    );
+    assert_eq!(
+        request("text/plain").form(),
+        Err(FormError::ContentType(Some("text/plain".to_string())))
+    );
+}
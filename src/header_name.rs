@@ -0,0 +1,196 @@
+use crate::is_tchar;
+use std::fmt;
+
+macro_rules! standard_headers {
+    ($($variant:ident => $name:expr,)+) => {
+        /// Header names common enough to be stored without allocating.
+        #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+        pub enum StandardHeader {
+            $($variant,)+
+        }
+
+        const STANDARD_HEADERS: &[StandardHeader] = &[$(StandardHeader::$variant,)+];
+
+        impl StandardHeader {
+            /// The canonical spelling of the name.
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(StandardHeader::$variant => $name,)+
+                }
+            }
+        }
+    };
+}
+
+standard_headers! {
+    Accept => "Accept",
+    AcceptCharset => "Accept-Charset",
+    AcceptEncoding => "Accept-Encoding",
+    AcceptLanguage => "Accept-Language",
+    AcceptRanges => "Accept-Ranges",
+    AccessControlAllowCredentials => "Access-Control-Allow-Credentials",
+    AccessControlAllowHeaders => "Access-Control-Allow-Headers",
+    AccessControlAllowMethods => "Access-Control-Allow-Methods",
+    AccessControlAllowOrigin => "Access-Control-Allow-Origin",
+    AccessControlExposeHeaders => "Access-Control-Expose-Headers",
+    AccessControlMaxAge => "Access-Control-Max-Age",
+    AccessControlRequestHeaders => "Access-Control-Request-Headers",
+    AccessControlRequestMethod => "Access-Control-Request-Method",
+    Age => "Age",
+    Allow => "Allow",
+    AltSvc => "Alt-Svc",
+    Authorization => "Authorization",
+    CacheControl => "Cache-Control",
+    Connection => "Connection",
+    ContentDisposition => "Content-Disposition",
+    ContentEncoding => "Content-Encoding",
+    ContentLanguage => "Content-Language",
+    ContentLength => "Content-Length",
+    ContentLocation => "Content-Location",
+    ContentRange => "Content-Range",
+    ContentSecurityPolicy => "Content-Security-Policy",
+    ContentType => "Content-Type",
+    Cookie => "Cookie",
+    Date => "Date",
+    ETag => "ETag",
+    Expect => "Expect",
+    Expires => "Expires",
+    Forwarded => "Forwarded",
+    From => "From",
+    Host => "Host",
+    IfMatch => "If-Match",
+    IfModifiedSince => "If-Modified-Since",
+    IfNoneMatch => "If-None-Match",
+    IfRange => "If-Range",
+    IfUnmodifiedSince => "If-Unmodified-Since",
+    KeepAlive => "Keep-Alive",
+    LastModified => "Last-Modified",
+    Link => "Link",
+    Location => "Location",
+    MaxForwards => "Max-Forwards",
+    Origin => "Origin",
+    Pragma => "Pragma",
+    Prefer => "Prefer",
+    PreferenceApplied => "Preference-Applied",
+    Priority => "Priority",
+    ProxyAuthenticate => "Proxy-Authenticate",
+    ProxyAuthorization => "Proxy-Authorization",
+    Range => "Range",
+    Referer => "Referer",
+    RetryAfter => "Retry-After",
+    SecWebSocketAccept => "Sec-WebSocket-Accept",
+    SecWebSocketExtensions => "Sec-WebSocket-Extensions",
+    SecWebSocketKey => "Sec-WebSocket-Key",
+    SecWebSocketProtocol => "Sec-WebSocket-Protocol",
+    SecWebSocketVersion => "Sec-WebSocket-Version",
+    Server => "Server",
+    SetCookie => "Set-Cookie",
+    StrictTransportSecurity => "Strict-Transport-Security",
+    TE => "TE",
+    Trailer => "Trailer",
+    TransferEncoding => "Transfer-Encoding",
+    Upgrade => "Upgrade",
+    UserAgent => "User-Agent",
+    Vary => "Vary",
+    Via => "Via",
+    Warning => "Warning",
+    WWWAuthenticate => "WWW-Authenticate",
+}
+
+/// A header field name, matched case-insensitively. Standard names are interned
+/// as `StandardHeader` and always spelled canonically, other names are kept as
+/// they were received.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum HeaderName {
+    Standard(StandardHeader),
+    Custom(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HeaderNameError {
+    /// The name is empty or has a character that is not allowed in a token.
+    Invalid(String),
+}
+
+impl HeaderName {
+    /// A name that is a token, so it cannot break out of its field line when
+    /// serialized: https://tools.ietf.org/html/rfc7230#section-3.2
+    pub fn new(name: &str) -> Result<HeaderName, HeaderNameError> {
+        if name.is_empty() || !name.chars().all(is_tchar) {
+            return Err(HeaderNameError::Invalid(name.to_owned()));
+        }
+        Ok(HeaderName::from_token(name))
+    }
+
+    /// Interns a name that is already known to be a token, e.g. because the
+    /// grammar matched it.
+    pub(crate) fn from_token(name: &str) -> HeaderName {
+        match STANDARD_HEADERS
+            .iter()
+            .find(|standard| standard.as_str().eq_ignore_ascii_case(name))
+        {
+            Some(standard) => HeaderName::Standard(*standard),
+            None => HeaderName::Custom(name.to_owned()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            HeaderName::Standard(standard) => standard.as_str(),
+            HeaderName::Custom(name) => name,
+        }
+    }
+
+    pub fn eq_ignore_ascii_case(&self, name: &str) -> bool {
+        self.as_str().eq_ignore_ascii_case(name)
+    }
+}
+
+impl std::str::FromStr for HeaderName {
+    type Err = HeaderNameError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        HeaderName::new(name)
+    }
+}
+
+impl From<StandardHeader> for HeaderName {
+    fn from(standard: StandardHeader) -> Self {
+        HeaderName::Standard(standard)
+    }
+}
+
+impl fmt::Display for HeaderName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[test]
+fn test_header_name() {
+    assert_eq!(
+        HeaderName::from_token("content-TYPE"),
+        HeaderName::Standard(StandardHeader::ContentType)
+    );
+    assert_eq!(
+        HeaderName::from_token("sec-websocket-key"),
+        HeaderName::Standard(StandardHeader::SecWebSocketKey)
+    );
+    assert_eq!(
+        HeaderName::from_token("x-Request-Id"),
+        HeaderName::Custom("x-Request-Id".to_string())
+    );
+    assert_eq!(HeaderName::from_token("etag").to_string(), "ETag");
+    assert!(HeaderName::from_token("X-Request-Id").eq_ignore_ascii_case("x-request-id"));
+
+    assert_eq!(
+        HeaderName::new("host"),
+        Ok(HeaderName::Standard(StandardHeader::Host))
+    );
+    for invalid in &["", "X\r\nEvil", "X Y", "X:", "X\0"] {
+        assert_eq!(
+            HeaderName::new(invalid),
+            Err(HeaderNameError::Invalid(invalid.to_string()))
+        );
+    }
+}
@@ -0,0 +1,629 @@
+use crate::{
+    host_or_ip, port, Header, HeaderName, Host, Method, ParseError, Request, Scheme,
+    StandardHeader, TargetForm,
+};
+use nom::{
+    combinator::{all_consuming, opt},
+    sequence::pair,
+    Err as NomErr, IResult,
+};
+use std::borrow::Cow;
+use std::fmt;
+
+/// Header fields in the order they were received. Lookups by name are
+/// case-insensitive: https://tools.ietf.org/html/rfc7230#section-3.2
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Headers {
+    fields: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl Headers {
+    pub fn new() -> Self {
+        Headers::default()
+    }
+
+    /// The value of the first field called `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The value of the first field called `name` as it was received, which
+    /// may not be UTF-8. `get` replaces such bytes.
+    pub fn get_bytes(&self, name: &str) -> Option<&[u8]> {
+        self.fields
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_bytes())
+    }
+
+    /// The values of all fields called `name`, in order.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.fields
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&HeaderName, &str)> {
+        self.fields.iter().map(|(n, v)| (n, v.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Replaces the value of the first field called `name` and removes any others,
+    /// or appends a new field if there is none. Like the value, `name` is
+    /// validated when it is built, see `HeaderName::new`.
+    pub fn set(&mut self, name: HeaderName, value: HeaderValue) {
+        let mut value = Some(value);
+        self.fields.retain_mut(|(n, v)| {
+            if !n.eq_ignore_ascii_case(name.as_str()) {
+                return true;
+            }
+            match value.take() {
+                Some(value) => {
+                    *v = value;
+                    true
+                }
+                None => false,
+            }
+        });
+        if let Some(value) = value {
+            self.fields.push((name, value));
+        }
+    }
+
+    /// Adds a field after all others, keeping existing fields with the same name.
+    pub fn append(&mut self, name: HeaderName, value: HeaderValue) {
+        self.fields.push((name, value));
+    }
+
+    // for a name that was validated or parsed already
+    pub(crate) fn push(&mut self, name: HeaderName, value: HeaderValue) {
+        self.fields.push((name, value));
+    }
+
+    /// Access to the first field called `name` for modifying it in place.
+    pub fn entry(&mut self, name: HeaderName) -> Entry<'_> {
+        Entry {
+            headers: self,
+            name,
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|(n, _)| n.eq_ignore_ascii_case(name))
+    }
+
+    /// Removes all fields called `name`, returning them in order.
+    pub fn remove(&mut self, name: &str) -> Vec<Header> {
+        let (removed, kept) = std::mem::take(&mut self.fields)
+            .into_iter()
+            .partition::<Vec<(HeaderName, HeaderValue)>, _>(|(n, _)| n.eq_ignore_ascii_case(name));
+        self.fields = kept;
+        removed
+            .into_iter()
+            .map(|(n, v)| (n.as_str().to_owned(), v.text))
+            .collect()
+    }
+
+    /// Rewrites every custom name into its canonical spelling, see
+    /// `canonical_name`. Standard names are always spelled canonically.
+    pub fn canonicalize(&mut self) {
+        for (name, _) in self.fields.iter_mut() {
+            if let HeaderName::Custom(custom) = name {
+                *custom = canonical_name(custom);
+            }
+        }
+    }
+
+    /// Serializes the fields as `name: value` lines terminated by CRLF. Standard
+    /// names use their canonical spelling, custom names either the spelling they
+    /// were received with or the canonical one.
+    pub fn serialize(&self, canonical: bool) -> String {
+        let mut out = String::new();
+        for (name, value) in self.fields.iter() {
+            match name {
+                HeaderName::Custom(custom) if canonical => out.push_str(&canonical_name(custom)),
+                _ => out.push_str(name.as_str()),
+            }
+            out.push_str(": ");
+            out.push_str(value.as_str());
+            out.push_str("\r\n");
+        }
+        out
+    }
+
+    /// Allow: https://tools.ietf.org/html/rfc7231#section-7.4.1
+    /// An empty value is legal and yields an empty list, unlike a missing header.
+    pub fn allow(&self) -> Option<Vec<Method>> {
+        if !self.contains("Allow") {
+            return None;
+        }
+        Some(
+            self.get_all("Allow")
+                .flat_map(split_list)
+                .map(Method::from)
+                .collect(),
+        )
+    }
+
+    pub fn set_allow(&mut self, methods: &[Method]) {
+        self.set(
+            StandardHeader::Allow.into(),
+            HeaderValue::new_unchecked(fmt_allow(methods)),
+        );
+    }
+
+    /// Host: https://tools.ietf.org/html/rfc7230#section-5.4
+    pub fn host(&self) -> Option<Result<(Host, Option<u16>), HostError>> {
+        self.get("Host").map(|value| {
+            let value = value.trim();
+            all_consuming(pair(host_or_ip, opt(port)))(value)
+                .map(|(_, res)| res)
+                .map_err(|_: NomErr<()>| HostError::Invalid(value.to_owned()))
+        })
+    }
+}
+
+impl From<Vec<Header>> for Headers {
+    fn from(fields: Vec<Header>) -> Self {
+        Headers {
+            fields: fields
+                .into_iter()
+                .map(|(name, value)| {
+                    (
+                        HeaderName::from_token(name.as_str()),
+                        HeaderValue::new_unchecked(value),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// See `Headers::entry`.
+#[derive(Debug)]
+pub struct Entry<'a> {
+    headers: &'a mut Headers,
+    name: HeaderName,
+}
+
+impl<'a> Entry<'a> {
+    /// The value of the first field, if there is one.
+    pub fn get(&self) -> Option<&str> {
+        self.headers.get(self.name.as_str())
+    }
+
+    /// Calls `f` with the value of the first field, if there is one.
+    pub fn and_modify<F: FnOnce(&mut HeaderValue)>(self, f: F) -> Self {
+        if let Some(index) = self.headers.position(self.name.as_str()) {
+            f(&mut self.headers.fields[index].1);
+        }
+        self
+    }
+
+    /// The value of the first field, appending a field with `default` if there
+    /// is none.
+    pub fn or_insert(self, default: HeaderValue) -> &'a mut HeaderValue {
+        let index = match self.headers.position(self.name.as_str()) {
+            Some(index) => index,
+            None => {
+                self.headers.append(self.name, default);
+                self.headers.fields.len() - 1
+            }
+        };
+        &mut self.headers.fields[index].1
+    }
+}
+
+/// A header field value that cannot break out of its field line when
+/// serialized, as it contains no CR, LF or NUL.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct HeaderValue {
+    text: String,
+    // the value as received, if it is not UTF-8
+    bytes: Option<Vec<u8>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HeaderValueError {
+    /// The value contains CR, LF or NUL.
+    Invalid(String),
+}
+
+impl HeaderValue {
+    pub fn new(value: &str) -> Result<HeaderValue, HeaderValueError> {
+        if value.contains(['\r', '\n', '\0']) {
+            return Err(HeaderValueError::Invalid(value.to_owned()));
+        }
+        Ok(HeaderValue::new_unchecked(value.to_owned()))
+    }
+
+    /// Skips the validation, so the caller has to make sure the value contains
+    /// no CR, LF or NUL. Bytes that are not UTF-8 are kept for `as_bytes`.
+    pub fn from_bytes_unchecked(value: &[u8]) -> HeaderValue {
+        match String::from_utf8_lossy(value) {
+            Cow::Borrowed(text) => HeaderValue::new_unchecked(text.to_owned()),
+            Cow::Owned(text) => HeaderValue {
+                text,
+                bytes: Some(value.to_vec()),
+            },
+        }
+    }
+
+    // for values built by this crate from parsed or formatted parts
+    pub(crate) fn new_unchecked(value: String) -> HeaderValue {
+        HeaderValue {
+            text: value,
+            bytes: None,
+        }
+    }
+
+    /// The value as text, with bytes that are not UTF-8 replaced like
+    /// `String::from_utf8_lossy` does.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The value as it was received, like a Latin-1 `caf\xe9`.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_deref().unwrap_or(self.text.as_bytes())
+    }
+
+    /// Whether the value was received as valid UTF-8.
+    pub fn is_utf8(&self) -> bool {
+        self.bytes.is_none()
+    }
+}
+
+impl std::str::FromStr for HeaderValue {
+    type Err = HeaderValueError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        HeaderValue::new(value)
+    }
+}
+
+/// Names whose conventional spelling differs from plain title-case.
+const CANONICAL_EXCEPTIONS: [&str; 10] = [
+    "ETag",
+    "WWW-Authenticate",
+    "TE",
+    "DNT",
+    "Content-MD5",
+    "Content-ID",
+    "X-XSS-Protection",
+    "X-UA-Compatible",
+    "X-DNS-Prefetch-Control",
+    "SourceMap",
+];
+
+/// Title-cases a header name on dash boundaries (`x-request-id` becomes
+/// `X-Request-Id`), except for the names with a conventional spelling like `ETag`.
+pub fn canonical_name(name: &str) -> String {
+    if let Some(exception) = CANONICAL_EXCEPTIONS
+        .iter()
+        .find(|exception| exception.eq_ignore_ascii_case(name))
+    {
+        return (*exception).to_owned();
+    }
+    name.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => {
+                    first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+                }
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+/// Runs `parser` over a complete header value, ignoring surrounding whitespace.
+/// Error offsets are relative to the trimmed value.
+pub(crate) fn parse_value<'a, O, F>(value: &'a str, parser: F) -> Result<O, ParseError>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    let value = value.trim();
+    all_consuming(parser)(value)
+        .map(|(_, res)| res)
+        .map_err(|e| ParseError::from_nom(value, e))
+}
+
+/// Formats methods as an Allow header value, like `GET, HEAD, PUT`.
+pub fn fmt_allow(methods: &[Method]) -> String {
+    methods
+        .iter()
+        .map(Method::as_str)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+/// Splits a comma-separated `#rule` list, skipping empty elements:
+/// https://tools.ietf.org/html/rfc7230#section-7
+pub(crate) fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// What is wrong with a malformed header line, see `ParseError::InvalidHeader`.
+/// Offsets are byte offsets of the whole input.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum HeaderError {
+    /// A character that is not allowed in a field name, like the space in
+    /// `Bad Header: x`. Names are made up of letters, digits and `-`.
+    InvalidNameChar { ch: char, offset: usize },
+    /// The line starts with the colon.
+    EmptyName,
+    /// The line has no colon after the field name.
+    MissingColon,
+    /// A character that is not allowed in a field value, a CR without LF.
+    InvalidValueChar { ch: char, offset: usize },
+    /// The input ends before the line ending of a field or the empty line
+    /// after the fields.
+    MissingLineEnding,
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeaderError::InvalidNameChar { ch, offset } => write!(
+                f,
+                "invalid character {:?} in a header name at offset {}",
+                ch, offset
+            ),
+            HeaderError::EmptyName => write!(f, "empty header name"),
+            HeaderError::MissingColon => write!(f, "missing colon after the header name"),
+            HeaderError::InvalidValueChar { ch, offset } => write!(
+                f,
+                "invalid character {:?} in a header value at offset {}",
+                ch, offset
+            ),
+            HeaderError::MissingLineEnding => write!(f, "missing line ending"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HostError {
+    /// The Host header value is not a valid `host [":" port]`.
+    Invalid(String),
+    /// The Host header does not match the authority of an absolute-form target.
+    Mismatch,
+}
+
+impl Request {
+    /// When the target is in absolute-form, a Host header has to name the same
+    /// authority as the target, otherwise the request should be answered with a 400.
+    pub fn validate_host(&self) -> Result<(), HostError> {
+        let uri = &self.request_line.uri;
+        if self.request_line.target_form() != TargetForm::Absolute {
+            return Ok(());
+        }
+        let (host, port) = match self.headers.host() {
+            Some(res) => res?,
+            None => return Ok(()),
+        };
+        let same_host = match (&host, &uri.host) {
+            (Host::HOST(a), Some(Host::HOST(b))) => a.eq_ignore_ascii_case(b),
+            (a, Some(b)) => a == b,
+            (_, None) => false,
+        };
+        let default_port = uri.scheme.as_ref().and_then(Scheme::default_port);
+        if same_host && port.or(default_port) == uri.port.or(default_port) {
+            Ok(())
+        } else {
+            Err(HostError::Mismatch)
+        }
+    }
+}
+
+#[test]
+fn test_host_header() {
+    let request = |i| crate::parse_http(i).unwrap().1;
+
+    let matching = request("GET http://Example.org/p HTTP/1.1\r\nHost: example.org\r\n\r\n");
+    assert_eq!(
+        matching.headers.host(),
+        Some(Ok((Host::HOST("example.org".to_string()), None)))
+    );
+    assert_eq!(matching.validate_host(), Ok(()));
+
+    let mismatching = request("GET http://example.org/p HTTP/1.1\r\nHost: other.org\r\n\r\n");
+    assert_eq!(mismatching.validate_host(), Err(HostError::Mismatch));
+
+    let with_port = request("GET http://example.org/p HTTP/1.1\r\nHost: example.org:8080\r\n\r\n");
+    assert_eq!(
+        with_port.headers.host(),
+        Some(Ok((Host::HOST("example.org".to_string()), Some(8080))))
+    );
+    assert_eq!(with_port.validate_host(), Err(HostError::Mismatch));
+    let default_port = request("GET http://example.org/p HTTP/1.1\r\nHost: example.org:80\r\n\r\n");
+    assert_eq!(default_port.validate_host(), Ok(()));
+
+    let invalid = request("GET http://example.org/p HTTP/1.1\r\nHost: exa mple.org\r\n\r\n");
+    assert_eq!(
+        invalid.headers.host(),
+        Some(Err(HostError::Invalid("exa mple.org".to_string())))
+    );
+    assert_eq!(
+        invalid.validate_host(),
+        Err(HostError::Invalid("exa mple.org".to_string()))
+    );
+
+    let origin_form = request("GET /p HTTP/1.1\r\nHost: other.org\r\n\r\n");
+    assert_eq!(origin_form.validate_host(), Ok(()));
+}
+
+#[test]
+fn test_header_value() {
+    assert_eq!(
+        HeaderValue::new("a\r\nX-Injected: 1"),
+        Err(HeaderValueError::Invalid("a\r\nX-Injected: 1".to_string()))
+    );
+    assert!(HeaderValue::new("a\0b").is_err());
+    assert_eq!(
+        "text/plain; charset=utf-8"
+            .parse::<HeaderValue>()
+            .unwrap()
+            .as_str(),
+        "text/plain; charset=utf-8"
+    );
+
+    let mut headers = Headers::new();
+    headers.set("X-Id".parse().unwrap(), HeaderValue::new("1").unwrap());
+    assert_eq!(headers.get("x-id"), Some("1"));
+}
+
+#[test]
+fn test_append_and_set() {
+    let name = |n: &str| HeaderName::new(n).unwrap();
+    let value = |v: &str| HeaderValue::new(v).unwrap();
+    let mut headers = Headers::from(vec![
+        ("Host".to_string(), "example.org".to_string()),
+        ("Accept".to_string(), "text/html".to_string()),
+        ("X-Trace".to_string(), "a".to_string()),
+    ]);
+    headers.append(name("accept"), value("application/json"));
+    headers.append(name("x-custom"), value("1"));
+    assert_eq!(
+        headers.get_all("Accept").collect::<Vec<&str>>(),
+        vec!["text/html", "application/json"]
+    );
+
+    headers.set(name("ACCEPT"), value("*/*"));
+    headers.set(name("X-New"), value("2"));
+    assert_eq!(
+        headers.serialize(false),
+        "Host: example.org\r\nAccept: */*\r\nX-Trace: a\r\nx-custom: 1\r\nX-New: 2\r\n"
+    );
+    // a name is a token, so a field cannot be smuggled in through it
+    assert_eq!(
+        "X-A\r\nEvil".parse::<HeaderName>(),
+        Err(crate::HeaderNameError::Invalid("X-A\r\nEvil".to_string()))
+    );
+}
+
+#[test]
+fn test_remove() {
+    let mut headers = Headers::from(vec![
+        ("Via".to_string(), "1.0 a".to_string()),
+        ("Host".to_string(), "example.org".to_string()),
+        ("via".to_string(), "1.1 b".to_string()),
+        ("Accept".to_string(), "*/*".to_string()),
+        ("VIA".to_string(), "1.1 c".to_string()),
+    ]);
+    assert_eq!(
+        headers.remove("Via"),
+        vec![
+            ("Via".to_string(), "1.0 a".to_string()),
+            ("Via".to_string(), "1.1 b".to_string()),
+            ("Via".to_string(), "1.1 c".to_string()),
+        ]
+    );
+    assert_eq!(
+        headers,
+        Headers::from(vec![
+            ("Host".to_string(), "example.org".to_string()),
+            ("Accept".to_string(), "*/*".to_string()),
+        ])
+    );
+    assert!(headers.remove("Via").is_empty());
+}
+
+#[test]
+fn test_entry() {
+    let mut headers = Headers::from(vec![
+        ("X-Count".to_string(), "1".to_string()),
+        ("X-Count".to_string(), "7".to_string()),
+    ]);
+    headers
+        .entry("x-count".parse().unwrap())
+        .and_modify(|value| {
+            let next = value.as_str().parse::<u32>().unwrap() + 1;
+            *value = HeaderValue::new(&next.to_string()).unwrap();
+        });
+    assert_eq!(
+        headers.get_all("X-Count").collect::<Vec<&str>>(),
+        vec!["2", "7"]
+    );
+
+    let inserted = headers
+        .entry("X-Request-Id".parse().unwrap())
+        .or_insert(HeaderValue::new("abc").unwrap());
+    assert_eq!(inserted.as_str(), "abc");
+    assert_eq!(
+        headers.entry("x-request-id".parse().unwrap()).get(),
+        Some("abc")
+    );
+    assert_eq!(headers.len(), 3);
+}
+
+#[test]
+fn test_allow() {
+    let headers = |v: &str| Headers::from(vec![("Allow".to_string(), v.to_string())]);
+
+    assert_eq!(
+        headers("GET, HEAD, PUT").allow(),
+        Some(vec![Method::GET, Method::HEAD, Method::PUT])
+    );
+    assert_eq!(
+        headers("OPTIONS,PROPFIND").allow(),
+        Some(vec![
+            Method::OPTIONS,
+            Method::EXTENSION("PROPFIND".to_string())
+        ])
+    );
+    assert_eq!(headers("").allow(), Some(vec![]));
+    assert_eq!(Headers::new().allow(), None);
+
+    let mut headers = Headers::new();
+    headers.set_allow(&[Method::GET, Method::EXTENSION("PROPFIND".to_string())]);
+    assert_eq!(headers.get("Allow"), Some("GET, PROPFIND"));
+    headers.set_allow(&[]);
+    assert_eq!(headers.allow(), Some(vec![]));
+}
+
+#[test]
+fn test_canonicalize() {
+    assert_eq!(canonical_name("x-request-id"), "X-Request-Id");
+    assert_eq!(canonical_name("etag"), "ETag");
+    assert_eq!(canonical_name("www-authenticate"), "WWW-Authenticate");
+    assert_eq!(canonical_name("CONTENT-TYPE"), "Content-Type");
+
+    let input = "x-request-id: 1\r\netag: \"a\"\r\nContent-TYPE: text/plain\r\n";
+    let (_, mut headers) = crate::headers::<nom::error::Error<&str>>(input).unwrap();
+    assert_eq!(
+        headers.serialize(false),
+        "x-request-id: 1\r\nETag: \"a\"\r\nContent-Type: text/plain\r\n"
+    );
+    assert_eq!(
+        headers.serialize(true),
+        "X-Request-Id: 1\r\nETag: \"a\"\r\nContent-Type: text/plain\r\n"
+    );
+    assert_eq!(headers.get("Content-Type"), Some("text/plain"));
+
+    headers.canonicalize();
+    assert_eq!(headers.serialize(false), headers.serialize(true));
+    assert_eq!(headers.get("x-request-id"), Some("1"));
+}
@@ -0,0 +1,134 @@
+use crate::cache::delta_seconds;
+use crate::{ows, token, token_or_quoted_string, Headers};
+use nom::{
+    bytes::complete::tag,
+    combinator::{all_consuming, opt},
+    multi::separated_list0,
+    sequence::{delimited, pair, preceded, tuple},
+    IResult,
+};
+use std::time::Duration;
+
+/// Strict-Transport-Security: https://tools.ietf.org/html/rfc6797#section-6.1
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StrictTransportSecurity {
+    pub max_age: Duration,
+    pub include_subdomains: bool,
+    pub preload: bool,
+    /// Directives other than the ones above, with lowercase names.
+    pub extensions: Vec<(String, Option<String>)>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum HstsError {
+    /// The value is not a `;` separated list of directives.
+    Invalid(String),
+    /// A directive appears more than once, which makes the whole header invalid.
+    Duplicate(String),
+    /// The required max-age directive is missing or not a number of seconds.
+    MaxAge,
+}
+
+type Directive = (String, Option<String>);
+
+fn directive(input: &str) -> IResult<&str, Directive> {
+    pair(
+        token,
+        opt(preceded(
+            tuple((ows, tag("="), ows)),
+            token_or_quoted_string,
+        )),
+    )(input)
+    .map(|(next_input, res)| (next_input, (res.0.to_lowercase(), res.1)))
+}
+
+// [ directive ] *( ";" [ directive ] )
+fn directives(input: &str) -> IResult<&str, Vec<Directive>> {
+    delimited(
+        ows,
+        separated_list0(tuple((ows, tag(";"), ows)), opt(directive)),
+        ows,
+    )(input)
+    .map(|(next_input, res)| (next_input, res.into_iter().flatten().collect()))
+}
+
+impl Headers {
+    /// Strict-Transport-Security: https://tools.ietf.org/html/rfc6797#section-6.1
+    pub fn strict_transport_security(&self) -> Option<Result<StrictTransportSecurity, HstsError>> {
+        self.get("Strict-Transport-Security").map(|value| {
+            let (_, directives) = all_consuming(directives)(value)
+                .map_err(|_| HstsError::Invalid(value.to_owned()))?;
+
+            let mut max_age = None;
+            let mut include_subdomains = false;
+            let mut preload = false;
+            let mut extensions = vec![];
+            for (i, (name, value)) in directives.iter().enumerate() {
+                if directives[..i].iter().any(|(seen, _)| seen == name) {
+                    return Err(HstsError::Duplicate(name.to_owned()));
+                }
+                match name.as_str() {
+                    "max-age" => max_age = value.as_deref().and_then(delta_seconds),
+                    "includesubdomains" => include_subdomains = true,
+                    "preload" => preload = true,
+                    _ => extensions.push((name.to_owned(), value.to_owned())),
+                }
+            }
+            Ok(StrictTransportSecurity {
+                max_age: max_age.ok_or(HstsError::MaxAge)?,
+                include_subdomains,
+                preload,
+                extensions,
+            })
+        })
+    }
+}
+
+#[test]
+fn test_strict_transport_security() {
+    let sts = |value: &str| {
+        Headers::from(vec![(
+            "Strict-Transport-Security".to_string(),
+            value.to_string(),
+        )])
+        .strict_transport_security()
+        .unwrap()
+    };
+
+    assert_eq!(
+        sts("max-age=31536000; includeSubDomains; preload"),
+        Ok(StrictTransportSecurity {
+            max_age: Duration::from_secs(31536000),
+            include_subdomains: true,
+            preload: true,
+            extensions: vec![],
+        })
+    );
+    assert_eq!(
+        sts("max-age=0"),
+        Ok(StrictTransportSecurity {
+            max_age: Duration::from_secs(0),
+            include_subdomains: false,
+            preload: false,
+            extensions: vec![],
+        })
+    );
+    assert_eq!(
+        sts("max-age=\"600\"; report-uri=\"https://example.org/r\""),
+        Ok(StrictTransportSecurity {
+            max_age: Duration::from_secs(600),
+            include_subdomains: false,
+            preload: false,
+            extensions: vec![(
+                "report-uri".to_string(),
+                Some("https://example.org/r".to_string())
+            )],
+        })
+    );
+    assert_eq!(
+        sts("max-age=600; includeSubDomains; IncludeSubDomains"),
+        Err(HstsError::Duplicate("includesubdomains".to_string()))
+    );
+    assert_eq!(sts("includeSubDomains"), Err(HstsError::MaxAge));
+    assert_eq!(sts("max-age=-1"), Err(HstsError::MaxAge));
+}
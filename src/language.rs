@@ -0,0 +1,73 @@
+use crate::{list, weight, Headers};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while_m_n},
+    combinator::{all_consuming, opt, recognize},
+    multi::many0,
+    sequence::{pair, preceded},
+    IResult,
+};
+
+/// language-range: https://tools.ietf.org/html/rfc4647#section-2.1
+pub(crate) fn language_range(input: &str) -> IResult<&str, &str> {
+    alt((
+        recognize(pair(
+            take_while_m_n(1, 8, |chr: char| chr.is_ascii_alphabetic()),
+            many0(preceded(
+                tag("-"),
+                take_while_m_n(1, 8, |chr: char| chr.is_ascii_alphanumeric()),
+            )),
+        )),
+        tag("*"),
+    ))(input)
+}
+
+fn language(input: &str) -> IResult<&str, (String, f32)> {
+    pair(language_range, opt(weight))(input)
+        .map(|(next_input, (range, q))| (next_input, (range.to_owned(), q.unwrap_or(1.0))))
+}
+
+impl Headers {
+    /// Accept-Language: https://tools.ietf.org/html/rfc7231#section-5.3.5
+    /// Language ranges sorted by descending q-value, keeping the order in which
+    /// they were sent for equal ones. Field lines that fail to parse are skipped.
+    pub fn accept_language(&self) -> Option<Vec<(String, f32)>> {
+        if !self.contains("Accept-Language") {
+            return None;
+        }
+        let mut languages = self
+            .get_all("Accept-Language")
+            .filter_map(|value| all_consuming(list(language))(value).ok())
+            .flat_map(|(_, res)| res)
+            .collect::<Vec<(String, f32)>>();
+        languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Some(languages)
+    }
+}
+
+#[test]
+fn test_accept_language() {
+    let accept_language = |value: &str| {
+        Headers::from(vec![("Accept-Language".to_string(), value.to_string())]).accept_language()
+    };
+
+    assert_eq!(
+        accept_language("fr;q=0.5, en-US, en;q=0.9, *;q=0.1"),
+        Some(vec![
+            ("en-US".to_string(), 1.0),
+            ("en".to_string(), 0.9),
+            ("fr".to_string(), 0.5),
+            ("*".to_string(), 0.1),
+        ])
+    );
+    assert_eq!(
+        accept_language("de, zh-Hant-TW ; Q=1.000, da"),
+        Some(vec![
+            ("de".to_string(), 1.0),
+            ("zh-Hant-TW".to_string(), 1.0),
+            ("da".to_string(), 1.0),
+        ])
+    );
+    assert_eq!(accept_language("en;q=1.5"), Some(vec![]));
+    assert_eq!(Headers::new().accept_language(), None);
+}
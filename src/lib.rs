@@ -1,29 +1,100 @@
+#![allow(clippy::upper_case_acronyms)]
 /// Example:
 ///
 /// Basic HTTP Parser
 use nom::{
     branch::alt,
-    bytes::complete::{tag, tag_no_case, take, take_until, take_while},
-    character::{
-        complete::{alpha1, alphanumeric0, alphanumeric1, digit1, newline, one_of, space0},
-        is_alphanumeric, is_newline,
-    },
-    combinator::{cond, opt},
+    bytes::complete::{tag, tag_no_case, take_while, take_while1, take_while_m_n},
+    character::complete::{alphanumeric1, anychar, digit1, none_of, space0},
+    combinator::{cut, map, map_res, opt, recognize},
     error::Error,
-    error::ErrorKind,
-    multi::{count, many0, many1, many_m_n, separated_list1},
+    error::{context, ContextError, ErrorKind, FromExternalError},
+    multi::{count, fold_many0, many0, many1, separated_list0, separated_list1},
     sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
-    AsChar, Err as NomErr, IResult, InputTakeAtPosition,
+    AsChar, Err as NomErr, IResult, InputTakeAtPosition, Needed,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+
+mod alt_svc;
+mod auth;
+mod base64;
+mod body;
+mod builder;
+mod cache;
+mod chunked;
+mod conditional;
+mod connection;
+mod content;
+mod cookie;
+mod cors;
+mod csp;
+mod date;
+mod form;
+mod header_name;
+mod headers;
+mod hsts;
+mod language;
+mod multipart;
+mod nested;
+pub mod parse;
+pub mod percent;
+mod prefer;
+mod priority;
+mod reader;
+mod response;
+mod streaming;
+mod uri;
+mod validate;
+mod verbose;
+mod websocket;
+
+pub use alt_svc::{AltService, AltSvc};
+pub use auth::{Challenge, Credentials};
+pub use body::{BodyError, BodyProgress, BodyReader};
+pub use builder::{RequestBuilder, ResponseBuilder, StatusLineError};
+pub use cache::{AgeError, CacheDirective, CacheWarning, Expires, Pragma};
+pub use chunked::{is_forbidden_trailer, Chunk, ChunkedBody, TrailerError};
+pub use conditional::{EntityTag, EtagCondition, IfRange, Precondition};
+pub use connection::KeepAlive;
+pub use content::{AcceptRanges, ContentCoding, ContentDisposition, ContentRange, MediaType};
+pub use cookie::{parse_set_cookie, CookieError, SameSite, SetCookie};
+pub use cors::{AllowOrigin, Origin};
+pub use csp::{ContentSecurityPolicy, CspDirective, CspSource};
+pub use date::{fmt_http_date, parse_http_date, DateError, RetryAfter};
+pub use form::{parse_form_body, FormError};
+pub use header_name::{HeaderName, HeaderNameError, StandardHeader};
+pub use headers::{
+    canonical_name, fmt_allow, Entry, HeaderError, HeaderValue, HeaderValueError, Headers,
+    HostError,
 };
+pub use hsts::{HstsError, StrictTransportSecurity};
+pub use multipart::{parse_byteranges, parse_multipart, MultipartError, Part, RangePart};
+pub use nested::{parse_nested, NestedError, QueryValue, MAX_NESTING_DEPTH};
+pub use prefer::Preference;
+pub use priority::{Priority, PriorityError};
+pub use reader::parse_request_from_reader;
+pub use response::{parse_response, Response, StatusLine};
+pub use streaming::{
+    parse_head_partial, parse_request_line_partial, parse_request_partial, ParseOutcome,
+};
+pub use uri::UriError;
+pub use validate::ValidationError;
+pub use verbose::{explain_error, parse_http_verbose};
+pub use websocket::{websocket_accept, WebSocketExtension, WebSocketKeyError};
 
 type Header = (String, String);
-type Headers = Vec<Header>;
 
 type QueryParam = (String, String);
 type QueryParams = Vec<QueryParam>;
 
-#[derive(Debug, PartialEq, Eq)]
-enum Method {
+pub(crate) type Parameter = (String, String);
+pub(crate) type Parameters = Vec<Parameter>;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Method {
     GET,
     HEAD,
     POST,
@@ -32,12 +103,17 @@ enum Method {
     CONNECT,
     OPTIONS,
     TRACE,
+    PATCH,
+    /// Any other method token, as written in the request.
+    EXTENSION(String),
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum Host {
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Host {
     HOST(String),
     IP([u8; 4]),
+    /// An IPv6 address, written in brackets: https://tools.ietf.org/html/rfc3986#section-3.2.2
+    IPV6([u16; 8]),
     ASTERISK,
 }
 
@@ -52,70 +128,286 @@ impl From<&str> for Method {
             "CONNECT" => Method::CONNECT,
             "OPTIONS" => Method::OPTIONS,
             "TRACE" => Method::TRACE,
-            _ => unimplemented!("There are no other request methods"),
+            "PATCH" => Method::PATCH,
+            _ => Method::EXTENSION(i.to_owned()),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum Scheme {
+impl Method {
+    /// Safe methods are read-only: https://tools.ietf.org/html/rfc7231#section-4.2.1
+    /// Extension methods are not known to be safe.
+    pub const fn is_safe(&self) -> bool {
+        matches!(
+            self,
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+        )
+    }
+
+    /// Idempotent methods can be retried: https://tools.ietf.org/html/rfc7231#section-4.2.2
+    pub const fn is_idempotent(&self) -> bool {
+        self.is_safe() || matches!(self, Method::PUT | Method::DELETE)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Method::GET => "GET",
+            Method::HEAD => "HEAD",
+            Method::POST => "POST",
+            Method::PUT => "PUT",
+            Method::DELETE => "DELETE",
+            Method::CONNECT => "CONNECT",
+            Method::OPTIONS => "OPTIONS",
+            Method::TRACE => "TRACE",
+            Method::PATCH => "PATCH",
+            Method::EXTENSION(method) => method.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Scheme {
     HTTP,
     HTTPS,
+    /// https://tools.ietf.org/html/rfc6455#section-3
+    WS,
+    WSS,
+    /// Any other scheme, in lowercase.
+    OTHER(String),
 }
 
 impl From<&str> for Scheme {
     fn from(i: &str) -> Self {
-        match i.to_uppercase().as_str() {
+        match i.to_lowercase().as_str() {
             "http" => Scheme::HTTP,
             "https" => Scheme::HTTPS,
-            _ => unimplemented!("no other schemes supported"),
+            "ws" => Scheme::WS,
+            "wss" => Scheme::WSS,
+            other => Scheme::OTHER(other.to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Version {
+    HTTP10,
+    HTTP11,
+}
+
+impl TryFrom<&str> for Version {
+    type Error = ParseError;
+
+    fn try_from(i: &str) -> Result<Self, Self::Error> {
+        match i {
+            "HTTP/1.0" => Ok(Version::HTTP10),
+            "HTTP/1.1" => Ok(Version::HTTP11),
+            _ => Err(ParseError::InvalidVersion {
+                kind: ErrorKind::Tag,
+                position: Position::new(i, 0),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Version::HTTP10 => write!(f, "HTTP/1.0"),
+            Version::HTTP11 => write!(f, "HTTP/1.1"),
         }
     }
 }
 
 /// Based on https://url.spec.whatwg.org/#urls
-struct URI {
-    scheme: Scheme,
-    authority: Option<(Option<String>, Option<String>)>, // username & password
-    host: Host,
-    port: Option<u16>,
-    path: Option<String>,
-    query: Option<Vec<(String, String)>>,
-    fragment: Option<String>,
+/// Scheme and host are absent for origin-form request targets (`/path?query`).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct URI {
+    pub scheme: Option<Scheme>,
+    pub authority: Option<(Option<String>, Option<String>)>, // username & password
+    pub host: Option<Host>,
+    pub port: Option<u16>,
+    pub path: Option<String>,
+    pub query: Option<Vec<(String, String)>>,
+    /// The query as received, see `query_string`; to be kept in line with
+    /// `query` when setting it directly.
+    pub raw_query: Option<String>,
+    pub fragment: Option<String>,
+}
+
+/// The four request-target forms: https://tools.ietf.org/html/rfc7230#section-5.3
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TargetForm {
+    Origin,
+    Absolute,
+    Authority,
+    Asterisk,
+}
+
+/// REQUEST LINE: https://tools.ietf.org/html/rfc7230#section-3.1.1
+#[derive(Debug, PartialEq, Eq)]
+pub struct RequestLine {
+    pub method: Method,
+    pub uri: URI,
+    pub version: Version,
+}
+
+impl RequestLine {
+    pub fn target_form(&self) -> TargetForm {
+        match (&self.uri.scheme, &self.uri.host) {
+            (Some(_), _) => TargetForm::Absolute,
+            (None, Some(Host::ASTERISK)) => TargetForm::Asterisk,
+            (None, Some(_)) => TargetForm::Authority,
+            (None, None) => TargetForm::Origin,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Request {
+    pub request_line: RequestLine,
+    pub headers: Headers,
+    pub body: Vec<u8>,
+    /// The trailer section of a chunked body.
+    pub trailers: Headers,
+    /// Raw header lines skipped with `ParseOptions::skip_malformed_headers`.
+    pub malformed_headers: Vec<String>,
 }
 
-struct Request {
-    method: Method,
-    uri: URI,
+impl Request {
+    /// The method name, whether it is a known method or an extension.
+    pub fn method_str(&self) -> &str {
+        self.request_line.method.as_str()
+    }
+
+    pub fn body_bytes(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// The body as text, validated on each call.
+    pub fn body_str(&self) -> Result<&str, std::str::Utf8Error> {
+        std::str::from_utf8(&self.body)
+    }
+
+    /// Maps the request line and Host header to the HTTP/2 request pseudo-header
+    /// fields: https://tools.ietf.org/html/rfc7540#section-8.1.2.3
+    /// CONNECT requests only carry `:method` and `:authority` (section 8.3).
+    pub fn to_pseudo_headers(&self) -> Vec<(String, String)> {
+        let uri = &self.request_line.uri;
+        let method = self.request_line.method.to_string();
+        let authority = match self.headers.get("Host") {
+            Some(host) => Some(host.trim().to_owned()),
+            None => uri.host.as_ref().map(|host| match uri.port {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.to_string(),
+            }),
+        };
+
+        let mut pseudo = vec![(":method".to_string(), method)];
+        if self.request_line.method != Method::CONNECT {
+            let scheme = uri.scheme.as_ref().unwrap_or(&Scheme::HTTP);
+            pseudo.push((":scheme".to_string(), scheme.to_string()));
+        }
+        if let Some(authority) = authority {
+            pseudo.push((":authority".to_string(), authority));
+        }
+        if self.request_line.method != Method::CONNECT {
+            let path = match self.request_line.target_form() {
+                TargetForm::Asterisk => "*".to_string(),
+                _ => {
+                    let mut path = uri.path.clone().unwrap_or_else(|| "/".to_string());
+                    if let Some(query) = &uri.query {
+                        path.push('?');
+                        path.push_str(&uri::fmt_query(query));
+                    }
+                    path
+                }
+            };
+            pseudo.push((":path".to_string(), path));
+        }
+        pseudo
+    }
 }
 
-fn scheme(input: &str) -> IResult<&str, Scheme> {
-    alt((tag_no_case("HTTP://"), tag_no_case("HTTPS://")))(input)
-        .and_then(|(next_input, res)| Ok((next_input, res.into())))
+/// scheme: https://tools.ietf.org/html/rfc3986#section-3.1
+/// Only schemes followed by an authority (`://`) are recognized. Any other
+/// scheme, like `mailto:`, fails with `UriError::UnknownScheme`, unless what
+/// follows its colon is a port, as in the authority-form `example.org:443`.
+pub(crate) fn scheme<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, Scheme, E> {
+    let (rest, name) = recognize(pair(
+        take_while_m_n(1, 1, |chr: char| chr.is_ascii_alphabetic()),
+        take_while(|chr: char| chr.is_ascii_alphanumeric() || "+-.".contains(chr)),
+    ))(input)?;
+    if let Some(rest) = rest.strip_prefix("://") {
+        return Ok((rest, name.into()));
+    }
+    match rest.strip_prefix(':') {
+        Some(port)
+            if !port
+                .split(' ')
+                .next()
+                .unwrap_or_default()
+                .bytes()
+                .all(|b| b.is_ascii_digit()) =>
+        {
+            Err(NomErr::Failure(E::from_external_error(
+                input,
+                ErrorKind::Tag,
+                Cause::Uri(UriError::UnknownScheme(name.to_ascii_lowercase())),
+            )))
+        }
+        _ => Err(NomErr::Error(E::from_error_kind(rest, ErrorKind::Tag))),
+    }
 }
 
-fn authority(input: &str) -> IResult<&str, Option<(&str, Option<&str>)>> {
+pub(crate) fn authority<'a, E: GrammarError<'a>>(
+    input: &'a str,
+) -> IResult<&'a str, Option<(&'a str, Option<&'a str>)>, E> {
     opt(terminated(
         separated_pair(alphanumeric1, opt(tag(":")), opt(alphanumeric1)),
         tag("@"),
     ))(input)
 }
 
-fn host(input: &str) -> IResult<&str, Host> {
-    alt((
-        tuple((many1(terminated(alphanumerichyphen1, tag("."))), alpha1)),
-        tuple((many_m_n(1, 1, alphanumerichyphen1), take(0 as usize))),
-    ))(input)
-    .and_then(|(next_input, mut res)| {
-        println!("res: {:?}", res);
-        if !res.1.is_empty() {
-            res.0.push(res.1);
+/// A domain name like example.org, or a single label like localhost.
+pub(crate) fn host<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, Host, E> {
+    let (next_input, labels) = separated_list1(tag("."), alphanumerichyphen1)(input)
+        .map_err(|e: NomErr<E>| e.map(|_| invalid_host(input)))?;
+    match labels.last() {
+        // a domain name like example.org ends in an alphabetic top-level label
+        // and may be written fully qualified with a trailing dot, like example.org.
+        Some(last) if labels.len() > 1 && last.chars().all(|chr| chr.is_ascii_alphabetic()) => {
+            match next_input.strip_prefix('.') {
+                Some(rest) => Ok((rest, Host::HOST(labels.join(".") + "."))),
+                None => Ok((next_input, Host::HOST(labels.join(".")))),
+            }
         }
-        Ok((next_input, Host::HOST(res.0.join("."))))
-    })
+        // otherwise only the first label is taken as a single-label host like localhost
+        _ => Ok((&input[labels[0].len()..], Host::HOST(labels[0].to_owned()))),
+    }
 }
 
-fn alphanumerichyphen1<T>(i: T) -> IResult<T, T>
+// `host` is missing where a name has to start, or starts with a character no
+// name may start with
+fn invalid_host<'a, E: GrammarError<'a>>(input: &'a str) -> E {
+    match input.chars().next() {
+        Some(chr) if !"/?#:".contains(chr) && !chr.is_whitespace() => {
+            invalid_uri_char(input, ErrorKind::AlphaNumeric)
+        }
+        _ => E::from_external_error(
+            input,
+            ErrorKind::AlphaNumeric,
+            Cause::Uri(UriError::InvalidHost(String::new())),
+        ),
+    }
+}
+
+fn alphanumerichyphen1<T, E: nom::error::ParseError<T>>(i: T) -> IResult<T, T, E>
 where
     T: InputTakeAtPosition,
     <T as InputTakeAtPosition>::Item: AsChar,
@@ -123,14 +415,15 @@ where
     i.split_at_position1_complete(
         |item| {
             let char_item = item.as_char();
-            !(char_item == '-') && !char_item.is_alphanum()
+            char_item != '-' && !char_item.is_alphanum()
         },
         ErrorKind::AlphaNumeric,
     )
 }
 
-// TODO: https://url.spec.whatwg.org/#url-code-points
-fn url_code_points<T>(i: T) -> IResult<T, T>
+// pchar: https://tools.ietf.org/html/rfc3986#section-3.3
+// percent-encoded octets are kept as they are
+fn url_code_points<T, E: nom::error::ParseError<T>>(i: T) -> IResult<T, T, E>
 where
     T: InputTakeAtPosition,
     <T as InputTakeAtPosition>::Item: AsChar,
@@ -138,322 +431,2769 @@ where
     i.split_at_position1_complete(
         |item| {
             let char_item = item.as_char();
-            !(char_item == '-') && !char_item.is_alphanum() && !(char_item == '.')
+            !char_item.is_alphanum() && !"-._~!$&'()*+,;=:@%".contains(char_item)
         },
         ErrorKind::AlphaNumeric,
     )
 }
 
-fn host_asterisk(input: &str) -> IResult<&str, Host> {
-    tag("*")(input).and_then(|(next_input, res)| Ok((next_input, Host::ASTERISK)))
+pub(crate) fn host_asterisk<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, Host, E> {
+    tag("*")(input).map(|(next_input, _)| (next_input, Host::ASTERISK))
 }
 
-// only IPv4
-fn ip(input: &str) -> IResult<&str, Host> {
-    tuple((count(terminated(ip_num, tag(".")), 3), ip_num))(input).and_then(|(next_input, res)| {
+/// IPv4address: https://tools.ietf.org/html/rfc3986#section-3.2.2
+pub(crate) fn ip<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, Host, E> {
+    tuple((count(terminated(ip_num, tag(".")), 3), ip_num))(input).map(|(next_input, res)| {
         let mut result: [u8; 4] = [0, 0, 0, 0];
-        res.0
-            .into_iter()
-            .enumerate()
-            .for_each(|(i, v)| result[i] = v);
-        result[3] = res.1;
-        Ok((next_input, Host::IP(result)))
+        for (octet, v) in result.iter_mut().zip(res.0.into_iter().chain(Some(res.1))) {
+            *octet = v;
+        }
+        (next_input, Host::IP(result))
     })
 }
 
-fn ip_num(input: &str) -> IResult<&str, u8> {
-    one_to_three_digits(input).and_then(|(next_input, result)| match result.parse::<u8>() {
-        Ok(n) => Ok((next_input, n)),
-        Err(_) => Err(NomErr::Error(Error::new(next_input, ErrorKind::Digit))), // TODO: use https://docs.rs/nom/6.0.0/nom/error/index.html to add error context
-    })
+// four numbers separated by dots are an address, so an octet out of range fails
+// with `UriError::InvalidIpv4Octet` rather than being taken for a label of a name
+fn dotted_quad<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, Host, E> {
+    let (rest, address) = recognize(pair(count(pair(digit1, tag(".")), 3), digit1))(input)?;
+    // numeric labels of a longer name, like 1.2.3.4.example.org
+    if rest.starts_with(|chr: char| chr.is_ascii_alphanumeric() || chr == '-' || chr == '.') {
+        return Err(NomErr::Error(E::from_error_kind(rest, ErrorKind::Digit)));
+    }
+    let mut offset = 0;
+    for octet in address.split('.') {
+        if octet.len() > 3 {
+            return Err(NomErr::Failure(E::from_external_error(
+                &input[offset..],
+                ErrorKind::Digit,
+                Cause::Uri(UriError::InvalidIpv4Octet(saturating_u32(octet))),
+            )));
+        }
+        offset += octet.len() + 1;
+    }
+    cut(ip)(input)
 }
 
-fn version(input: &str) -> IResult<&str, &str> {
-    tag("HTTP/1.1")(input)
+/// IP-literal: https://tools.ietf.org/html/rfc3986#section-3.2.2
+/// The last 32 bits may be written as a dotted quad, like the IPv4-mapped
+/// `[::ffff:192.168.0.1]`. Once the bracket is open, anything but a valid
+/// address fails with `UriError::InvalidIpv6`.
+pub(crate) fn ipv6<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, Host, E> {
+    let (rest, address) = preceded(
+        tag("["),
+        take_while(|chr: char| chr.is_ascii_hexdigit() || chr == ':' || chr == '.'),
+    )(input)?;
+    let literal_len = match rest.strip_prefix(']') {
+        Some(_) => input.len() - rest.len() + 1,
+        None => input.len() - rest.len(),
+    };
+    match ipv6_address(address) {
+        Some(segments) if rest.starts_with(']') => Ok((&rest[1..], Host::IPV6(segments))),
+        _ => Err(NomErr::Failure(E::from_external_error(
+            input,
+            ErrorKind::Verify,
+            Cause::Uri(UriError::InvalidIpv6(input[..literal_len].to_owned())),
+        ))),
+    }
 }
 
-fn headers(input: &str) -> IResult<&str, Headers> {
-    many0(header)(input)
+// the 16-bit pieces before or after a `::`, where only the very last one may be
+// a dotted quad standing for two pieces
+fn ipv6_pieces(part: &str, may_end_in_ipv4: bool) -> Option<Vec<u16>> {
+    if part.is_empty() {
+        return Some(Vec::new());
+    }
+    let pieces = part.split(':').collect::<Vec<&str>>();
+    let mut segments = Vec::new();
+    for (i, piece) in pieces.iter().enumerate() {
+        if piece.contains('.') {
+            match ip::<()>(piece) {
+                Ok(("", Host::IP(octets))) if may_end_in_ipv4 && i == pieces.len() - 1 => {
+                    segments.push(u16::from_be_bytes([octets[0], octets[1]]));
+                    segments.push(u16::from_be_bytes([octets[2], octets[3]]));
+                }
+                _ => return None,
+            }
+        } else if (1..=4).contains(&piece.len()) {
+            segments.push(u16::from_str_radix(piece, 16).ok()?);
+        } else {
+            return None;
+        }
+    }
+    Some(segments)
 }
 
-fn header(input: &str) -> IResult<&str, Header> {
-    separated_pair(
-        alphanumerichyphen1,
-        spaced_colon,
-        terminated(take_while(not_newline), newline),
-    )(input)
-    .and_then(|(next_input, res)| Ok((next_input, (res.0.to_owned(), res.1.to_owned()))))
+fn ipv6_address(address: &str) -> Option<[u16; 8]> {
+    let (head, tail) = match address.find("::") {
+        Some(i) => (&address[..i], Some(&address[i + 2..])),
+        None => (address, None),
+    };
+    let head = ipv6_pieces(head, tail.is_none())?;
+    let mut segments = [0; 8];
+    match tail {
+        None if head.len() == 8 => segments.copy_from_slice(&head),
+        // `::` stands for at least one piece of zeros
+        Some(tail) if !tail.contains("::") => {
+            let tail = ipv6_pieces(tail, true)?;
+            if head.len() + tail.len() > 7 {
+                return None;
+            }
+            segments[..head.len()].copy_from_slice(&head);
+            segments[8 - tail.len()..].copy_from_slice(&tail);
+        }
+        _ => return None,
+    }
+    Some(segments)
 }
 
-fn spaced_colon(input: &str) -> IResult<&str, &str> {
-    delimited(space0, tag(":"), space0)(input)
+// an octet of an IPv4 address
+fn ip_num<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, u8, E> {
+    let (next_input, digits) = take_while_m_n(1, 3, |chr: char| chr.is_ascii_digit())(input)?;
+    match digits.parse() {
+        Ok(n) => Ok((next_input, n)),
+        Err(_) => Err(NomErr::Error(E::from_external_error(
+            next_input,
+            ErrorKind::Digit,
+            Cause::Uri(UriError::InvalidIpv4Octet(saturating_u32(digits))),
+        ))),
+    }
 }
 
-fn not_newline(chr: char) -> bool {
-    chr != '\n'
+fn saturating_u32(digits: &str) -> u32 {
+    digits.parse().unwrap_or(u32::MAX)
 }
 
-// TODO: n to m digits
-
-fn one_to_three_digits(input: &str) -> IResult<&str, String> {
-    many_m_n(1, 3, one_digit)(input)
-        .and_then(|(next_input, result)| Ok((next_input, result.into_iter().collect())))
+pub(crate) fn version<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, Version, E> {
+    alt((
+        map(tag("HTTP/1.1"), |_| Version::HTTP11),
+        map(tag("HTTP/1.0"), |_| Version::HTTP10),
+    ))(input)
 }
 
-fn two_to_four_digits(input: &str) -> IResult<&str, String> {
-    many_m_n(2, 4, one_digit)(input)
-        .and_then(|(next_input, result)| Ok((next_input, result.into_iter().collect())))
+pub(crate) fn headers<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, Headers, E> {
+    many0(header)(input).map(|(next_input, res)| (next_input, res.into()))
 }
 
-fn one_digit(input: &str) -> IResult<&str, char> {
-    one_of("0123456789")(input)
+/// Like `headers`, but names and values borrow from the input instead of being
+/// copied. Only values continued with obs-fold need to be joined into a new
+/// string.
+pub(crate) fn header_slices(input: &str) -> IResult<&str, Vec<(&str, Cow<'_, str>)>> {
+    let mut fields = Vec::new();
+    let mut input = input;
+    loop {
+        let (mut rest, (name, line)) = match parse_header_line(input) {
+            Ok(res) => res,
+            Err(NomErr::Error(_)) => return Ok((input, fields)),
+            Err(e) => return Err(e),
+        };
+        // folds are rare, so they are not collected before being joined
+        let mut value = Cow::Borrowed(line);
+        while let Ok((next_input, fold)) = obs_fold::<Error<&str>>(rest) {
+            let value = value.to_mut();
+            value.push(' ');
+            value.push_str(fold);
+            rest = next_input;
+        }
+        fields.push((name, value));
+        input = rest;
+    }
 }
 
-fn host_or_ip(input: &str) -> IResult<&str, Host> {
-    alt((host, ip))(input)
+/// Like `headers`, but keyed by lowercased name for constant-time lookups. The
+/// values of each name keep their order, the order across names is lost.
+pub(crate) fn parse_headers_map(input: &str) -> IResult<&str, HashMap<String, Vec<String>>> {
+    fold_many0(
+        header,
+        HashMap::new(),
+        |mut map: HashMap<String, Vec<String>>, (name, value)| {
+            map.entry(name.to_ascii_lowercase())
+                .or_default()
+                .push(value);
+            map
+        },
+    )(input)
 }
 
-fn port(input: &str) -> IResult<&str, u16> {
-    preceded(tag(":"), two_to_four_digits)(input).and_then(|(next_input, res)| {
-        match res.parse::<u16>() {
-            Ok(n) => Ok((next_input, n)),
-            Err(_) => Err(NomErr::Error(Error::new(next_input, ErrorKind::Digit))), // TODO: use https://docs.rs/nom/6.0.0/nom/error/index.html to add error context
+/// Like `headers`, but lines that are not a valid header are skipped and returned
+/// separately, up to the empty line ending the header section.
+pub(crate) fn headers_skipping_malformed<'a, E: GrammarError<'a>>(
+    input: &'a str,
+) -> IResult<&'a str, (Headers, Vec<String>), E> {
+    let mut fields = Vec::new();
+    let mut malformed = Vec::new();
+    let mut input = input;
+    while !input.is_empty() && line_ending::<E>(input).is_err() {
+        match header::<E>(input) {
+            Ok((next_input, res)) => {
+                fields.push(res);
+                input = next_input;
+            }
+            Err(NomErr::Error(_)) => {
+                let (next_input, line) = terminated(take_while(not_newline), line_ending)(input)?;
+                malformed.push(line.to_owned());
+                input = next_input;
+            }
+            Err(e) => return Err(e),
         }
-    })
+    }
+    Ok((input, (fields.into(), malformed)))
+}
+
+pub(crate) fn header<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, Header, E> {
+    context("header", pair(parse_header_line, many0(obs_fold)))(input).map(
+        |(next_input, ((name, line), folds))| {
+            // continuation lines (obs-fold) are joined to the value with a single space
+            let mut value = line.to_owned();
+            for fold in folds {
+                value.push(' ');
+                value.push_str(fold);
+            }
+            (next_input, (name.to_owned(), value))
+        },
+    )
 }
 
-fn path(input: &str) -> IResult<&str, String> {
+/// A single header line as slices of the input, without allocating. Unlike
+/// `header`, obsolete continuation lines are not folded into the value but
+/// left in the remaining input. Whitespace around the value is not part of it,
+/// so a value of only spaces is empty.
+pub(crate) fn parse_header_line<'a, E: GrammarError<'a>>(
+    input: &'a str,
+) -> IResult<&'a str, (&'a str, &'a str), E> {
     tuple((
-        tag("/"),
-        many0(terminated(url_code_points, tag("/"))),
-        opt(url_code_points),
+        context("header name", field_name),
+        context("header colon", spaced_colon),
+        context("header value", field_value),
     ))(input)
-    .and_then(|(next_input, res)| {
-        println!("res: {:?}", res);
-        let mut path = res.0.to_owned();
-        path.push_str(&res.1.join("/"));
-        path.push_str("/");
-        if let Some(last) = res.2 {
-            path.push_str(last);
-        }
-        Ok((next_input, path))
+    .map(|(next_input, (name, _, value))| (next_input, (name, value.trim_end_matches([' ', '\t']))))
+}
+
+// field-name, which fails with `HeaderError::EmptyName` before a colon
+fn field_name<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    alphanumerichyphen1(input).map_err(|e: NomErr<E>| {
+        e.map(|_| {
+            let cause = match input.chars().next() {
+                None => Cause::Header(HeaderError::MissingLineEnding),
+                Some(':') => Cause::Header(HeaderError::EmptyName),
+                Some(_) => Cause::NameChar,
+            };
+            E::from_external_error(input, ErrorKind::AlphaNumeric, cause)
+        })
     })
 }
 
-fn query_params(input: &str) -> IResult<&str, QueryParams> {
-    tuple((
-        tag("?"),
-        alphanumerichyphen1, // TODO: https://infra.spec.whatwg.org/#ascii-code-point
-        tag("="),
-        alphanumerichyphen1,
-        many0(tuple((
-            tag("&"),
-            alphanumerichyphen1,
-            tag("="),
-            alphanumerichyphen1,
-        ))),
-    ))(input)
-    .and_then(|(next_input, res)| {
-        let mut qps = Vec::new();
+// the colon after the name, which may be preceded by whitespace, see
+// `Warning::WhitespaceBeforeColon`; when the line has a colon further on, the
+// name ends in a character it may not contain
+fn spaced_colon<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    delimited(space0, tag(":"), space0)(input).map_err(|e: NomErr<E>| {
+        e.map(|_| {
+            let colon = input.trim_start_matches([' ', '\t']);
+            match input.find('\n') {
+                None => E::from_external_error(
+                    colon,
+                    ErrorKind::Tag,
+                    Cause::Header(HeaderError::MissingLineEnding),
+                ),
+                Some(end) if input[..end].contains(':') => {
+                    E::from_external_error(input, ErrorKind::Tag, Cause::NameChar)
+                }
+                Some(_) => E::from_external_error(
+                    colon,
+                    ErrorKind::Tag,
+                    Cause::Header(HeaderError::MissingColon),
+                ),
+            }
+        })
+    })
+}
 
-        qps.push((res.1.to_owned(), res.3.to_owned()));
+// field-value and the line ending after it, which only a CR right before the LF
+// may be part of; a NUL fails with `HeaderError::InvalidValueChar`
+fn field_value<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    let (rest, value) = take_while(value_char)(input)?;
+    let (next_input, _) = line_ending(rest).map_err(|e: NomErr<E>| {
+        e.map(|_| {
+            let cause = match rest {
+                "" => Cause::Header(HeaderError::MissingLineEnding),
+                _ => Cause::ValueChar,
+            };
+            E::from_external_error(rest, ErrorKind::Tag, cause)
+        })
+    })?;
+    Ok((next_input, value))
+}
 
-        for qp in res.4 {
-            qps.push((qp.1.to_owned(), qp.3.to_owned()));
-        }
+/// obs-fold: https://tools.ietf.org/html/rfc7230#section-3.2.4
+fn obs_fold<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    preceded(
+        take_while1(|chr| chr == ' ' || chr == '\t'),
+        terminated(take_while(value_char), line_ending),
+    )(input)
+}
 
-        Ok((next_input, qps))
-    })
+fn not_newline(chr: char) -> bool {
+    chr != '\n' && chr != '\r'
+}
+
+// anything but the CR, LF and NUL a `HeaderValue` may not contain
+fn value_char(chr: char) -> bool {
+    not_newline(chr) && chr != '\0'
+}
+
+/// Optional whitespace (OWS): https://tools.ietf.org/html/rfc7230#section-3.2.3
+pub(crate) fn ows(input: &str) -> IResult<&str, &str> {
+    take_while(|chr| chr == ' ' || chr == '\t')(input)
+}
+
+/// token: https://tools.ietf.org/html/rfc7230#section-3.2.6
+pub(crate) fn token(input: &str) -> IResult<&str, &str> {
+    take_while1(is_tchar)(input)
 }
 
-fn fragment(input: &str) -> IResult<&str, &str> {
-    tuple((tag("#"), alphanumerichyphen1))(input)
-        .and_then(|(next_input, res)| Ok((next_input, res.1)))
+pub(crate) fn is_tchar(chr: char) -> bool {
+    chr.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(chr)
 }
 
-// fn uri(input: &str) -> IResult<&str, URI> {
-//     // TODO: optional (optional (scheme, authority, host or ip, port), relative path and query), or *
-// }
+/// quoted-string: https://tools.ietf.org/html/rfc7230#section-3.2.6
+/// The surrounding quotes are stripped and quoted-pairs are unescaped.
+pub(crate) fn quoted_string(input: &str) -> IResult<&str, String> {
+    delimited(
+        tag("\""),
+        many0(alt((preceded(tag("\\"), anychar), none_of("\"\\")))),
+        tag("\""),
+    )(input)
+    .map(|(next_input, res)| (next_input, res.into_iter().collect()))
+}
 
-fn request_method(input: &str) -> IResult<&str, Method> {
+pub(crate) fn token_or_quoted_string(input: &str) -> IResult<&str, String> {
     alt((
-        tag_no_case("GET"),
-        tag_no_case("POST"),
-        tag_no_case("PUT"),
-        tag_no_case("DELETE"),
-        tag_no_case("CONNECT"),
-        tag_no_case("OPTIONS"),
-        tag_no_case("TRACE"),
+        |i| token(i).map(|(next_input, res)| (next_input, res.to_owned())),
+        quoted_string,
     ))(input)
-    .and_then(|(next_input, res)| Ok((next_input, res.into())))
 }
-/// REQUEST LINE: https://tools.ietf.org/html/rfc7230#section-3.1.1
-// fn parse_http(input: &str) -> IResult<&str, Request> {
-//     Ok(("", Request {}))
-// }
 
-#[test]
-fn test_request_method() {
-    assert_eq!(request_method("GET 1234"), Ok((" 1234", Method::GET)));
-    assert_eq!(
-        request_method("1234"),
-        Err(NomErr::Error(Error::new("1234", ErrorKind::Tag)))
-    );
-    assert_eq!(request_method("PUT POST"), Ok((" POST", Method::PUT)));
+/// #rule lists: https://tools.ietf.org/html/rfc7230#section-7
+/// Empty list elements are skipped, as recipients are required to accept them.
+pub(crate) fn list<'a, O, F>(element: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    map(
+        delimited(
+            ows,
+            separated_list0(tuple((ows, tag(","), ows)), opt(element)),
+            ows,
+        ),
+        |res| res.into_iter().flatten().collect(),
+    )
 }
 
-#[test]
-fn test_authority() {
-    assert_eq!(
-        authority("username:password@zupzup.org"),
-        Ok(("zupzup.org", Some(("username", Some("password")))))
-    );
-    assert_eq!(
-        authority("username@zupzup.org"),
-        Ok(("zupzup.org", Some(("username", None))))
-    );
-    assert_eq!(authority("zupzup.org"), Ok(("zupzup.org", None)));
-    assert_eq!(authority(":zupzup.org"), Ok((":zupzup.org", None)));
-    assert_eq!(
-        authority("username:passwordzupzup.org"),
-        Ok(("username:passwordzupzup.org", None))
-    );
-    assert_eq!(authority("@zupzup.org"), Ok(("@zupzup.org", None)));
+/// Parameters as shared by media types, dispositions, transfer codings, Link and Prefer:
+/// `*( OWS ";" OWS [ name [ OWS "=" OWS value ] ] )`, where value is a token or a
+/// quoted-string. Flag-style parameters without a value yield an empty value and
+/// stray semicolons are skipped.
+pub(crate) fn parameters(input: &str) -> IResult<&str, Parameters> {
+    many0(preceded(tuple((ows, tag(";"), ows)), opt(parameter)))(input)
+        .map(|(next_input, res)| (next_input, res.into_iter().flatten().collect()))
 }
 
-#[test]
-fn test_host() {
-    assert_eq!(
-        host("localhost:8080"),
-        Ok((":8080", Host::HOST("localhost".to_string())))
-    );
-    assert_eq!(
-        host("example.org:8080"),
-        Ok((":8080", Host::HOST("example.org".to_string())))
-    );
-    assert_eq!(
-        host("some-subsite.example.org:8080"),
-        Ok((":8080", Host::HOST("some-subsite.example.org".to_string())))
-    );
-    assert_eq!(
-        host("example.123"),
-        Ok((".123", Host::HOST("example".to_string())))
-    );
-    assert_eq!(
-        host("$$$.com"),
-        Err(NomErr::Error(Error::new(
-            "$$$.com",
-            ErrorKind::AlphaNumeric
-        )))
-    );
-    assert_eq!(
-        host(".com"),
-        Err(NomErr::Error(Error::new(".com", ErrorKind::AlphaNumeric)))
-    );
+fn parameter(input: &str) -> IResult<&str, Parameter> {
+    pair(
+        token,
+        opt(preceded(
+            tuple((ows, tag("="), ows)),
+            token_or_quoted_string,
+        )),
+    )(input)
+    .map(|(next_input, res)| (next_input, (res.0.to_owned(), res.1.unwrap_or_default())))
 }
 
-#[test]
-fn test_ipv4() {
-    assert_eq!(
-        ip("192.168.0.1:8080"),
-        Ok((":8080", Host::IP([192, 168, 0, 1])))
-    );
-    assert_eq!(ip("0.0.0.0:8080"), Ok((":8080", Host::IP([0, 0, 0, 0]))));
-    assert_eq!(
-        ip("1924.168.0.1:8080"),
-        Err(NomErr::Error(Error::new("4.168.0.1:8080", ErrorKind::Tag)))
-    );
-    assert_eq!(
-        ip("192.168.0000.144:8080"),
-        Err(NomErr::Error(Error::new("0.144:8080", ErrorKind::Tag)))
-    );
-    assert_eq!(
-        ip("192.168.0.1444:8080"),
-        Ok(("4:8080", Host::IP([192, 168, 0, 144])))
-    );
-    assert_eq!(
-        ip("192.168.0:8080"),
-        Err(NomErr::Error(Error::new(":8080", ErrorKind::Tag)))
-    );
-    assert_eq!(
-        ip("999.168.0.0:8080"),
-        Err(NomErr::Error(Error::new(".168.0.0:8080", ErrorKind::Digit)))
-    );
+/// qvalue: https://tools.ietf.org/html/rfc7231#section-5.3.1
+pub(crate) fn qvalue(input: &str) -> IResult<&str, f32> {
+    map_res(
+        recognize(alt((
+            pair(
+                tag("0"),
+                opt(pair(
+                    tag("."),
+                    take_while_m_n(0, 3, |chr: char| chr.is_ascii_digit()),
+                )),
+            ),
+            pair(
+                tag("1"),
+                opt(pair(tag("."), take_while_m_n(0, 3, |chr| chr == '0'))),
+            ),
+        ))),
+        str::parse,
+    )(input)
 }
 
-#[test]
-fn test_header() {
-    assert_eq!(
-        header("Content-Type: application/json\nabc"),
-        Ok((
-            "abc",
-            ("Content-Type".to_string(), "application/json".to_string())
-        ))
-    );
-    assert_eq!(
-        header("Content-Type  :          application/json\nabc"),
-        Ok((
-            "abc",
-            ("Content-Type".to_string(), "application/json".to_string())
-        ))
-    );
-    assert_eq!(
-        header("Some1:123$$$%*%*\nabc"),
-        Ok(("abc", ("Some1".to_string(), "123$$$%*%*".to_string())))
-    );
+/// weight = OWS ";" OWS "q=" qvalue
+pub(crate) fn weight(input: &str) -> IResult<&str, f32> {
+    preceded(tuple((ows, tag(";"), ows, tag_no_case("q="))), qvalue)(input)
 }
 
-#[test]
-fn test_headers() {
-    assert_eq!(
-        headers("Content-Type: application/json\nAuthorization: pw\nHost: zupzup.org\nabc"),
-        Ok((
-            "abc",
-            vec![
-                ("Content-Type".to_string(), "application/json".to_string()),
-                ("Authorization".to_string(), "pw".to_string()),
-                ("Host".to_string(), "zupzup.org".to_string())
-            ]
-        ))
-    );
+// ip first, as host would otherwise take the first octet of an address as a label
+pub(crate) fn host_or_ip<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, Host, E> {
+    alt((
+        context("ip", ipv6),
+        context("ip", dotted_quad),
+        context("host", host),
+    ))(input)
 }
 
-#[test]
-fn test_path() {
-    assert_eq!(path("/a/b/c?d"), Ok(("?d", "/a/b/c".to_string())));
-    assert_eq!(path("/a/b/c/?d"), Ok(("?d", "/a/b/c/".to_string())));
-    assert_eq!(path("/a/b-c-d/c/?d"), Ok(("?d", "/a/b-c-d/c/".to_string())));
-    assert_eq!(path("/a/1234/c/?d"), Ok(("?d", "/a/1234/c/".to_string())));
-    assert_eq!(
-        path("/a/1234/c.txt?d"),
-        Ok(("?d", "/a/1234/c.txt".to_string()))
-    );
+/// port: https://tools.ietf.org/html/rfc3986#section-3.2.3
+/// The port runs up to the end of the authority. A missing colon fails with
+/// `UriError::InvalidPort`, anything after it that is not a port in range
+/// fails for good with `UriError::InvalidPort` or `UriError::PortOutOfRange`,
+/// with `ErrorKind::Digit` at the first digit.
+pub(crate) fn port<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, u16, E> {
+    let digits = match input.strip_prefix(':') {
+        Some(digits) => digits,
+        None => {
+            return Err(NomErr::Error(E::from_external_error(
+                input,
+                ErrorKind::Tag,
+                Cause::Uri(UriError::InvalidPort(String::new())),
+            )))
+        }
+    };
+    let len = digits
+        .find(['/', '?', '#', ' ', '\r', '\n'])
+        .unwrap_or(digits.len());
+    let (port, next_input) = digits.split_at(len);
+    let error = if port.is_empty() || !port.bytes().all(|b| b.is_ascii_digit()) {
+        UriError::InvalidPort(port.to_owned())
+    } else {
+        match port.parse() {
+            Ok(n) => return Ok((next_input, n)),
+            Err(_) => UriError::PortOutOfRange(saturating_u32(port)),
+        }
+    };
+    Err(NomErr::Failure(E::from_external_error(
+        digits,
+        ErrorKind::Digit,
+        Cause::Uri(error),
+    )))
 }
 
-#[test]
-fn test_query_params() {
-    assert_eq!(
-        query_params("?bla=5&blub=val#yay"),
-        Ok((
-            "#yay",
-            vec![
-                ("bla".to_string(), "5".to_string()),
-                ("blub".to_string(), "val".to_string())
-            ]
-        ))
-    );
+/// path-abempty: https://tools.ietf.org/html/rfc3986#section-3.3
+/// At least one segment, each of which may be empty, like in `//a`. An absent
+/// path is left to the callers, so `http://host` and `http://host/` differ.
+pub(crate) fn path<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, String, E> {
+    recognize(many1(preceded(tag("/"), opt(url_code_points))))(input)
+        .map(|(next_input, res)| (next_input, res.to_owned()))
+}
 
-    assert_eq!(
-        query_params("?bla-blub=arr-arr#yay"),
-        Ok((
-            "#yay",
-            vec![("bla-blub".to_string(), "arr-arr".to_string()),]
-        ))
-    );
+/// query: https://tools.ietf.org/html/rfc3986#section-3.4
+/// A `?` without any pairs is an empty query, which is kept apart from no query
+/// at all. The pairs are split like a form body, see `parse_form_body`, but kept
+/// undecoded. Brackets are accepted unencoded, as browsers send keys like
+/// `list[]` that way.
+pub(crate) fn query_params<'a, E: GrammarError<'a>>(
+    input: &'a str,
+) -> IResult<&'a str, QueryParams, E> {
+    preceded(
+        tag("?"),
+        take_while(|chr: char| {
+            chr.is_ascii_alphanumeric() || "-._~!$&'()*+,;=:@%/?[]".contains(chr)
+        }),
+    )(input)
+    .map(|(next_input, query)| {
+        (
+            next_input,
+            uri::split_pairs(query)
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                .collect(),
+        )
+    })
 }
 
-#[test]
+// the query as received, without the `?`, and split into pairs
+fn query<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, (String, QueryParams), E> {
+    let (next_input, pairs) = query_params(input)?;
+    let raw = &input[1..input.len() - next_input.len()];
+    Ok((next_input, (raw.to_owned(), pairs)))
+}
+
+/// fragment: https://tools.ietf.org/html/rfc3986#section-3.5
+/// Once the `#` is seen, a missing or malformed fragment fails for good.
+pub(crate) fn fragment<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    let (rest, _) = tag("#")(input)?;
+    alphanumerichyphen1(rest)
+        .map_err(|_: NomErr<E>| NomErr::Failure(invalid_uri_char(rest, ErrorKind::AlphaNumeric)))
+}
+
+// a failure at the first character of `input`, which the grammar of the target
+// does not allow there
+fn invalid_uri_char<'a, E: GrammarError<'a>>(input: &'a str, kind: ErrorKind) -> E {
+    match input.chars().next() {
+        Some(_) => E::from_external_error(input, kind, Cause::UriChar),
+        None => E::from_error_kind(input, kind),
+    }
+}
+
+/// request-target: https://tools.ietf.org/html/rfc7230#section-5.3
+/// The target ends at the space before the version, any other character it
+/// ends at is not allowed in it.
+pub(crate) fn target<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, URI, E> {
+    context("target", |input| {
+        let (rest, uri) = uri(input)?;
+        match rest.chars().next() {
+            None | Some(' ') => Ok((rest, uri)),
+            Some(_) => Err(NomErr::Error(invalid_uri_char(rest, ErrorKind::Eof))),
+        }
+    })(input)
+}
+
+pub(crate) fn uri<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, URI, E> {
+    alt((absolute_uri, asterisk_uri, origin_uri, authority_uri))(input)
+}
+
+// a scheme rules out the other forms, so everything after it is committed to
+fn absolute_uri<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, URI, E> {
+    let (input, scheme) = context("scheme", scheme)(input)?;
+    cut(tuple((
+        context("authority", authority),
+        host_or_ip,
+        opt(context("port", port)),
+        opt(context("path", path)),
+        opt(context("query", query)),
+        opt(context("fragment", fragment)),
+    )))(input)
+    .map(|(next_input, res)| {
+        let (raw_query, query) = res.4.unzip();
+        (
+            next_input,
+            URI {
+                scheme: Some(scheme),
+                authority: res
+                    .0
+                    .map(|(user, pw)| (Some(user.to_owned()), pw.map(|pw| pw.to_owned()))),
+                host: Some(res.1),
+                port: res.2,
+                path: res.3,
+                query,
+                raw_query,
+                fragment: res.5.map(|f| f.to_owned()),
+            },
+        )
+    })
+}
+
+fn origin_uri<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, URI, E> {
+    tuple((
+        context("path", path),
+        opt(context("query", query)),
+        opt(context("fragment", fragment)),
+    ))(input)
+    .map(|(next_input, res)| {
+        let (raw_query, query) = res.1.unzip();
+        (
+            next_input,
+            URI {
+                scheme: None,
+                authority: None,
+                host: None,
+                port: None,
+                path: Some(res.0),
+                query,
+                raw_query,
+                fragment: res.2.map(|f| f.to_owned()),
+            },
+        )
+    })
+}
+
+fn authority_uri<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, URI, E> {
+    pair(host_or_ip, context("port", port))(input).map(|(next_input, res)| {
+        (
+            next_input,
+            URI {
+                scheme: None,
+                authority: None,
+                host: Some(res.0),
+                port: Some(res.1),
+                path: None,
+                query: None,
+                raw_query: None,
+                fragment: None,
+            },
+        )
+    })
+}
+
+fn asterisk_uri<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, URI, E> {
+    context("host", host_asterisk)(input).map(|(next_input, res)| {
+        (
+            next_input,
+            URI {
+                scheme: None,
+                authority: None,
+                host: Some(res),
+                port: None,
+                path: None,
+                query: None,
+                raw_query: None,
+                fragment: None,
+            },
+        )
+    })
+}
+
+/// method: https://tools.ietf.org/html/rfc7231#section-4.1
+/// Extension methods are accepted as long as they start with a letter.
+pub(crate) fn request_method<'a, E: GrammarError<'a>>(
+    input: &'a str,
+) -> IResult<&'a str, Method, E> {
+    match token(input) {
+        Ok((next_input, res)) if res.starts_with(|chr: char| chr.is_ascii_alphabetic()) => {
+            Ok((next_input, res.into()))
+        }
+        _ => Err(NomErr::Error(E::from_error_kind(input, ErrorKind::Tag))),
+    }
+}
+
+fn line_ending<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    alt((tag("\r\n"), tag("\n")))(input)
+}
+
+/// REQUEST LINE: https://tools.ietf.org/html/rfc7230#section-3.1.1
+/// As `mode` reads it.
+pub(crate) fn request_line_in<'a, E: GrammarError<'a>>(
+    input: &'a str,
+    mode: ParseMode,
+) -> IResult<&'a str, RequestLine, E> {
+    match mode {
+        ParseMode::Lenient => request_line_with(input, spaces),
+        ParseMode::Strict => {
+            request_line_with(input, single_space).map(|(next_input, mut res)| {
+                // the method is case-sensitive, so `get` is not GET:
+                // https://tools.ietf.org/html/rfc7230#section-3.1.1
+                let token = &input[..input.find(' ').unwrap_or(0)];
+                if res.method.as_str() != token {
+                    res.method = Method::EXTENSION(token.to_owned());
+                }
+                (next_input, res)
+            })
+        }
+    }
+}
+
+fn single_space<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    tag(" ")(input)
+}
+
+// a run of spaces, as recipients may split the request line on whitespace:
+// https://tools.ietf.org/html/rfc7230#section-3.5
+fn spaces<'a, E: GrammarError<'a>>(input: &'a str) -> IResult<&'a str, &'a str, E> {
+    recognize(pair(tag(" "), take_while(|chr| chr == ' ')))(input)
+}
+
+fn request_line_with<'a, E: GrammarError<'a>>(
+    input: &'a str,
+    separator: fn(&'a str) -> IResult<&'a str, &'a str, E>,
+) -> IResult<&'a str, RequestLine, E> {
+    context(
+        "request line",
+        tuple((
+            context("method", terminated(request_method, separator)),
+            target,
+            context(
+                "version",
+                preceded(separator, terminated(version, line_ending)),
+            ),
+        )),
+    )(input)
+    .map(|(next_input, res)| {
+        (
+            next_input,
+            RequestLine {
+                method: res.0,
+                uri: res.1,
+                version: res.2,
+            },
+        )
+    })
+}
+
+/// How closely a request has to follow RFC 7230, see `ParseOptions::mode`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ParseMode {
+    /// Enforce RFC 7230 to the letter: line endings are CRLF, there is no
+    /// whitespace before the colon of a header, the parts of the request line
+    /// are separated by a single space, methods are case-sensitive (`get` is
+    /// an extension method), and obsolete line folding is rejected.
+    Strict,
+    /// Accept what browsers and tools like curl actually send: bare LF line
+    /// endings, whitespace before the colon, runs of spaces in the request
+    /// line, lowercase methods and obsolete line folding. Earlier releases
+    /// required a single space between the parts of the request line, so
+    /// `GET  / HTTP/1.1` used to be rejected; `parse_http_verbose` accepts it
+    /// too.
+    #[default]
+    Lenient,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseOptions {
+    /// Whether to reject constructs the RFCs deprecate instead of tolerating
+    /// them. Lenient by default.
+    pub mode: ParseMode,
+    /// Skip header lines that fail to parse instead of failing the whole request.
+    /// The skipped lines are kept in `Request::malformed_headers`.
+    pub skip_malformed_headers: bool,
+    /// The maximum number of segments in the request target's path.
+    pub max_path_segments: usize,
+    /// When set, absolute-form targets with a scheme not in the list are rejected.
+    /// Schemes are compared case-insensitively.
+    pub allowed_schemes: Option<Vec<String>>,
+    /// Take everything after the head of a response as its body when there is
+    /// neither Content-Length nor Transfer-Encoding, for connection-close framing.
+    /// Only used by `parse_response`.
+    pub read_body_to_end: bool,
+    /// The maximum length of the extensions on a chunk-size line of a chunked body.
+    pub max_chunk_extension_len: usize,
+    /// When set, bodies longer than this are rejected before they are copied, so
+    /// a server can answer with 413 (Payload Too Large).
+    pub max_body_len: Option<u64>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            mode: ParseMode::default(),
+            skip_malformed_headers: false,
+            max_path_segments: 255,
+            allowed_schemes: None,
+            read_body_to_end: false,
+            max_chunk_extension_len: 1024,
+            max_body_len: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn strict() -> Self {
+        ParseOptions {
+            mode: ParseMode::Strict,
+            ..ParseOptions::default()
+        }
+    }
+}
+
+// the start and end of a run of spaces
+type Run = Option<(usize, usize)>;
+
+// the first two runs of spaces in a request line, which end the method and
+// the target
+fn separators(line: &str) -> (Run, Run) {
+    let run = |from: usize| {
+        line[from..].find(' ').map(|start| {
+            let start = from + start;
+            (
+                start,
+                line.len() - line[start..].trim_start_matches(' ').len(),
+            )
+        })
+    };
+    let first = run(0);
+    (first, first.and_then(|(_, end)| run(end)))
+}
+
+/// Where in the input parsing failed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Position {
+    /// The byte offset into the input.
+    pub offset: usize,
+    /// The line, counting from 1. Lines end in LF, with or without CR.
+    pub line: usize,
+    /// The character in the line, counting from 1.
+    pub column: usize,
+}
+
+impl Position {
+    /// The position of the byte `offset` in `input`, which has to be on a
+    /// character boundary.
+    pub fn new(input: &str, offset: usize) -> Position {
+        let before = input.get(..offset).unwrap_or(input);
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+/// The error types the grammar of message heads is generic over: nom's
+/// `Error`, `VerboseError` for `parse_http_verbose`, and `HeadError`, which
+/// keeps what is wrong for `ParseError`. The rules of the grammar are labeled
+/// with `context`.
+pub(crate) trait GrammarError<'a>:
+    nom::error::ParseError<&'a str> + ContextError<&'a str> + FromExternalError<&'a str, Cause>
+{
+}
+
+impl<'a, E> GrammarError<'a> for E where
+    E: nom::error::ParseError<&'a str> + ContextError<&'a str> + FromExternalError<&'a str, Cause>
+{
+}
+
+/// What is wrong with the part of a head the grammar failed in.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Cause {
+    Uri(UriError),
+    /// `UriError::InvalidChar` for the character the error is at.
+    UriChar,
+    Header(HeaderError),
+    /// `HeaderError::InvalidNameChar` for the character the error is at.
+    NameChar,
+    /// `HeaderError::InvalidValueChar` for the character the error is at.
+    ValueChar,
+}
+
+/// The error of the head grammar for `ParseError::from_head`: where it failed,
+/// the rules it failed in and what is wrong.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct HeadError<'a> {
+    input: &'a str,
+    kind: ErrorKind,
+    rules: Vec<&'static str>,
+    cause: Option<Cause>,
+}
+
+impl<'a> nom::error::ParseError<&'a str> for HeadError<'a> {
+    fn from_error_kind(input: &'a str, kind: ErrorKind) -> Self {
+        HeadError {
+            input,
+            kind,
+            rules: Vec::new(),
+            cause: None,
+        }
+    }
+
+    // the innermost error tells the most
+    fn append(_: &'a str, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<'a> ContextError<&'a str> for HeadError<'a> {
+    fn add_context(input: &'a str, rule: &'static str, mut other: Self) -> Self {
+        // `input` starts with the target, which ends at a space or line ending
+        if rule == "target" && other.cause.is_none() {
+            let end = input.find([' ', '\r', '\n']).unwrap_or(input.len());
+            other.cause = Some(Cause::Uri(UriError::Invalid(input[..end].to_owned())));
+        }
+        other.rules.push(rule);
+        other
+    }
+}
+
+impl<'a> FromExternalError<&'a str, Cause> for HeadError<'a> {
+    fn from_external_error(input: &'a str, kind: ErrorKind, cause: Cause) -> Self {
+        HeadError {
+            input,
+            kind,
+            rules: Vec::new(),
+            cause: Some(cause),
+        }
+    }
+}
+
+/// The error of the crate's `Result`-returning parsers. None of the parsers
+/// panic on any input, however malformed, truncated or large it is; every
+/// failure is reported as a `ParseError` or the error of the nom parser.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input does not match the grammar at the given position.
+    Syntax { kind: ErrorKind, position: Position },
+    /// The input ended before the request was complete.
+    Incomplete,
+    /// A header line starts with whitespace: https://tools.ietf.org/html/rfc7230#section-3.2.4
+    ObsoleteLineFolding,
+    /// In strict mode, a line of the head ends in LF without CR:
+    /// https://tools.ietf.org/html/rfc7230#section-3.5
+    BareLineFeed,
+    /// In strict mode, whitespace between the named header's name and the
+    /// colon: https://tools.ietf.org/html/rfc7230#section-3.2.4
+    WhitespaceBeforeColon(String),
+    /// The request line or a header contains a NUL byte.
+    NulByte,
+    /// The path has more segments than `ParseOptions::max_path_segments` allows.
+    PathTooDeep,
+    /// The target's scheme is not in `ParseOptions::allowed_schemes`.
+    DisallowedScheme(String),
+    /// In strict mode, the Host header names a different authority than an
+    /// absolute-form target: https://tools.ietf.org/html/rfc7230#section-5.4
+    HostMismatch,
+    /// Content-Length is given more than once with different values, or more
+    /// than once at all in strict mode. The body cannot be framed safely, so
+    /// the connection should be closed.
+    DuplicateContentLength,
+    /// The chunked coding is not the final Transfer-Encoding of a request, or
+    /// is applied more than once, so the body cannot be framed.
+    InvalidTransferEncoding,
+    /// The body is longer than `ParseOptions::max_body_len`. `len` is the
+    /// announced Content-Length, or for a chunked body the length decoded up to
+    /// and including the chunk that crossed the limit.
+    BodyTooLarge { limit: u64, len: u64 },
+    /// The head is longer than the reader accepts, see `parse_request_from_reader`.
+    HeadTooLarge,
+    /// The body framing is malformed.
+    Body(BodyError),
+    /// Reading the request failed.
+    Io(std::io::ErrorKind),
+    /// The method of the request line is malformed.
+    InvalidMethod { kind: ErrorKind, position: Position },
+    /// The request target or one of its components is malformed. `error` tells
+    /// what is wrong with it.
+    InvalidUri {
+        kind: ErrorKind,
+        position: Position,
+        error: UriError,
+    },
+    /// The HTTP version at the end of the request line is malformed.
+    InvalidVersion { kind: ErrorKind, position: Position },
+    /// The status line of a response is malformed.
+    InvalidStatusLine { kind: ErrorKind, position: Position },
+    /// A header line is malformed. `error` tells what is wrong with it.
+    InvalidHeader {
+        kind: ErrorKind,
+        position: Position,
+        error: HeaderError,
+    },
+    /// The chunked framing or the Content-Length of the body is malformed.
+    InvalidBody { kind: ErrorKind, position: Position },
+}
+
+impl ParseError {
+    pub(crate) fn from_nom(input: &str, err: NomErr<Error<&str>>) -> Self {
+        match err {
+            NomErr::Incomplete(_) => ParseError::Incomplete,
+            NomErr::Error(e) | NomErr::Failure(e) => ParseError::Syntax {
+                kind: e.code,
+                position: Position::new(input, input.len() - e.input.len()),
+            },
+        }
+    }
+
+    // an error of the head grammar, in the part of the head named by the rules
+    // it failed in
+    pub(crate) fn from_head(input: &str, err: NomErr<HeadError>) -> Self {
+        let e = match err {
+            NomErr::Incomplete(_) => return ParseError::Incomplete,
+            NomErr::Error(e) | NomErr::Failure(e) => e,
+        };
+        let kind = e.kind;
+        let offset = input.len() - e.input.len();
+        let position = Position::new(input, offset);
+        let ch = e.input.chars().next().unwrap_or_default();
+        let in_rule = |rule| e.rules.contains(&rule);
+        if in_rule("header") {
+            let error = match e.cause {
+                Some(Cause::Header(error)) => error,
+                Some(Cause::NameChar) => HeaderError::InvalidNameChar { ch, offset },
+                Some(Cause::ValueChar) => HeaderError::InvalidValueChar { ch, offset },
+                _ => HeaderError::MissingLineEnding,
+            };
+            ParseError::InvalidHeader {
+                kind,
+                position,
+                error,
+            }
+        } else if in_rule("status line") {
+            ParseError::InvalidStatusLine { kind, position }
+        } else if in_rule("method") {
+            ParseError::InvalidMethod { kind, position }
+        } else if in_rule("target") {
+            let error = match e.cause {
+                Some(Cause::Uri(error)) => error,
+                _ => UriError::InvalidChar { ch, offset },
+            };
+            ParseError::InvalidUri {
+                kind,
+                position,
+                error,
+            }
+        } else if in_rule("version") {
+            ParseError::InvalidVersion { kind, position }
+        } else {
+            ParseError::Syntax { kind, position }
+        }
+    }
+
+    // `input` is the whole message with `headers`, the error is somewhere in
+    // its body, or `message_body` refused to frame it
+    pub(crate) fn from_body(input: &str, headers: &Headers, err: NomErr<Error<&str>>) -> Self {
+        if let Err(e) = chunked::validate_transfer_encoding(headers) {
+            return e;
+        }
+        match ParseError::from_nom(input, err) {
+            ParseError::Syntax { kind, position } => ParseError::InvalidBody { kind, position },
+            other => other,
+        }
+    }
+
+    /// Where the input does not match the grammar, for syntax errors.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            ParseError::Syntax { position, .. }
+            | ParseError::InvalidMethod { position, .. }
+            | ParseError::InvalidUri { position, .. }
+            | ParseError::InvalidVersion { position, .. }
+            | ParseError::InvalidStatusLine { position, .. }
+            | ParseError::InvalidHeader { position, .. }
+            | ParseError::InvalidBody { position, .. } => Some(*position),
+            _ => None,
+        }
+    }
+
+    /// The byte offset of a syntax error, see `position`.
+    pub fn offset(&self) -> Option<usize> {
+        self.position().map(|position| position.offset)
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.position().map(|position| position.line)
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.position().map(|position| position.column)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Syntax { kind, position } => write!(
+                f,
+                "syntax error ({:?}) at line {}, column {}",
+                kind, position.line, position.column
+            ),
+            ParseError::Incomplete => write!(f, "incomplete input"),
+            ParseError::ObsoleteLineFolding => write!(f, "obsolete line folding in a header"),
+            ParseError::BareLineFeed => write!(f, "line ending without CR"),
+            ParseError::WhitespaceBeforeColon(name) => {
+                write!(f, "whitespace between header name {} and colon", name)
+            }
+            ParseError::NulByte => write!(f, "NUL byte in the request head"),
+            ParseError::PathTooDeep => write!(f, "too many path segments"),
+            ParseError::DisallowedScheme(scheme) => write!(f, "scheme {} is not allowed", scheme),
+            ParseError::HostMismatch => write!(f, "Host header does not match the request target"),
+            ParseError::DuplicateContentLength => write!(f, "conflicting Content-Length values"),
+            ParseError::InvalidTransferEncoding => {
+                write!(f, "Transfer-Encoding does not end with a single chunked")
+            }
+            ParseError::BodyTooLarge { limit, len } => write!(
+                f,
+                "body of {} bytes exceeds the limit of {} bytes",
+                len, limit
+            ),
+            ParseError::HeadTooLarge => write!(f, "request head is too large"),
+            ParseError::Body(e) => write!(f, "invalid body: {}", e),
+            ParseError::Io(kind) => write!(f, "reading the request failed: {}", kind),
+            ParseError::InvalidMethod { position, .. } => write!(
+                f,
+                "invalid method at line {}, column {}",
+                position.line, position.column
+            ),
+            ParseError::InvalidUri { position, .. } => write!(
+                f,
+                "invalid request target at line {}, column {}",
+                position.line, position.column
+            ),
+            ParseError::InvalidVersion { position, .. } => write!(
+                f,
+                "invalid HTTP version at line {}, column {}",
+                position.line, position.column
+            ),
+            ParseError::InvalidStatusLine { position, .. } => write!(
+                f,
+                "invalid status line at line {}, column {}",
+                position.line, position.column
+            ),
+            ParseError::InvalidHeader { position, .. } => write!(
+                f,
+                "invalid header at line {}, column {}",
+                position.line, position.column
+            ),
+            ParseError::InvalidBody { position, .. } => write!(
+                f,
+                "invalid body framing at line {}, column {}",
+                position.line, position.column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a complete request, applying the checks selected in `options`. The
+/// body is framed by Content-Length, see `parse_http`.
+pub fn parse_request(input: &str, options: &ParseOptions) -> Result<Request, ParseError> {
+    let (rest, mut request) = parse_request_head(input, options)?;
+    check_body_len(rest, &request.headers, options)?;
+    let (body, trailers) = message_body(rest, &request.headers, options)
+        .map(|(_, res)| res)
+        .map_err(|e| ParseError::from_body(input, &request.headers, e))?;
+    request.body = body;
+    request.trailers = trailers;
+    Ok(request)
+}
+
+/// Parses the request line and headers including the empty line after them,
+/// applying the checks selected in `options`, and returns the remaining input
+/// with the request. The body is left to the caller, for example to a
+/// `BodyReader`.
+pub fn parse_request_head<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<(&'a str, Request), ParseError> {
+    if options.mode == ParseMode::Strict {
+        reject_warnings(input)?;
+    }
+    let request_line = |input| request_line_in(input, options.mode);
+    let result = if options.skip_malformed_headers {
+        tuple((request_line, headers_skipping_malformed, line_ending))(input).map(
+            |(next_input, res)| {
+                (
+                    next_input,
+                    Request {
+                        request_line: res.0,
+                        headers: (res.1).0,
+                        body: Vec::new(),
+                        trailers: Headers::new(),
+                        malformed_headers: (res.1).1,
+                    },
+                )
+            },
+        )
+    } else {
+        request_head(input, options.mode)
+    };
+    let (rest, request) = result.map_err(|e| ParseError::from_head(input, e))?;
+    check_head(request, options).map(|request| (rest, request))
+}
+
+// what `ParseMode::Strict` rejects before the head is parsed
+fn reject_warnings(input: &str) -> Result<(), ParseError> {
+    match warnings(input).into_iter().next() {
+        Some(Warning::ObsoleteFolding) => Err(ParseError::ObsoleteLineFolding),
+        Some(Warning::BareLineFeed) => Err(ParseError::BareLineFeed),
+        Some(Warning::WhitespaceBeforeColon(name)) => Err(ParseError::WhitespaceBeforeColon(name)),
+        None => Ok(()),
+    }
+}
+
+// the checks of a parsed head, whether it was parsed from text or bytes
+fn check_head(request: Request, options: &ParseOptions) -> Result<Request, ParseError> {
+    if request.request_line.uri.path_segments().count() > options.max_path_segments {
+        return Err(ParseError::PathTooDeep);
+    }
+    if options.mode == ParseMode::Strict && request.validate_host() == Err(HostError::Mismatch) {
+        return Err(ParseError::HostMismatch);
+    }
+    content::validate_content_length(&request.headers, options)?;
+    chunked::validate_transfer_encoding(&request.headers)?;
+    if let (Some(allowed), Some(scheme)) =
+        (&options.allowed_schemes, &request.request_line.uri.scheme)
+    {
+        let scheme = scheme.to_string();
+        if !allowed.iter().any(|a| a.eq_ignore_ascii_case(&scheme)) {
+            return Err(ParseError::DisallowedScheme(scheme));
+        }
+    }
+    Ok(request)
+}
+
+/// Parses a request from raw bytes. The head (request line and headers) must not
+/// contain NUL bytes, and only header values may contain bytes that are not
+/// UTF-8, see `Headers::get_bytes`. The body is framed like in `parse_http`,
+/// by the chunked transfer coding or Content-Length. The trailer section of a
+/// chunked body is parsed into `trailers` like its head, so it can be checked
+/// with `Request::validate_trailers`. Without either, the request has
+/// no body, and anything after the head belongs to the next request.
+pub fn parse_request_bytes(input: &[u8], options: &ParseOptions) -> Result<Request, ParseError> {
+    let head_len = head_len(input).ok_or(ParseError::Incomplete)?;
+    let mut request = parse_head_bytes(&input[..head_len], options)?;
+    let rest = &input[head_len..];
+    if request.headers.is_chunked() {
+        let mut reader = BodyReader::new(&request.headers, options)?;
+        let progress = reader.push(rest).map_err(streaming::from_body_error)?;
+        if !progress.done {
+            return Err(ParseError::Incomplete);
+        }
+        if let Some(start) = reader.trailer_start() {
+            let end = head_len + progress.consumed;
+            let start = head_len + usize::try_from(start).unwrap_or(end);
+            request.trailers = header_bytes_section(&input[..end], start, false)?.0;
+        }
+        request.body = progress.data;
+        return Ok(request);
+    }
+    if let (Some(limit), Some(Ok(len))) = (options.max_body_len, request.headers.content_length()) {
+        if len > limit {
+            return Err(ParseError::BodyTooLarge { limit, len });
+        }
+    }
+    request.body = match request.headers.content_length() {
+        None => Vec::new(),
+        Some(Ok(len)) => rest
+            .get(..usize::try_from(len).unwrap_or(usize::MAX))
+            .ok_or(ParseError::Incomplete)?
+            .to_vec(),
+        Some(Err(e)) => return Err(e),
+    };
+    Ok(request)
+}
+
+// the head up to and including the empty line, see `head_len`
+pub(crate) fn parse_head_bytes(head: &[u8], options: &ParseOptions) -> Result<Request, ParseError> {
+    if head.contains(&0) {
+        return Err(ParseError::NulByte);
+    }
+    if let Ok(head) = std::str::from_utf8(head) {
+        return parse_request_head(head, options).map(|(_, request)| request);
+    }
+    if options.mode == ParseMode::Strict {
+        reject_warnings(&String::from_utf8_lossy(head))?;
+    }
+    let line_len = head
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(head.len(), |i| i + 1);
+    let line = request_line_utf8(&head[..line_len])?;
+    let (_, request_line) =
+        request_line_in(line, options.mode).map_err(|e| ParseError::from_head(line, e))?;
+    let (headers, malformed_headers) =
+        header_bytes_section(head, line_len, options.skip_malformed_headers)?;
+    check_head(
+        Request {
+            request_line,
+            headers,
+            body: Vec::new(),
+            trailers: Headers::new(),
+            malformed_headers,
+        },
+        options,
+    )
+}
+
+/// The request line as text, or the component with the first byte that is not
+/// UTF-8 failing at its position.
+pub(crate) fn request_line_utf8(line: &[u8]) -> Result<&str, ParseError> {
+    std::str::from_utf8(line).map_err(|e| {
+        // the part before the invalid byte is valid
+        let valid = std::str::from_utf8(&line[..e.valid_up_to()]).unwrap_or_default();
+        let kind = ErrorKind::Char;
+        let position = Position::new(valid, valid.len());
+        match separators(valid) {
+            (None, _) => ParseError::InvalidMethod { kind, position },
+            (Some(_), None) => ParseError::InvalidUri {
+                kind,
+                position,
+                error: UriError::InvalidChar {
+                    ch: char::REPLACEMENT_CHARACTER,
+                    offset: valid.len(),
+                },
+            },
+            _ => ParseError::InvalidVersion { kind, position },
+        }
+    })
+}
+
+// the header section of a head that is not UTF-8, starting at `start`, like
+// `headers` or `headers_skipping_malformed`, but with the values taken as the
+// bytes they are
+fn header_bytes_section(
+    head: &[u8],
+    start: usize,
+    skip_malformed: bool,
+) -> Result<(Headers, Vec<String>), ParseError> {
+    let mut fields = Headers::new();
+    let mut malformed = Vec::new();
+    let mut input = &head[start..];
+    while !input.is_empty() && line_ending_bytes(input).is_err() {
+        match header_bytes(input) {
+            Ok((next_input, (name, value))) => {
+                fields.push(name, value);
+                input = next_input;
+            }
+            Err(_) if skip_malformed => {
+                let (next_input, line) = rest_of_line_bytes(input)
+                    .map_err(|_| header_bytes_error(head, head.len() - input.len()))?;
+                malformed.push(String::from_utf8_lossy(line).into_owned());
+                input = next_input;
+            }
+            Err(_) => return Err(header_bytes_error(head, head.len() - input.len())),
+        }
+    }
+    Ok((fields, malformed))
+}
+
+// like `header`, but the name is the only part that has to be text
+fn header_bytes(input: &[u8]) -> IResult<&[u8], (HeaderName, HeaderValue), ()> {
+    pair(
+        tuple((
+            alphanumerichyphen1,
+            delimited(space0, tag(":"), space0),
+            rest_of_line_bytes,
+        )),
+        many0(preceded(
+            take_while1(|b| b == b' ' || b == b'\t'),
+            rest_of_line_bytes,
+        )),
+    )(input)
+    .map(|(next_input, ((name, _, line), folds))| {
+        // continuation lines (obs-fold) are joined to the value with a single space
+        let mut value = line.to_vec();
+        while value.ends_with(b" ") || value.ends_with(b"\t") {
+            value.pop();
+        }
+        for fold in folds {
+            value.push(b' ');
+            value.extend_from_slice(fold);
+        }
+        (
+            next_input,
+            (
+                HeaderName::from_token(String::from_utf8_lossy(name).as_ref()),
+                HeaderValue::from_bytes_unchecked(&value),
+            ),
+        )
+    })
+}
+
+// the line up to its line ending, which only a CR right before the LF may be part of
+fn rest_of_line_bytes(input: &[u8]) -> IResult<&[u8], &[u8], ()> {
+    terminated(take_while(|b| b != b'\n' && b != b'\r'), line_ending_bytes)(input)
+}
+
+fn line_ending_bytes(input: &[u8]) -> IResult<&[u8], &[u8], ()> {
+    alt((tag("\r\n"), tag("\n")))(input)
+}
+
+// the error of the header line at `start`, as `header` reports it for the line
+// with its bytes that are not UTF-8 read as U+FFFD
+fn header_bytes_error(head: &[u8], start: usize) -> ParseError {
+    let line = &head[start..];
+    let line = &line[..line
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(line.len(), |i| i + 1)];
+    let text = String::from_utf8_lossy(line);
+    let (kind, offset, error) =
+        match header::<HeadError>(&text).map_err(|e| ParseError::from_head(&text, e)) {
+            Err(ParseError::InvalidHeader {
+                kind,
+                position,
+                error,
+            }) => (kind, start + lossy_offset(line, position.offset), error),
+            // the grammars agree on the line, so it fails as text too
+            _ => (ErrorKind::Verify, start, HeaderError::MissingLineEnding),
+        };
+    let error = match error {
+        HeaderError::InvalidNameChar { ch, .. } => HeaderError::InvalidNameChar { ch, offset },
+        HeaderError::InvalidValueChar { ch, .. } => HeaderError::InvalidValueChar { ch, offset },
+        error => error,
+    };
+    let before = String::from_utf8_lossy(&head[..offset]);
+    ParseError::InvalidHeader {
+        kind,
+        position: Position {
+            offset,
+            ..Position::new(&before, before.len())
+        },
+        error,
+    }
+}
+
+// the offset into `bytes` of the byte `offset` into their text as
+// `String::from_utf8_lossy` reads it
+fn lossy_offset(bytes: &[u8], offset: usize) -> usize {
+    let mut text_len = 0;
+    let mut bytes_len = 0;
+    for chunk in bytes.utf8_chunks() {
+        let valid = chunk.valid().len();
+        if offset <= text_len + valid {
+            return bytes_len + offset - text_len;
+        }
+        text_len += valid;
+        bytes_len += valid;
+        if !chunk.invalid().is_empty() {
+            if offset < text_len + char::REPLACEMENT_CHARACTER.len_utf8() {
+                return bytes_len;
+            }
+            text_len += char::REPLACEMENT_CHARACTER.len_utf8();
+            bytes_len += chunk.invalid().len();
+        }
+    }
+    bytes_len
+}
+
+// length of the request line and headers including the terminating empty line
+pub(crate) fn head_len(input: &[u8]) -> Option<usize> {
+    input.iter().enumerate().find_map(|(i, b)| match b {
+        b'\n' if input[i + 1..].starts_with(b"\n") => Some(i + 2),
+        b'\n' if input[i + 1..].starts_with(b"\r\n") => Some(i + 3),
+        _ => None,
+    })
+}
+
+/// A recoverable oddity in a request that parsed successfully.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Warning {
+    /// A header value is continued on the next line: https://tools.ietf.org/html/rfc7230#section-3.2.4
+    ObsoleteFolding,
+    /// A line of the head ends in a bare LF instead of CRLF.
+    BareLineFeed,
+    /// Whitespace between the named header's name and the colon.
+    WhitespaceBeforeColon(String),
+}
+
+/// Like `parse_request`, but additionally reports the recoverable oddities that
+/// were tolerated while parsing the head.
+pub fn parse_request_with_warnings(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<(Request, Vec<Warning>), ParseError> {
+    let request = parse_request(input, options)?;
+    Ok((request, warnings(input)))
+}
+
+fn warnings(input: &str) -> Vec<Warning> {
+    let mut warnings = vec![];
+    let mut head = input.split_inclusive('\n');
+    if let Some(request_line) = head.next() {
+        if !request_line.ends_with("\r\n") {
+            warnings.push(Warning::BareLineFeed);
+        }
+    }
+    for line in head {
+        if !line.ends_with("\r\n") && !warnings.contains(&Warning::BareLineFeed) {
+            warnings.push(Warning::BareLineFeed);
+        }
+        if line.trim_end().is_empty() {
+            break;
+        }
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if !warnings.contains(&Warning::ObsoleteFolding) {
+                warnings.push(Warning::ObsoleteFolding);
+            }
+        } else if let Some((name, _)) = line.split_once(':') {
+            if name.ends_with(' ') || name.ends_with('\t') {
+                warnings.push(Warning::WhitespaceBeforeColon(name.trim_end().to_owned()));
+            }
+        }
+    }
+    warnings
+}
+
+/// HTTP-message: https://tools.ietf.org/html/rfc7230#section-3
+/// Parses a request and its body. The body is framed by the chunked
+/// transfer coding or the Content-Length header, requests without either have
+/// no body:
+/// https://tools.ietf.org/html/rfc7230#section-3.3.3
+/// Anything after the body, like a pipelined request, is left in the remaining
+/// input. A body shorter than announced yields `ParseError::Incomplete`, one
+/// whose Transfer-Encoding does not end in chunked, or names it more than
+/// once, `ParseError::InvalidTransferEncoding`.
+pub fn parse_http(input: &str) -> Result<(&str, Request), ParseError> {
+    let (rest, mut request) =
+        request_head(input, ParseMode::default()).map_err(|e| ParseError::from_head(input, e))?;
+    let (rest, (body, trailers)) =
+        message_body(rest, &request.headers, &ParseOptions::default())
+            .map_err(|e| ParseError::from_body(input, &request.headers, e))?;
+    request.body = body;
+    request.trailers = trailers;
+    Ok((rest, request))
+}
+
+// the empty line after the header section; when it is missing, the error of
+// the header line that failed to parse tells more about what went wrong
+pub(crate) fn end_of_headers<'a, E: GrammarError<'a>>(
+    input: &'a str,
+) -> IResult<&'a str, &'a str, E> {
+    context("empty line", line_ending)(input).map_err(|e| match header::<E>(input) {
+        Err(header_error @ NomErr::Error(_)) => header_error,
+        _ => e,
+    })
+}
+
+pub(crate) fn request_head<'a, E: GrammarError<'a>>(
+    input: &'a str,
+    mode: ParseMode,
+) -> IResult<&'a str, Request, E> {
+    tuple((
+        |input| request_line_in(input, mode),
+        headers,
+        end_of_headers,
+    ))(input)
+    .map(|(next_input, res)| {
+        (
+            next_input,
+            Request {
+                request_line: res.0,
+                headers: res.1,
+                body: Vec::new(),
+                trailers: Headers::new(),
+                malformed_headers: Vec::new(),
+            },
+        )
+    })
+}
+
+// `ParseOptions::max_body_len` for `message_body`, checked before anything is
+// copied: Content-Length is compared as announced, chunked bodies by their
+// chunk sizes
+pub(crate) fn check_body_len(
+    input: &str,
+    headers: &Headers,
+    options: &ParseOptions,
+) -> Result<(), ParseError> {
+    let limit = match options.max_body_len {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+    let len = if headers.is_chunked() {
+        chunked::exceeded_len(input, options, limit)
+    } else {
+        match headers.content_length() {
+            Some(Ok(len)) if len > limit => Some(len),
+            _ => None,
+        }
+    };
+    match len {
+        Some(len) => Err(ParseError::BodyTooLarge { limit, len }),
+        None => Ok(()),
+    }
+}
+
+// the body and trailer section, Transfer-Encoding takes precedence over
+// Content-Length
+pub(crate) fn message_body<'a>(
+    input: &'a str,
+    headers: &Headers,
+    options: &ParseOptions,
+) -> IResult<&'a str, (Vec<u8>, Headers)> {
+    // a body whose framing is not certain is not read at all, as the message
+    // after it could be smuggled into it: https://tools.ietf.org/html/rfc7230#section-3.3.3
+    if chunked::validate_transfer_encoding(headers).is_err() {
+        return Err(NomErr::Failure(Error::new(input, ErrorKind::Verify)));
+    }
+    if headers.is_chunked() {
+        return chunked::chunked_body_with(input, options)
+            .map(|(next_input, body)| (next_input, (body.data, body.trailers)));
+    }
+    let len = match headers.content_length() {
+        None => return Ok((input, (Vec::new(), Headers::new()))),
+        Some(Ok(len)) => usize::try_from(len).unwrap_or(usize::MAX),
+        Some(Err(_)) => return Err(NomErr::Error(Error::new(input, ErrorKind::Digit))),
+    };
+    if input.len() < len {
+        return Err(NomErr::Incomplete(Needed::new(len - input.len())));
+    }
+    if !input.is_char_boundary(len) {
+        return Err(NomErr::Error(Error::new(input, ErrorKind::Char)));
+    }
+    Ok((
+        &input[len..],
+        (input.as_bytes()[..len].to_vec(), Headers::new()),
+    ))
+}
+
+#[test]
+fn test_request_method() {
+    assert_eq!(
+        request_method::<Error<&str>>("GET 1234"),
+        Ok((" 1234", Method::GET))
+    );
+    assert_eq!(
+        request_method("1234"),
+        Err(NomErr::Error(Error::new("1234", ErrorKind::Tag)))
+    );
+    assert_eq!(
+        request_method::<Error<&str>>("PUT POST"),
+        Ok((" POST", Method::PUT))
+    );
+    assert_eq!(
+        request_method::<Error<&str>>("head /"),
+        Ok((" /", Method::HEAD))
+    );
+    assert_eq!(
+        request_method::<Error<&str>>("PURGE /"),
+        Ok((" /", Method::EXTENSION("PURGE".to_string())))
+    );
+}
+
+#[test]
+fn test_method_round_trip() {
+    let methods = vec![
+        Method::GET,
+        Method::HEAD,
+        Method::POST,
+        Method::PUT,
+        Method::DELETE,
+        Method::CONNECT,
+        Method::OPTIONS,
+        Method::TRACE,
+        Method::PATCH,
+        Method::EXTENSION("PURGE".to_string()),
+        Method::EXTENSION("M-SEARCH".to_string()),
+    ];
+    for method in methods {
+        assert_eq!(Method::from(method.as_str()), method);
+        assert_eq!(
+            request_method::<Error<&str>>(&format!("{} X", method)),
+            Ok((" X", method.clone()))
+        );
+    }
+}
+
+#[test]
+fn test_method_str() {
+    let method = |i| parse_http(i).unwrap().1.method_str().to_string();
+    assert_eq!(method("GET / HTTP/1.1\r\n\r\n"), "GET");
+    assert_eq!(method("get / HTTP/1.1\r\n\r\n"), "GET");
+    assert_eq!(method("M-SEARCH * HTTP/1.1\r\n\r\n"), "M-SEARCH");
+}
+
+#[test]
+fn test_version_try_from() {
+    assert_eq!(Version::try_from("HTTP/1.1"), Ok(Version::HTTP11));
+    assert_eq!(Version::try_from("HTTP/1.0"), Ok(Version::HTTP10));
+    assert!(matches!(
+        Version::try_from("HTTP/2"),
+        Err(ParseError::InvalidVersion { .. })
+    ));
+}
+
+#[test]
+fn test_method_classification() {
+    const SAFE: [bool; 3] = [
+        Method::GET.is_safe(),
+        Method::HEAD.is_safe(),
+        Method::POST.is_safe(),
+    ];
+    const HTTPS_PORT: Option<u16> = Scheme::HTTPS.default_port();
+    assert_eq!(SAFE, [true, true, false]);
+    assert_eq!(HTTPS_PORT, Some(443));
+    assert!(!Method::POST.is_idempotent());
+    assert!(Method::DELETE.is_idempotent());
+    assert!(!Method::EXTENSION("PROPFIND".to_string()).is_idempotent());
+}
+
+#[test]
+fn test_authority() {
+    assert_eq!(
+        authority::<Error<&str>>("username:password@zupzup.org"),
+        Ok(("zupzup.org", Some(("username", Some("password")))))
+    );
+    assert_eq!(
+        authority::<Error<&str>>("username@zupzup.org"),
+        Ok(("zupzup.org", Some(("username", None))))
+    );
+    assert_eq!(
+        authority::<Error<&str>>("zupzup.org"),
+        Ok(("zupzup.org", None))
+    );
+    assert_eq!(
+        authority::<Error<&str>>(":zupzup.org"),
+        Ok((":zupzup.org", None))
+    );
+    assert_eq!(
+        authority::<Error<&str>>("username:passwordzupzup.org"),
+        Ok(("username:passwordzupzup.org", None))
+    );
+    assert_eq!(
+        authority::<Error<&str>>("@zupzup.org"),
+        Ok(("@zupzup.org", None))
+    );
+}
+
+#[test]
+fn test_host() {
+    assert_eq!(
+        host::<Error<&str>>("localhost:8080"),
+        Ok((":8080", Host::HOST("localhost".to_string())))
+    );
+    assert_eq!(
+        host::<Error<&str>>("example.org:8080"),
+        Ok((":8080", Host::HOST("example.org".to_string())))
+    );
+    assert_eq!(
+        host::<Error<&str>>("some-subsite.example.org:8080"),
+        Ok((":8080", Host::HOST("some-subsite.example.org".to_string())))
+    );
+    assert_eq!(
+        host::<Error<&str>>("example.org.:8080"),
+        Ok((":8080", Host::HOST("example.org.".to_string())))
+    );
+    assert_eq!(
+        host::<Error<&str>>("example.123"),
+        Ok((".123", Host::HOST("example".to_string())))
+    );
+    assert_eq!(
+        host::<Error<&str>>("myhost:1234"),
+        Ok((":1234", Host::HOST("myhost".to_string())))
+    );
+    assert_eq!(
+        host::<Error<&str>>("my-host2/path"),
+        Ok(("/path", Host::HOST("my-host2".to_string())))
+    );
+    assert_eq!(
+        host("$$$.com"),
+        Err(NomErr::Error(Error::new(
+            "$$$.com",
+            ErrorKind::AlphaNumeric
+        )))
+    );
+    assert_eq!(
+        host(".com"),
+        Err(NomErr::Error(Error::new(".com", ErrorKind::AlphaNumeric)))
+    );
+}
+
+#[test]
+fn test_ipv4() {
+    assert_eq!(
+        ip::<Error<&str>>("192.168.0.1:8080"),
+        Ok((":8080", Host::IP([192, 168, 0, 1])))
+    );
+    assert_eq!(
+        ip::<Error<&str>>("0.0.0.0:8080"),
+        Ok((":8080", Host::IP([0, 0, 0, 0])))
+    );
+    assert_eq!(
+        ip("1924.168.0.1:8080"),
+        Err(NomErr::Error(Error::new("4.168.0.1:8080", ErrorKind::Tag)))
+    );
+    assert_eq!(
+        ip("192.168.0000.144:8080"),
+        Err(NomErr::Error(Error::new("0.144:8080", ErrorKind::Tag)))
+    );
+    assert_eq!(
+        ip::<Error<&str>>("192.168.0.1444:8080"),
+        Ok(("4:8080", Host::IP([192, 168, 0, 144])))
+    );
+    assert_eq!(
+        ip("192.168.0:8080"),
+        Err(NomErr::Error(Error::new(":8080", ErrorKind::Tag)))
+    );
+    assert_eq!(
+        ip("999.168.0.0:8080"),
+        Err(NomErr::Error(Error::new(".168.0.0:8080", ErrorKind::Digit)))
+    );
+}
+
+#[test]
+fn test_ipv6() {
+    assert_eq!(
+        ipv6::<Error<&str>>("[::ffff:192.168.0.1]:8080"),
+        Ok((":8080", Host::IPV6([0, 0, 0, 0, 0, 0xffff, 0xc0a8, 0x0001])))
+    );
+    assert_eq!(
+        ipv6::<Error<&str>>("[::1]"),
+        Ok(("", Host::IPV6([0, 0, 0, 0, 0, 0, 0, 1])))
+    );
+    assert_eq!(
+        ipv6::<Error<&str>>("[2001:DB8:0:0:8:800:200C:417A]"),
+        Ok((
+            "",
+            Host::IPV6([0x2001, 0xdb8, 0, 0, 8, 0x800, 0x200c, 0x417a])
+        ))
+    );
+    assert_eq!(
+        ipv6::<Error<&str>>("[fe80::]"),
+        Ok(("", Host::IPV6([0xfe80, 0, 0, 0, 0, 0, 0, 0])))
+    );
+    assert!(ipv6::<Error<&str>>("[1::2::3]").is_err());
+    assert!(ipv6::<Error<&str>>("[1:2:3:4:5:6:7]").is_err());
+    assert!(ipv6::<Error<&str>>("[::192.168.0.1:1]").is_err());
+    assert!(ipv6::<Error<&str>>("[::12345]").is_err());
+
+    let uri = uri::<Error<&str>>("http://[::ffff:192.168.0.1]:8080/")
+        .unwrap()
+        .1;
+    assert_eq!(uri.port, Some(8080));
+    assert_eq!(uri.to_string(), "http://[::ffff:192.168.0.1]:8080/");
+}
+
+#[cfg(test)]
+fn cause<O>(res: IResult<&str, O, HeadError>) -> Option<Cause> {
+    match res {
+        Err(NomErr::Error(e)) | Err(NomErr::Failure(e)) => e.cause,
+        _ => None,
+    }
+}
+
+#[test]
+fn test_parser_causes() {
+    assert_eq!(
+        cause(port(":70000/")),
+        Some(Cause::Uri(UriError::PortOutOfRange(70000)))
+    );
+    assert_eq!(
+        cause(port(":8o/")),
+        Some(Cause::Uri(UriError::InvalidPort("8o".to_string())))
+    );
+    assert_eq!(
+        cause(ip_num("256")),
+        Some(Cause::Uri(UriError::InvalidIpv4Octet(256)))
+    );
+    assert_eq!(
+        cause(host_or_ip("10.0.1924.1/")),
+        Some(Cause::Uri(UriError::InvalidIpv4Octet(1924)))
+    );
+    assert_eq!(cause(host_or_ip("1.2.3.4.example.org")), None);
+    assert_eq!(
+        cause(ipv6("[::g]")),
+        Some(Cause::Uri(UriError::InvalidIpv6("[::".to_string())))
+    );
+    assert_eq!(
+        cause(scheme("mailto:jo@example.org")),
+        Some(Cause::Uri(UriError::UnknownScheme("mailto".to_string())))
+    );
+    assert_eq!(cause(scheme("example.org:443")), None);
+    assert_eq!(
+        cause(header(": value\r\n")),
+        Some(Cause::Header(HeaderError::EmptyName))
+    );
+    assert_eq!(
+        cause(header("Name value\r\n")),
+        Some(Cause::Header(HeaderError::MissingColon))
+    );
+    assert_eq!(cause(header("Na@me: value\r\n")), Some(Cause::NameChar));
+    assert_eq!(cause(header("Name: a\rb\r\n")), Some(Cause::ValueChar));
+    assert_eq!(
+        cause(header("Name: value")),
+        Some(Cause::Header(HeaderError::MissingLineEnding))
+    );
+}
+
+#[test]
+fn test_header() {
+    assert_eq!(
+        header::<Error<&str>>("Content-Type: application/json\nabc"),
+        Ok((
+            "abc",
+            ("Content-Type".to_string(), "application/json".to_string())
+        ))
+    );
+    assert_eq!(
+        header::<Error<&str>>("Content-Type  :          application/json\nabc"),
+        Ok((
+            "abc",
+            ("Content-Type".to_string(), "application/json".to_string())
+        ))
+    );
+    assert_eq!(
+        header::<Error<&str>>("Some1:123$$$%*%*\nabc"),
+        Ok(("abc", ("Some1".to_string(), "123$$$%*%*".to_string())))
+    );
+}
+
+#[test]
+fn test_empty_header_value() {
+    assert_eq!(
+        header::<Error<&str>>("X-Empty:\nrest"),
+        Ok(("rest", ("X-Empty".to_string(), "".to_string())))
+    );
+    assert_eq!(
+        header::<Error<&str>>("X-Empty:    \t \r\nrest"),
+        Ok(("rest", ("X-Empty".to_string(), "".to_string())))
+    );
+    assert_eq!(
+        header::<Error<&str>>("X-Padded:  value  \nrest"),
+        Ok(("rest", ("X-Padded".to_string(), "value".to_string())))
+    );
+}
+
+#[test]
+fn test_parse_headers_map() {
+    let (rest, map) =
+        parse_headers_map("Set-Cookie: a=1\r\nHost: x\r\nset-cookie: b=2\r\n\r\n").unwrap();
+    assert_eq!(rest, "\r\n");
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["set-cookie"], vec!["a=1", "b=2"]);
+    assert_eq!(map["host"], vec!["x"]);
+}
+
+#[test]
+fn test_parse_header_line() {
+    let input = "Content-Type:  text/html\nabc";
+    let (rest, (name, value)) = parse_header_line::<Error<&str>>(input).unwrap();
+    assert_eq!((rest, name, value), ("abc", "Content-Type", "text/html"));
+    assert_eq!(name.as_ptr(), input.as_ptr());
+    assert_eq!(value.as_ptr(), input[15..].as_ptr());
+}
+
+#[test]
+fn test_headers() {
+    assert_eq!(
+        headers::<Error<&str>>(
+            "Content-Type: application/json\nAuthorization: pw\nHost: zupzup.org\nabc"
+        ),
+        Ok((
+            "abc",
+            Headers::from(vec![
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("Authorization".to_string(), "pw".to_string()),
+                ("Host".to_string(), "zupzup.org".to_string())
+            ])
+        ))
+    );
+}
+
+#[test]
+fn test_header_slices() {
+    let input =
+        "Host: zupzup.org\r\nAccept: */*\r\nX-Long: a\r\n b\r\nUser-Agent: curl/7.64.1\r\n\r\n";
+    let (rest, slices) = header_slices(input).unwrap();
+    let (owned_rest, owned) = headers::<Error<&str>>(input).unwrap();
+    assert_eq!(rest, owned_rest);
+    assert_eq!(
+        Headers::from(
+            slices
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<Vec<Header>>()
+        ),
+        owned
+    );
+    assert!(matches!(slices[0].1, Cow::Borrowed("zupzup.org")));
+    assert!(matches!(&slices[2].1, Cow::Owned(value) if value == "a b"));
+}
+
+#[test]
+fn test_path() {
+    assert_eq!(
+        path::<Error<&str>>("/a/b/c?d"),
+        Ok(("?d", "/a/b/c".to_string()))
+    );
+    assert_eq!(
+        path::<Error<&str>>("/a/b/c/?d"),
+        Ok(("?d", "/a/b/c/".to_string()))
+    );
+    assert_eq!(
+        path::<Error<&str>>("/a/b-c-d/c/?d"),
+        Ok(("?d", "/a/b-c-d/c/".to_string()))
+    );
+    assert_eq!(
+        path::<Error<&str>>("/a/1234/c/?d"),
+        Ok(("?d", "/a/1234/c/".to_string()))
+    );
+    assert_eq!(
+        path::<Error<&str>>("/a/1234/c.txt?d"),
+        Ok(("?d", "/a/1234/c.txt".to_string()))
+    );
+    assert_eq!(
+        path::<Error<&str>>("/a%2Fb/~c@d:e;f=g?h"),
+        Ok(("?h", "/a%2Fb/~c@d:e;f=g".to_string()))
+    );
+    assert_eq!(path::<Error<&str>>("//a?b"), Ok(("?b", "//a".to_string())));
+
+    let uri_path = |i| uri::<Error<&str>>(i).unwrap().1.path;
+    assert_eq!(uri_path("http://host"), None);
+    assert_eq!(uri_path("http://host/"), Some("/".to_string()));
+    assert_eq!(uri_path("http://host//a"), Some("//a".to_string()));
+}
+
+#[test]
+fn test_query_params() {
+    assert_eq!(
+        query_params::<Error<&str>>("?bla=5&blub=val#yay"),
+        Ok((
+            "#yay",
+            vec![
+                ("bla".to_string(), "5".to_string()),
+                ("blub".to_string(), "val".to_string())
+            ]
+        ))
+    );
+
+    assert_eq!(
+        query_params::<Error<&str>>("?bla-blub=arr-arr#yay"),
+        Ok((
+            "#yay",
+            vec![("bla-blub".to_string(), "arr-arr".to_string()),]
+        ))
+    );
+}
+
+#[test]
+fn test_empty_query() {
+    assert_eq!(query_params::<Error<&str>>("?#yay"), Ok(("#yay", vec![])));
+
+    let (_, without) = uri::<Error<&str>>("http://x.com/p").unwrap();
+    assert_eq!(without.query, None);
+    assert_eq!(without.to_string(), "http://x.com/p");
+
+    let (_, empty) = uri::<Error<&str>>("http://x.com/p?").unwrap();
+    assert_eq!(empty.query, Some(vec![]));
+    assert_eq!(empty.to_string(), "http://x.com/p?");
+}
+
+#[test]
 fn test_fragmetn() {
-    assert_eq!(fragment("#bla"), Ok(("", "bla")));
-    assert_eq!(fragment("#bla-blub"), Ok(("", "bla-blub")));
+    assert_eq!(fragment::<Error<&str>>("#bla"), Ok(("", "bla")));
+    assert_eq!(fragment::<Error<&str>>("#bla-blub"), Ok(("", "bla-blub")));
+}
+
+#[test]
+fn test_target_form() {
+    let form =
+        |i| request_line_in::<Error<&str>>(i, ParseMode::Strict).map(|(_, res)| res.target_form());
+    assert_eq!(form("GET /path HTTP/1.1\r\n"), Ok(TargetForm::Origin));
+    assert_eq!(
+        form("GET http://x/p HTTP/1.1\r\n"),
+        Ok(TargetForm::Absolute)
+    );
+    assert_eq!(
+        form("CONNECT x:443 HTTP/1.1\r\n"),
+        Ok(TargetForm::Authority)
+    );
+    assert_eq!(form("OPTIONS * HTTP/1.1\r\n"), Ok(TargetForm::Asterisk));
+}
+
+#[test]
+fn test_request_line() {
+    assert_eq!(
+        request_line_in::<Error<&str>>(
+            "GET http://192.168.0.1:8080/a/b?c=d HTTP/1.0\nHost: x",
+            ParseMode::Strict
+        ),
+        Ok((
+            "Host: x",
+            RequestLine {
+                method: Method::GET,
+                uri: URI {
+                    scheme: Some(Scheme::HTTP),
+                    authority: None,
+                    host: Some(Host::IP([192, 168, 0, 1])),
+                    port: Some(8080),
+                    path: Some("/a/b".to_string()),
+                    query: Some(vec![("c".to_string(), "d".to_string())]),
+                    raw_query: Some("c=d".to_string()),
+                    fragment: None,
+                },
+                version: Version::HTTP10,
+            }
+        ))
+    );
+    assert_eq!(
+        request_line_in("GET / HTTP/2.0\r\n", ParseMode::Strict),
+        Err(NomErr::Error(Error::new("HTTP/2.0\r\n", ErrorKind::Tag)))
+    );
+}
+
+#[test]
+fn test_obsolete_line_folding() {
+    let input = "GET / HTTP/1.1\r\nX-Long: a\r\n  b\r\n\tc\r\nHost: x\r\n\r\n";
+
+    let request = parse_request(input, &ParseOptions::default()).unwrap();
+    assert_eq!(request.headers.get("X-Long"), Some("a b c"));
+    assert_eq!(request.headers.get("Host"), Some("x"));
+
+    assert_eq!(
+        parse_request(input, &ParseOptions::strict()),
+        Err(ParseError::ObsoleteLineFolding)
+    );
+}
+
+#[test]
+fn test_parse_mode() {
+    let lenient = ParseOptions::default();
+    let strict = ParseOptions::strict();
+    assert_eq!(lenient.mode, ParseMode::Lenient);
+
+    let input = "GET / HTTP/1.1\nHost: x\n\n";
+    assert_eq!(
+        parse_request(input, &lenient).map(|r| r.headers.get("Host").map(str::to_owned)),
+        Ok(Some("x".to_string()))
+    );
+    assert_eq!(parse_request(input, &strict), Err(ParseError::BareLineFeed));
+
+    let input = "GET / HTTP/1.1\r\nHost : x\r\n\r\n";
+    assert_eq!(
+        parse_request(input, &lenient).map(|r| r.headers.get("Host").map(str::to_owned)),
+        Ok(Some("x".to_string()))
+    );
+    assert_eq!(
+        parse_request(input, &strict),
+        Err(ParseError::WhitespaceBeforeColon("Host".to_string()))
+    );
+
+    let input = "GET  /a  HTTP/1.1\r\nHost: x\r\n\r\n";
+    let request = parse_request(input, &lenient).unwrap();
+    assert_eq!(
+        request.request_line.uri.path_segments().collect::<Vec<_>>(),
+        vec!["a"]
+    );
+    assert_eq!(request.request_line.version, Version::HTTP11);
+    assert!(matches!(
+        parse_request(input, &strict),
+        Err(ParseError::InvalidUri { position, .. }) if position.offset == 4
+    ));
+
+    let input = "get / HTTP/1.1\r\nHost: x\r\n\r\n";
+    assert_eq!(
+        parse_request(input, &lenient).map(|r| r.request_line.method),
+        Ok(Method::GET)
+    );
+    assert_eq!(
+        parse_request(input, &strict).map(|r| r.request_line.method),
+        Ok(Method::EXTENSION("get".to_string()))
+    );
+
+    let input = "GET / HTTP/1.1\r\nX-Long: a\r\n b\r\nHost: x\r\n\r\n";
+    assert_eq!(
+        parse_request(input, &lenient).map(|r| r.headers.get("X-Long").map(str::to_owned)),
+        Ok(Some("a b".to_string()))
+    );
+    assert_eq!(
+        parse_request(input, &strict),
+        Err(ParseError::ObsoleteLineFolding)
+    );
+
+    // the mode carries over to input that arrives in parts
+    let input = b"GET  / HTTP/1.1\r\n";
+    assert!(parse_head_partial(input, &lenient).is_ok());
+    assert!(parse_head_partial(input, &strict).is_err());
+}
+
+#[test]
+fn test_parse_request_with_warnings() {
+    let options = ParseOptions::default();
+    let input = "GET / HTTP/1.1\r\nX-Long: a\r\n  b\r\nHost: x\r\n\r\n";
+    let (request, warnings) = parse_request_with_warnings(input, &options).unwrap();
+    assert_eq!(request.headers.get("X-Long"), Some("a b"));
+    assert_eq!(warnings, vec![Warning::ObsoleteFolding]);
+
+    let input = "GET / HTTP/1.1\nContent-Type : text/plain\n\n";
+    let (_, warnings) = parse_request_with_warnings(input, &options).unwrap();
+    assert_eq!(
+        warnings,
+        vec![
+            Warning::BareLineFeed,
+            Warning::WhitespaceBeforeColon("Content-Type".to_string())
+        ]
+    );
+
+    let input = "GET / HTTP/1.1\r\nHost: x\r\n\r\n";
+    assert_eq!(
+        parse_request_with_warnings(input, &options).unwrap().1,
+        vec![]
+    );
+}
+
+#[test]
+fn test_parse_error_categories() {
+    let error = |input| parse_http(input).map(|_| ()).unwrap_err();
+
+    assert_eq!(
+        error("G{T / HTTP/1.1\r\n\r\n"),
+        ParseError::InvalidMethod {
+            kind: ErrorKind::Tag,
+            position: Position {
+                offset: 1,
+                line: 1,
+                column: 2
+            }
+        }
+    );
+    assert!(matches!(
+        error("GET /a|b HTTP/1.1\r\n\r\n"),
+        ParseError::InvalidUri { .. }
+    ));
+    assert_eq!(
+        error("GET / HTTP/2.0\r\n\r\n"),
+        ParseError::InvalidVersion {
+            kind: ErrorKind::Tag,
+            position: Position {
+                offset: 6,
+                line: 1,
+                column: 7
+            }
+        }
+    );
+    let bad_header = error("GET / HTTP/1.1\r\nHost: a\r\nBad Header\r\n\r\n");
+    assert!(matches!(bad_header, ParseError::InvalidHeader { .. }));
+    assert_eq!(bad_header.offset(), Some(29));
+    let bad_chunk = error("POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n");
+    assert!(matches!(bad_chunk, ParseError::InvalidBody { .. }));
+    assert_eq!(bad_chunk.offset(), Some(47));
+    assert_eq!(
+        error("POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nab"),
+        ParseError::Incomplete
+    );
+    assert!(matches!(
+        URI::parse("http://[::1"),
+        Err(ParseError::InvalidUri { .. })
+    ));
+    assert_eq!(
+        URI::parse("/a?b=c").map(|uri| uri.path),
+        Ok(Some("/a".to_string()))
+    );
+    assert_eq!(bad_header.to_string(), "invalid header at line 3, column 5");
+}
+
+#[test]
+fn test_component_errors() {
+    let uri_error = |target: &str| match parse_http(&format!("GET {} HTTP/1.1\r\n\r\n", target)) {
+        Err(ParseError::InvalidUri { error, .. }) => error,
+        other => panic!("{:?}", other),
+    };
+    let header_error = |head: &str| match parse_http(&format!("GET / HTTP/1.1\r\n{}", head)) {
+        Err(ParseError::InvalidHeader { error, .. }) => error,
+        other => panic!("{:?}", other),
+    };
+
+    assert_eq!(
+        uri_error("http://example.org:70000/"),
+        UriError::PortOutOfRange(70000)
+    );
+    assert_eq!(
+        uri_error("http://example.org:99999999999/"),
+        UriError::PortOutOfRange(u32::MAX)
+    );
+    assert_eq!(
+        uri_error("http://example.org:8o/"),
+        UriError::InvalidPort("8o".to_string())
+    );
+    assert_eq!(
+        uri_error("http://10.0.0.256/"),
+        UriError::InvalidIpv4Octet(256)
+    );
+    assert_eq!(
+        uri_error("http://[::1::2]/"),
+        UriError::InvalidIpv6("[::1::2]".to_string())
+    );
+    assert_eq!(
+        uri_error("mailto:jo@example.org"),
+        UriError::UnknownScheme("mailto".to_string())
+    );
+    assert_eq!(
+        uri_error("/a/b|c?d=e"),
+        UriError::InvalidChar { ch: '|', offset: 8 }
+    );
+    assert_eq!(
+        uri_error("http://exa_mple.org/"),
+        UriError::InvalidChar {
+            ch: '_',
+            offset: 14
+        }
+    );
+    assert_eq!(
+        uri_error("example.org"),
+        UriError::InvalidPort(String::new())
+    );
+    assert_eq!(
+        URI::parse("http://example.org/#a.b"),
+        Err(ParseError::InvalidUri {
+            kind: ErrorKind::Eof,
+            position: Position::new("http://example.org/#a.b", 21),
+            error: UriError::InvalidChar {
+                ch: '.',
+                offset: 21
+            }
+        })
+    );
+
+    assert_eq!(
+        header_error("X-Custom@: 1\r\n\r\n"),
+        HeaderError::InvalidNameChar {
+            ch: '@',
+            offset: 24
+        }
+    );
+    assert_eq!(
+        header_error("Bad Header: 1\r\n\r\n"),
+        HeaderError::InvalidNameChar {
+            ch: ' ',
+            offset: 19
+        }
+    );
+    assert_eq!(header_error(": 1\r\n\r\n"), HeaderError::EmptyName);
+    assert_eq!(
+        header_error("Bad Header\r\n\r\n"),
+        HeaderError::MissingColon
+    );
+    assert_eq!(
+        header_error("Host: a\rb\r\n\r\n"),
+        HeaderError::InvalidValueChar {
+            ch: '\r',
+            offset: 23
+        }
+    );
+    assert_eq!(
+        parse_request("GET / HTTP/1.1\r\nHost: a", &ParseOptions::default()),
+        Err(ParseError::InvalidHeader {
+            kind: ErrorKind::Tag,
+            position: Position::new("GET / HTTP/1.1\r\nHost: a", 23),
+            error: HeaderError::MissingLineEnding
+        })
+    );
+    assert_eq!(
+        parse_http("GET http://a:8/ HTTP/1.1\r\n\r\n")
+            .map(|(_, request)| request.request_line.uri.port),
+        Ok(Some(8))
+    );
+}
+
+#[test]
+fn test_error_position() {
+    let input = "GET / HTTP/1.1\r\nHost: example.org\r\nAccept: */*\r\nX-Custom@: 1\r\n\r\n";
+    let error = parse_request(input, &ParseOptions::default()).unwrap_err();
+    assert!(matches!(error, ParseError::InvalidHeader { .. }));
+    assert_eq!(error.line(), Some(4));
+    assert_eq!(error.column(), Some(9));
+    assert_eq!(&input[error.offset().unwrap()..][..1], "@");
+    assert_eq!(ParseError::Incomplete.line(), None);
+}
+
+#[test]
+fn test_skip_malformed_headers() {
+    let input = "GET / HTTP/1.1\r\nHost: x\r\n@@ garbage @@\r\nAccept: */*\r\n\r\n";
+
+    assert_eq!(
+        parse_request(input, &ParseOptions::default()),
+        Err(ParseError::InvalidHeader {
+            kind: ErrorKind::AlphaNumeric,
+            position: Position::new(input, 25),
+            error: HeaderError::InvalidNameChar {
+                ch: '@',
+                offset: 25
+            }
+        })
+    );
+
+    let options = ParseOptions {
+        skip_malformed_headers: true,
+        ..ParseOptions::default()
+    };
+    let request = parse_request(input, &options).unwrap();
+    assert_eq!(request.headers.len(), 2);
+    assert_eq!(request.headers.get("Accept"), Some("*/*"));
+    assert_eq!(request.malformed_headers, vec!["@@ garbage @@".to_string()]);
+}
+
+#[test]
+fn test_max_path_segments() {
+    let options = ParseOptions {
+        max_path_segments: 3,
+        ..ParseOptions::default()
+    };
+    assert!(parse_request("GET /a/b/c/ HTTP/1.1\r\n\r\n", &options).is_ok());
+    assert_eq!(
+        parse_request("GET /a/b/c/d HTTP/1.1\r\n\r\n", &options),
+        Err(ParseError::PathTooDeep)
+    );
+
+    let deep = format!("GET {} HTTP/1.1\r\n\r\n", "/a".repeat(300));
+    assert_eq!(
+        parse_request(&deep, &ParseOptions::default()),
+        Err(ParseError::PathTooDeep)
+    );
+}
+
+#[test]
+fn test_host_mismatch() {
+    let matching = "GET http://example.org/p HTTP/1.1\r\nHost: Example.org:80\r\n\r\n";
+    assert!(parse_request(matching, &ParseOptions::strict()).is_ok());
+
+    let mismatching = "GET http://example.org/p HTTP/1.1\r\nHost: other.org\r\n\r\n";
+    assert_eq!(
+        parse_request(mismatching, &ParseOptions::strict()),
+        Err(ParseError::HostMismatch)
+    );
+    assert!(parse_request(mismatching, &ParseOptions::default()).is_ok());
+}
+
+#[test]
+fn test_allowed_schemes() {
+    let options = ParseOptions {
+        allowed_schemes: Some(vec!["https".to_string()]),
+        ..ParseOptions::default()
+    };
+    assert!(parse_request("GET https://example.org/ HTTP/1.1\r\n\r\n", &options).is_ok());
+    assert!(parse_request("GET / HTTP/1.1\r\n\r\n", &options).is_ok());
+    assert_eq!(
+        parse_request("GET http://example.org/ HTTP/1.1\r\n\r\n", &options),
+        Err(ParseError::DisallowedScheme("http".to_string()))
+    );
+}
+
+#[test]
+fn test_nul_byte() {
+    let options = ParseOptions::default();
+    assert_eq!(
+        parse_request_bytes(b"GET / HTTP/1.1\r\nX-Evil: a\0b\r\n\r\n", &options),
+        Err(ParseError::NulByte)
+    );
+    assert_eq!(
+        parse_request_bytes(b"GET /\0 HTTP/1.1\r\n\r\n", &options),
+        Err(ParseError::NulByte)
+    );
+
+    let input = "GET / HTTP/1.1\r\nX-Evil: a\0b\r\n\r\n";
+    assert_eq!(
+        parse_http(input),
+        Err(ParseError::InvalidHeader {
+            kind: ErrorKind::Tag,
+            position: Position::new(input, 25),
+            error: HeaderError::InvalidValueChar {
+                ch: '\0',
+                offset: 25
+            }
+        })
+    );
+    assert!(parse_http("GET / HTTP/1.1\r\nX-Evil: a\r\n \0\r\n\r\n").is_err());
+
+    let request = parse_request_bytes(
+        b"POST / HTTP/1.1\r\nHost: x\r\nContent-Length: 3\r\n\r\n\0\x01\0",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(request.headers.get("Host"), Some("x"));
+    assert_eq!(request.body, b"\0\x01\0".to_vec());
+
+    assert_eq!(
+        parse_request_bytes(b"GET / HTTP/1.1\r\nHost: x\r\n", &options),
+        Err(ParseError::Incomplete)
+    );
+}
+
+#[test]
+fn test_non_utf8_head() {
+    let options = ParseOptions::default();
+    let request = parse_request_bytes(
+        b"GET / HTTP/1.1\r\nX-Name: caf\xe9\r\n  cr\xe8me\r\nHost: x\r\n\r\n",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(
+        request.headers.get_bytes("X-Name"),
+        Some(&b"caf\xe9 cr\xe8me"[..])
+    );
+    assert_eq!(
+        request.headers.get("X-Name"),
+        Some("caf\u{FFFD} cr\u{FFFD}me")
+    );
+    assert_eq!(request.headers.get_bytes("Host"), Some(&b"x"[..]));
+
+    assert_eq!(
+        parse_request_bytes(b"G\xffT / HTTP/1.1\r\n\r\n", &options),
+        Err(ParseError::InvalidMethod {
+            kind: ErrorKind::Char,
+            position: Position::new("G", 1)
+        })
+    );
+    assert_eq!(
+        parse_request_bytes(b"GET /caf\xe9 HTTP/1.1\r\n\r\n", &options),
+        Err(ParseError::InvalidUri {
+            kind: ErrorKind::Char,
+            position: Position::new("GET /caf", 8),
+            error: UriError::InvalidChar {
+                ch: char::REPLACEMENT_CHARACTER,
+                offset: 8
+            }
+        })
+    );
+    assert!(matches!(
+        parse_request_bytes(b"GET / HTTP/1.1\r\nX-N\xe4me: a\r\n\r\n", &options),
+        Err(ParseError::InvalidHeader {
+            error: HeaderError::InvalidNameChar { offset: 19, .. },
+            ..
+        })
+    ));
+
+    assert_eq!(
+        parse_request_bytes(b"GET / HTTP/1.1\r\nX-Name: caf\xe9 \t\r\n\r\n", &options)
+            .map(|request| request.headers.get_bytes("X-Name").map(<[u8]>::to_vec)),
+        Ok(Some(b"caf\xe9".to_vec()))
+    );
+    assert_eq!(
+        parse_request_bytes(b"GET / HTTP/1.1\r\nX-Name: \xe9\ra\r\n\r\n", &options),
+        Err(ParseError::InvalidHeader {
+            kind: ErrorKind::Tag,
+            position: Position {
+                offset: 25,
+                line: 2,
+                column: 10
+            },
+            error: HeaderError::InvalidValueChar {
+                ch: '\r',
+                offset: 25
+            }
+        })
+    );
+    let strict = ParseOptions {
+        mode: ParseMode::Strict,
+        ..ParseOptions::default()
+    };
+    assert_eq!(
+        parse_request_bytes(b"GET / HTTP/1.1\r\nX-Name: a\r\n caf\xe9\r\n\r\n", &strict),
+        Err(ParseError::ObsoleteLineFolding)
+    );
+
+    let options = ParseOptions {
+        skip_malformed_headers: true,
+        ..ParseOptions::default()
+    };
+    let request = parse_request_bytes(
+        b"GET / HTTP/1.1\r\nB\xe4d\r\nX-Name: caf\xe9\r\n\r\n",
+        &options,
+    )
+    .unwrap();
+    assert_eq!(request.malformed_headers, vec!["B\u{FFFD}d".to_string()]);
+    assert_eq!(request.headers.get_bytes("X-Name"), Some(&b"caf\xe9"[..]));
+}
+
+#[test]
+fn test_content_length_body() {
+    let (rest, request) = parse_http("POST /a HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello").unwrap();
+    assert_eq!(request.body, b"hello".to_vec());
+    assert_eq!(rest, "");
+
+    assert!(matches!(
+        parse_http("POST /a HTTP/1.1\r\nContent-Length: 10\r\n\r\nhello"),
+        Err(ParseError::Incomplete)
+    ));
+    assert_eq!(
+        parse_request(
+            "POST /a HTTP/1.1\r\nContent-Length: 10\r\n\r\nhello",
+            &ParseOptions::default()
+        ),
+        Err(ParseError::Incomplete)
+    );
+
+    let pipelined = "POST /a HTTP/1.1\r\nContent-Length: 2\r\n\r\nokGET /b HTTP/1.1\r\n\r\n";
+    let (rest, request) = parse_http(pipelined).unwrap();
+    assert_eq!(request.body, b"ok".to_vec());
+    assert_eq!(rest, "GET /b HTTP/1.1\r\n\r\n");
+
+    let (rest, request) =
+        parse_http("POST /a HTTP/1.1\r\nContent-Length: 0\r\n\r\nGET /b").unwrap();
+    assert!(request.body.is_empty());
+    assert_eq!(rest, "GET /b");
+
+    let (rest, request) = parse_http("GET /a HTTP/1.1\r\n\r\nGET /b").unwrap();
+    assert!(request.body.is_empty());
+    assert_eq!(rest, "GET /b");
+}
+
+#[test]
+fn test_zero_content_length() {
+    let input = "POST /a HTTP/1.1\r\nContent-Length: 0\r\n\r\nPOST /b HTTP/1.1\r\nContent-Length: 2\r\n\r\nhi";
+    let (rest, first) = parse_http(input).unwrap();
+    assert!(first.body.is_empty());
+    let (rest, second) = parse_http(rest).unwrap();
+    assert_eq!(second.body, b"hi".to_vec());
+    assert_eq!(rest, "");
+
+    let request = parse_request_bytes(input.as_bytes(), &ParseOptions::default()).unwrap();
+    assert!(request.body.is_empty());
+}
+
+#[test]
+fn test_bytes_body_framing() {
+    let options = ParseOptions::default();
+    // Transfer-Encoding overrides Content-Length, so the smuggled request is
+    // not part of the body
+    let input = "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nContent-Length: 30\r\n\r\n3\r\nabc\r\n0\r\n\r\nGET /admin HTTP/1.1\r\n\r\n";
+    let (rest, request) = parse_http(input).unwrap();
+    assert_eq!(rest, "GET /admin HTTP/1.1\r\n\r\n");
+    assert_eq!(
+        parse_request_bytes(input.as_bytes(), &options).map(|r| r.body),
+        Ok(request.body)
+    );
+    // without framing there is no body, a pipelined request is left alone
+    assert_eq!(
+        parse_request_bytes(
+            b"GET / HTTP/1.1\r\nHost: a\r\n\r\nGET /second HTTP/1.1\r\nHost: a\r\n\r\n",
+            &options
+        )
+        .map(|r| r.body),
+        Ok(Vec::new())
+    );
+
+    assert_eq!(
+        parse_request_bytes(
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\n\xff\x00\xfe\r\n0\r\n\r\n",
+            &options
+        )
+        .map(|r| r.body),
+        Ok(b"\xff\x00\xfe".to_vec())
+    );
+    assert_eq!(
+        parse_request_bytes(
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nab",
+            &options
+        ),
+        Err(ParseError::Incomplete)
+    );
+    assert!(matches!(
+        parse_request_bytes(
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n",
+            &options
+        ),
+        Err(ParseError::Body(BodyError::InvalidChunk(b'z')))
+    ));
+    let trailers = |trailers: &str| {
+        let input = format!(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nTrailer: X-Checksum\r\n\r\n2\r\nok\r\n0\r\n{}\r\n",
+            trailers
+        );
+        parse_request_bytes(input.as_bytes(), &options).unwrap()
+    };
+    let request = trailers("X-Checksum: abc\r\n");
+    assert_eq!(request.body, b"ok".to_vec());
+    assert_eq!(request.trailers.get("x-checksum"), Some("abc"));
+    assert_eq!(request.validate_trailers(), Ok(()));
+    assert_eq!(
+        trailers("Content-Length: 2\r\n").validate_trailers(),
+        Err(vec![TrailerError::Forbidden("Content-Length".to_string())])
+    );
+    assert!(trailers("").trailers.is_empty());
+    let limited = ParseOptions {
+        max_body_len: Some(2),
+        ..ParseOptions::default()
+    };
+    assert_eq!(
+        parse_request_bytes(
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n",
+            &limited
+        ),
+        Err(ParseError::BodyTooLarge { limit: 2, len: 3 })
+    );
+}
+
+#[test]
+fn test_max_body_len() {
+    let options = ParseOptions {
+        max_body_len: Some(10),
+        ..ParseOptions::default()
+    };
+    let declared = "POST / HTTP/1.1\r\nContent-Length: 5000000000\r\n\r\nnot all there";
+    assert_eq!(
+        parse_request(declared, &options),
+        Err(ParseError::BodyTooLarge {
+            limit: 10,
+            len: 5_000_000_000
+        })
+    );
+
+    let chunked = "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n8\r\n, world!\r\n0\r\n\r\n";
+    assert_eq!(
+        parse_request(chunked, &options),
+        Err(ParseError::BodyTooLarge { limit: 10, len: 13 })
+    );
+    assert_eq!(
+        parse_request(chunked, &ParseOptions::default()).map(|r| r.body),
+        Ok(b"hello, world!".to_vec())
+    );
+
+    let exact = "POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\n0123456789";
+    assert_eq!(
+        parse_request(exact, &options).map(|r| r.body),
+        Ok(b"0123456789".to_vec())
+    );
+    assert_eq!(
+        parse_request_bytes(exact.as_bytes(), &options).map(|r| r.body),
+        Ok(b"0123456789".to_vec())
+    );
+}
+
+#[test]
+fn test_body_accessors() {
+    let text = parse_http("POST / HTTP/1.1\r\nContent-Length: 6\r\n\r\ngrüß")
+        .unwrap()
+        .1;
+    assert_eq!(text.body_bytes(), "grüß".as_bytes());
+    assert_eq!(text.body_str(), Ok("grüß"));
+
+    let binary = parse_request_bytes(
+        b"POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\n\xff\x00\xfe",
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(binary.body_bytes(), b"\xff\x00\xfe");
+    assert!(binary.body_str().is_err());
+}
+
+#[test]
+fn test_pseudo_headers() {
+    let pseudo = |i| parse_http(i).unwrap().1.to_pseudo_headers();
+    let pairs = |p: &[(&str, &str)]| {
+        p.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<Vec<(String, String)>>()
+    };
+
+    assert_eq!(
+        pseudo("GET /a/b?c=d HTTP/1.1\r\nHost: example.org:8080\r\nAccept: */*\r\n\r\n"),
+        pairs(&[
+            (":method", "GET"),
+            (":scheme", "http"),
+            (":authority", "example.org:8080"),
+            (":path", "/a/b?c=d"),
+        ])
+    );
+    assert_eq!(
+        pseudo("GET https://example.org HTTP/1.1\r\n\r\n"),
+        pairs(&[
+            (":method", "GET"),
+            (":scheme", "https"),
+            (":authority", "example.org"),
+            (":path", "/"),
+        ])
+    );
+    assert_eq!(
+        pseudo("CONNECT example.org:443 HTTP/1.1\r\n\r\n"),
+        pairs(&[(":method", "CONNECT"), (":authority", "example.org:443")])
+    );
+}
+
+#[test]
+fn test_list() {
+    assert_eq!(list(token)(" a, ,b ,c,"), Ok(("", vec!["a", "b", "c"])));
+    assert_eq!(list(token)(""), Ok(("", vec![])));
+    assert_eq!(
+        list(quoted_string)("\"a, b\", \"c\""),
+        Ok(("", vec!["a, b".to_string(), "c".to_string()]))
+    );
+}
+
+#[test]
+fn test_parameters() {
+    assert_eq!(
+        parameters("; charset=utf-8; name=\"a \\\"b\\\" c\""),
+        Ok((
+            "",
+            vec![
+                ("charset".to_string(), "utf-8".to_string()),
+                ("name".to_string(), "a \"b\" c".to_string())
+            ]
+        ))
+    );
+    assert_eq!(
+        parameters(" ;q = 0.5;  secure ; level=1"),
+        Ok((
+            "",
+            vec![
+                ("q".to_string(), "0.5".to_string()),
+                ("secure".to_string(), "".to_string()),
+                ("level".to_string(), "1".to_string())
+            ]
+        ))
+    );
+    assert_eq!(
+        parameters("; a=b;, next"),
+        Ok((", next", vec![("a".to_string(), "b".to_string())]))
+    );
 }
@@ -0,0 +1,277 @@
+use crate::{headers, line_ending, ContentRange, Headers};
+use nom::{combinator::all_consuming, sequence::terminated, Err as NomErr};
+
+/// A part of a multipart/form-data body: https://tools.ietf.org/html/rfc7578
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Part {
+    /// The `name` parameter of the part's Content-Disposition.
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    /// All header fields of the part, including the ones above.
+    pub headers: Headers,
+    pub data: Vec<u8>,
+}
+
+/// A part of a multipart/byteranges body, sent in a 206 response to a request
+/// for several ranges: https://tools.ietf.org/html/rfc7233#appendix-A
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RangePart {
+    pub content_range: ContentRange,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MultipartError {
+    /// No delimiter line with the boundary was found.
+    NoBoundary,
+    /// The body ends without the closing `--boundary--` delimiter.
+    Unterminated,
+    /// The header section of the part with the given index does not parse.
+    Headers(usize),
+    /// The part with the given index has no Content-Disposition with a name.
+    MissingName(usize),
+    /// The byterange part with the given index has no satisfied Content-Range.
+    MissingContentRange(usize),
+    /// The data of the byterange part with the given index does not match the
+    /// length of its range, or its complete length differs from earlier parts.
+    InvalidRange(usize),
+    /// The range of the byterange part with the given index overlaps an
+    /// earlier part's range.
+    OverlappingRange(usize),
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|i| from + i)
+}
+
+// the header section and content of each body part:
+// https://tools.ietf.org/html/rfc2046#section-5.1.1
+// The preamble before the first and the epilogue after the closing delimiter
+// are ignored.
+pub(crate) fn body_parts(
+    body: &[u8],
+    boundary: &str,
+) -> Result<Vec<(Headers, Vec<u8>)>, MultipartError> {
+    if boundary.is_empty() {
+        return Err(MultipartError::NoBoundary);
+    }
+    let delimiter = format!("\r\n--{}", boundary).into_bytes();
+    // the first delimiter may start the body, without the CRLF in front of it
+    let mut position = if body.starts_with(&delimiter[2..]) {
+        delimiter.len() - 2
+    } else {
+        find(body, &delimiter, 0).ok_or(MultipartError::NoBoundary)? + delimiter.len()
+    };
+
+    let mut parts = Vec::new();
+    loop {
+        let rest = &body[position..];
+        if rest.starts_with(b"--") {
+            return Ok(parts);
+        }
+        // transport padding, then the end of the delimiter line
+        let padding = rest
+            .iter()
+            .take_while(|&&byte| byte == b' ' || byte == b'\t')
+            .count();
+        if !rest[padding..].starts_with(b"\r\n") {
+            return Err(MultipartError::Unterminated);
+        }
+        let start = position + padding + 2;
+        let end = find(body, &delimiter, start).ok_or(MultipartError::Unterminated)?;
+        parts.push(body_part(&body[start..end], parts.len())?);
+        position = end + delimiter.len();
+    }
+}
+
+fn body_part(part: &[u8], index: usize) -> Result<(Headers, Vec<u8>), MultipartError> {
+    let head_len = if part.starts_with(b"\r\n") {
+        2
+    } else {
+        find(part, b"\r\n\r\n", 0).ok_or(MultipartError::Headers(index))? + 4
+    };
+    let head =
+        std::str::from_utf8(&part[..head_len]).map_err(|_| MultipartError::Headers(index))?;
+    let (_, headers) = all_consuming(terminated(headers, line_ending))(head)
+        .map_err(|_: NomErr<()>| MultipartError::Headers(index))?;
+    Ok((headers, part[head_len..].to_vec()))
+}
+
+/// Splits a multipart/form-data body into its parts. The boundary is the
+/// `boundary` parameter of the request's Content-Type. Part data is kept as
+/// it was sent, and each part's header fields are parsed like those of a
+/// request.
+pub fn parse_multipart(body: &[u8], boundary: &str) -> Result<Vec<Part>, MultipartError> {
+    body_parts(body, boundary)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, (headers, data))| {
+            let disposition = match headers.content_disposition() {
+                Some(Ok(disposition)) => disposition,
+                _ => return Err(MultipartError::MissingName(index)),
+            };
+            let name = disposition
+                .name()
+                .ok_or(MultipartError::MissingName(index))?
+                .to_owned();
+            Ok(Part {
+                name,
+                filename: disposition.filename().map(|filename| filename.to_owned()),
+                content_type: headers.get("Content-Type").map(|value| value.to_owned()),
+                headers,
+                data,
+            })
+        })
+        .collect()
+}
+
+/// Splits a multipart/byteranges body into its parts. The boundary is the
+/// `boundary` parameter of the response's Content-Type. Parts are returned in
+/// the order they were sent, which need not be the order of their ranges.
+pub fn parse_byteranges(body: &[u8], boundary: &str) -> Result<Vec<RangePart>, MultipartError> {
+    let mut parts: Vec<RangePart> = Vec::new();
+    for (index, (headers, data)) in body_parts(body, boundary)?.into_iter().enumerate() {
+        let content_range = match headers.content_range() {
+            Some(Ok(range @ ContentRange::Satisfied { .. })) => range,
+            _ => return Err(MultipartError::MissingContentRange(index)),
+        };
+        let (first, last, total) = bounds(&content_range);
+        let len = last
+            .checked_sub(first)
+            .and_then(|len| len.checked_add(1))
+            .ok_or(MultipartError::InvalidRange(index))?;
+        if data.len() as u64 != len {
+            return Err(MultipartError::InvalidRange(index));
+        }
+        for part in &parts {
+            let (other_first, other_last, other_total) = bounds(&part.content_range);
+            if total.is_some() && other_total.is_some() && total != other_total {
+                return Err(MultipartError::InvalidRange(index));
+            }
+            if first <= other_last && other_first <= last {
+                return Err(MultipartError::OverlappingRange(index));
+            }
+        }
+        parts.push(RangePart {
+            content_range,
+            content_type: headers.get("Content-Type").map(|value| value.to_owned()),
+            data,
+        });
+    }
+    Ok(parts)
+}
+
+// parts only hold satisfied ranges
+fn bounds(range: &ContentRange) -> (u64, u64, Option<u64>) {
+    match range {
+        ContentRange::Satisfied {
+            first, last, total, ..
+        } => (*first, *last, *total),
+        ContentRange::Unsatisfied { total, .. } => (0, 0, Some(*total)),
+    }
+}
+
+#[test]
+fn test_parse_multipart() {
+    let mut body = b"preamble\r\n--XyZ\r\n".to_vec();
+    body.extend_from_slice(b"Content-Disposition: form-data; name=\"title\"\r\n\r\n");
+    body.extend_from_slice(b"Holiday\r\n--XyZ\r\n");
+    body.extend_from_slice(
+        b"Content-Disposition: form-data; name=\"photo\"; filename=\"a.png\"\r\n",
+    );
+    body.extend_from_slice(b"Content-Type: image/png\r\n\r\n");
+    body.extend_from_slice(b"\x89PNG\r\n\x1a\n\0\xff\r\n--XyZ--\r\nepilogue");
+
+    let header = |name: &str, value: &str| (name.to_string(), value.to_string());
+    assert_eq!(
+        parse_multipart(&body, "XyZ"),
+        Ok(vec![
+            Part {
+                name: "title".to_string(),
+                filename: None,
+                content_type: None,
+                headers: vec![header("Content-Disposition", "form-data; name=\"title\"")].into(),
+                data: b"Holiday".to_vec(),
+            },
+            Part {
+                name: "photo".to_string(),
+                filename: Some("a.png".to_string()),
+                content_type: Some("image/png".to_string()),
+                headers: vec![
+                    header(
+                        "Content-Disposition",
+                        "form-data; name=\"photo\"; filename=\"a.png\""
+                    ),
+                    header("Content-Type", "image/png"),
+                ]
+                .into(),
+                data: b"\x89PNG\r\n\x1a\n\0\xff".to_vec(),
+            },
+        ])
+    );
+
+    let unterminated = b"--XyZ\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1\r\n";
+    assert_eq!(
+        parse_multipart(unterminated, "XyZ"),
+        Err(MultipartError::Unterminated)
+    );
+    assert_eq!(
+        parse_multipart(b"no parts", "XyZ"),
+        Err(MultipartError::NoBoundary)
+    );
+    assert_eq!(
+        parse_multipart(
+            b"--XyZ\r\nContent-Type: text/plain\r\n\r\n1\r\n--XyZ--",
+            "XyZ"
+        ),
+        Err(MultipartError::MissingName(0))
+    );
+}
+
+#[test]
+fn test_parse_byteranges() {
+    let mut body = b"--THIS_STRING_SEPARATES\r\n".to_vec();
+    body.extend_from_slice(b"Content-Type: text/plain\r\nContent-Range: bytes 7-11/13\r\n\r\n");
+    body.extend_from_slice(b"world\r\n--THIS_STRING_SEPARATES\r\n");
+    body.extend_from_slice(b"Content-Type: text/plain\r\nContent-Range: bytes 0-4/13\r\n\r\n");
+    body.extend_from_slice(b"hello\r\n--THIS_STRING_SEPARATES--\r\n");
+
+    let parts = parse_byteranges(&body, "THIS_STRING_SEPARATES").unwrap();
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].content_type, Some("text/plain".to_string()));
+    let mut resource = b"?????, ?????!".to_vec();
+    for part in parts {
+        let (first, _, _) = bounds(&part.content_range);
+        let first = first as usize;
+        resource[first..first + part.data.len()].copy_from_slice(&part.data);
+    }
+    assert_eq!(resource, b"hello, world!".to_vec());
+
+    let missing = b"--B\r\nContent-Type: text/plain\r\n\r\nhello\r\n--B--";
+    assert_eq!(
+        parse_byteranges(missing, "B"),
+        Err(MultipartError::MissingContentRange(0))
+    );
+    let short = b"--B\r\nContent-Range: bytes 0-9/14\r\n\r\nhello\r\n--B--";
+    assert_eq!(
+        parse_byteranges(short, "B"),
+        Err(MultipartError::InvalidRange(0))
+    );
+    let huge = b"--B\r\nContent-Range: bytes 0-18446744073709551615/*\r\n\r\nhello\r\n--B--";
+    assert_eq!(
+        parse_byteranges(huge, "B"),
+        Err(MultipartError::InvalidRange(0))
+    );
+    let overlapping = b"--B\r\nContent-Range: bytes 0-4/14\r\n\r\nhello\r\n\
+        --B\r\nContent-Range: bytes 2-3/14\r\n\r\nll\r\n--B--";
+    assert_eq!(
+        parse_byteranges(overlapping, "B"),
+        Err(MultipartError::OverlappingRange(1))
+    );
+}
@@ -0,0 +1,308 @@
+use crate::{percent, URI};
+
+/// A value of a query or form with keys in bracket notation, as used by
+/// Rails and PHP: `user[name]=jo&user[tags][]=a`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum QueryValue {
+    Scalar(String),
+    Array(Vec<QueryValue>),
+    /// Entries in the order their keys first appeared.
+    Map(Vec<(String, QueryValue)>),
+}
+
+impl QueryValue {
+    /// The entry of a map with the given key.
+    pub fn get(&self, key: &str) -> Option<&QueryValue> {
+        match self {
+            QueryValue::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            QueryValue::Scalar(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// The most bracketed segments `parse_nested` follows in a key, so that an
+/// attacker-controlled key cannot nest values without bound.
+pub const MAX_NESTING_DEPTH: usize = 32;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum NestedError {
+    /// The key uses a name as a scalar and as a map or array, or as both a map
+    /// and an array.
+    Conflict(String),
+    /// The key has an array index past the end of the array.
+    Index(String),
+    /// The key has more than `MAX_NESTING_DEPTH` bracketed segments.
+    TooDeep(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+    /// `[]` appends to an array.
+    Append,
+}
+
+// the name in front of the first bracket and the bracketed segments after it;
+// keys with unbalanced brackets are taken as a plain name
+fn segments(key: &str) -> Vec<Segment<'_>> {
+    let plain = vec![Segment::Key(key)];
+    let (name, mut rest) = match key.find('[') {
+        Some(i) if i > 0 => key.split_at(i),
+        _ => return plain,
+    };
+    let mut segments = vec![Segment::Key(name)];
+    while !rest.is_empty() {
+        let end = match rest.find(']') {
+            Some(end) if rest.starts_with('[') => end,
+            _ => return plain,
+        };
+        let inner = &rest[1..end];
+        if inner.contains('[') {
+            return plain;
+        }
+        segments.push(match inner {
+            "" => Segment::Append,
+            _ if inner.bytes().all(|b| b.is_ascii_digit()) => match inner.parse() {
+                Ok(index) => Segment::Index(index),
+                Err(_) => Segment::Key(inner),
+            },
+            _ => Segment::Key(inner),
+        });
+        rest = &rest[end + 1..];
+    }
+    segments
+}
+
+// a new slot for the segment after the current one
+fn empty(next: Option<&Segment>) -> QueryValue {
+    match next {
+        None => QueryValue::Scalar(String::new()),
+        Some(Segment::Key(_)) => QueryValue::Map(Vec::new()),
+        Some(_) => QueryValue::Array(Vec::new()),
+    }
+}
+
+fn insert(
+    node: &mut QueryValue,
+    path: &[Segment],
+    value: String,
+    key: &str,
+) -> Result<(), NestedError> {
+    let next = path.get(1);
+    let child = match (node, &path[0]) {
+        (QueryValue::Map(entries), Segment::Key(name)) => {
+            let i = match entries.iter().position(|(k, _)| k == name) {
+                Some(i) => i,
+                None => {
+                    entries.push((name.to_string(), empty(next)));
+                    entries.len() - 1
+                }
+            };
+            &mut entries[i].1
+        }
+        (QueryValue::Array(items), Segment::Index(i)) if *i < items.len() => &mut items[*i],
+        (QueryValue::Array(items), Segment::Index(i)) if *i > items.len() => {
+            return Err(NestedError::Index(key.to_owned()))
+        }
+        (QueryValue::Array(items), Segment::Index(_))
+        | (QueryValue::Array(items), Segment::Append) => {
+            items.push(empty(next));
+            let last = items.len() - 1;
+            &mut items[last]
+        }
+        _ => return Err(NestedError::Conflict(key.to_owned())),
+    };
+    match (next, child) {
+        // like a flat key, a repeated scalar takes the last value
+        (None, QueryValue::Scalar(scalar)) => {
+            *scalar = value;
+            Ok(())
+        }
+        (None, _) | (Some(_), QueryValue::Scalar(_)) => Err(NestedError::Conflict(key.to_owned())),
+        (Some(_), child) => insert(child, &path[1..], value, key),
+    }
+}
+
+/// Builds the nested structure of decoded pairs, as returned by
+/// `parse_form_body` or in the query of a `URI` once percent-decoded. `[]`
+/// appends to an array and `[0]` indexes into one, an index may be at most
+/// the length of the array. Keys without brackets stay scalars, keys with more
+/// than `MAX_NESTING_DEPTH` bracketed segments are rejected.
+pub fn parse_nested(pairs: &[(String, String)]) -> Result<Vec<(String, QueryValue)>, NestedError> {
+    let mut root = QueryValue::Map(Vec::new());
+    for (key, value) in pairs {
+        let segments = segments(key);
+        // the name in front of the brackets is not nested
+        if segments.len() > MAX_NESTING_DEPTH + 1 {
+            return Err(NestedError::TooDeep(key.to_owned()));
+        }
+        insert(&mut root, &segments, value.to_owned(), key)?;
+    }
+    match root {
+        QueryValue::Map(entries) => Ok(entries),
+        _ => Ok(Vec::new()),
+    }
+}
+
+impl URI {
+    /// The query with keys in bracket notation built into nested values, see
+    /// `parse_nested`. Keys and values are percent-decoded first; `query`
+    /// keeps the flat pairs as they were sent.
+    pub fn query_structured(&self) -> Option<Result<Vec<(String, QueryValue)>, NestedError>> {
+        self.query.as_ref().map(|query| {
+            let pairs: Vec<(String, String)> = query
+                .iter()
+                .map(|(key, value)| (percent::decode_lossy(key), percent::decode_lossy(value)))
+                .collect();
+            parse_nested(&pairs)
+        })
+    }
+}
+
+#[test]
+fn test_parse_nested() {
+    use crate::{parse_form_body, percent, query_params};
+
+    let scalar = |value: &str| QueryValue::Scalar(value.to_string());
+    let expected = vec![(
+        "user".to_string(),
+        QueryValue::Map(vec![
+            ("name".to_string(), scalar("jo")),
+            (
+                "tags".to_string(),
+                QueryValue::Array(vec![scalar("a"), scalar("b")]),
+            ),
+        ]),
+    )];
+
+    let body = "user[name]=jo&user[tags][]=a&user[tags][]=b";
+    assert_eq!(
+        parse_nested(&parse_form_body(body).unwrap()),
+        Ok(expected.clone())
+    );
+
+    // brackets may be percent-encoded in a query
+    let (_, query) = query_params::<nom::error::Error<&str>>(
+        "?user%5Bname%5D=jo&user%5Btags%5D%5B%5D=a&user%5Btags%5D%5B%5D=b",
+    )
+    .unwrap();
+    let query: Vec<(String, String)> = query
+        .iter()
+        .map(|(key, value)| (percent::decode_lossy(key), percent::decode_lossy(value)))
+        .collect();
+    assert_eq!(parse_nested(&query), Ok(expected));
+
+    let pairs = parse_form_body("items[0][id]=7&items[1][id]=9&items[0][qty]=2&page=1").unwrap();
+    let nested = QueryValue::Map(parse_nested(&pairs).unwrap());
+    assert_eq!(
+        nested.get("items"),
+        Some(&QueryValue::Array(vec![
+            QueryValue::Map(vec![
+                ("id".to_string(), scalar("7")),
+                ("qty".to_string(), scalar("2")),
+            ]),
+            QueryValue::Map(vec![("id".to_string(), scalar("9"))]),
+        ]))
+    );
+    assert_eq!(nested.get("page").and_then(QueryValue::as_str), Some("1"));
+}
+
+#[test]
+fn test_parse_nested_errors() {
+    let pairs = |body: &str| crate::parse_form_body(body).unwrap();
+
+    assert_eq!(
+        parse_nested(&pairs("user=jo&user[name]=jo")),
+        Err(NestedError::Conflict("user[name]".to_string()))
+    );
+    assert_eq!(
+        parse_nested(&pairs("user[name]=jo&user=jo")),
+        Err(NestedError::Conflict("user".to_string()))
+    );
+    assert_eq!(
+        parse_nested(&pairs("a[]=1&a[x]=2")),
+        Err(NestedError::Conflict("a[x]".to_string()))
+    );
+    assert_eq!(
+        parse_nested(&pairs("a[2]=1")),
+        Err(NestedError::Index("a[2]".to_string()))
+    );
+    let deep = format!("a{}=1", "[x]".repeat(200_000));
+    assert_eq!(
+        parse_nested(&pairs(&deep)),
+        Err(NestedError::TooDeep(deep[..deep.len() - 2].to_string()))
+    );
+    let deepest = format!("a{}=1", "[x]".repeat(MAX_NESTING_DEPTH));
+    assert!(parse_nested(&pairs(&deepest)).is_ok());
+    assert_eq!(
+        parse_nested(&pairs("a[b=1&[c]=2")),
+        Ok(vec![
+            ("a[b".to_string(), QueryValue::Scalar("1".to_string())),
+            ("[c]".to_string(), QueryValue::Scalar("2".to_string())),
+        ])
+    );
+}
+
+#[test]
+fn test_query_structured() {
+    let query = |target: &str| crate::URI::parse(target).unwrap().query_structured();
+    let scalar = |value: &str| QueryValue::Scalar(value.to_string());
+
+    assert_eq!(
+        query("/?a%5Bb%5D=1"),
+        Some(Ok(vec![(
+            "a".to_string(),
+            QueryValue::Map(vec![("b".to_string(), scalar("1"))])
+        )]))
+    );
+    assert_eq!(
+        query("/?list%5B%5D=1&list%5B%5D=2"),
+        Some(Ok(vec![(
+            "list".to_string(),
+            QueryValue::Array(vec![scalar("1"), scalar("2")])
+        )]))
+    );
+    let uri = crate::URI::parse("/?page=2&filter%5Bname%5D=jo%20k&sort=asc").unwrap();
+    assert_eq!(
+        uri.query_structured(),
+        Some(Ok(vec![
+            ("page".to_string(), scalar("2")),
+            (
+                "filter".to_string(),
+                QueryValue::Map(vec![("name".to_string(), scalar("jo k"))])
+            ),
+            ("sort".to_string(), scalar("asc")),
+        ]))
+    );
+    assert_eq!(uri.query.map(|q| q.len()), Some(3));
+    assert_eq!(query("/path"), None);
+
+    // unencoded, like browsers send them
+    let nested = |target: &str| {
+        let (_, uri) = crate::uri::<nom::error::Error<&str>>(target).unwrap();
+        assert_eq!(Some(uri.clone()), crate::URI::parse(target).ok());
+        uri.query_structured()
+    };
+    assert_eq!(
+        nested("/?a[b]=1"),
+        Some(Ok(vec![(
+            "a".to_string(),
+            QueryValue::Map(vec![("b".to_string(), scalar("1"))])
+        )]))
+    );
+    assert_eq!(
+        nested("/?list[]=1&list[]=2"),
+        Some(Ok(vec![(
+            "list".to_string(),
+            QueryValue::Array(vec![scalar("1"), scalar("2")])
+        )]))
+    );
+}
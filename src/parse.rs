@@ -0,0 +1,156 @@
+//! Parsing entry points that return `Result<T, ParseError>`. The nom parsers
+//! behind them are private, so nom types do not show up in the public API.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use nom::IResult;
+
+use crate::alt_svc::alt_svc;
+use crate::auth::credentials;
+use crate::chunked;
+use crate::conditional::entity_tag;
+use crate::content::{content_disposition, content_range, media_type};
+use crate::cors::{allow_origin, origin};
+use crate::headers::parse_value;
+use crate::{
+    parse_request, parse_response, request_method, AllowOrigin, AltSvc, Chunk, ChunkedBody,
+    ContentDisposition, ContentRange, Credentials, EntityTag, Headers, MediaType, Method, Origin,
+    ParseError, ParseOptions, Request, Response, StatusLine, URI,
+};
+
+/// A type that can be parsed from a complete header field value, see
+/// `header_value`.
+pub trait FromHeaderValue: Sized {
+    fn from_header_value(value: &str) -> Result<Self, ParseError>;
+}
+
+/// Parses a complete request with the default options, see `parse_request`.
+pub fn request(input: &str) -> Result<Request, ParseError> {
+    parse_request(input, &ParseOptions::default())
+}
+
+/// Parses a complete response with the default options, see `parse_response`.
+pub fn response(input: &str) -> Result<Response, ParseError> {
+    parse_response(input, &ParseOptions::default())
+}
+
+/// Parses a request target, which has to make up all of `input`.
+pub fn uri(input: &str) -> Result<URI, ParseError> {
+    URI::parse(input)
+}
+
+/// Parses a header section up to the empty line ending it, and returns the
+/// rest of `input` with the headers.
+pub fn headers(input: &str) -> Result<(&str, Headers), ParseError> {
+    leading(input, crate::headers)
+}
+
+// borrowed names and values
+type Fields<'a> = Vec<(&'a str, Cow<'a, str>)>;
+
+/// Like `headers`, but names and values borrow from the input instead of being
+/// copied.
+pub fn header_slices(input: &str) -> Result<(&str, Fields<'_>), ParseError> {
+    leading(input, crate::header_slices)
+}
+
+/// Like `headers`, but keyed by lowercased name.
+pub fn headers_map(input: &str) -> Result<(&str, HashMap<String, Vec<String>>), ParseError> {
+    leading(input, crate::parse_headers_map)
+}
+
+/// Like `headers`, but lines that are not a valid header are skipped and returned
+/// separately.
+pub fn headers_skipping_malformed(
+    input: &str,
+) -> Result<(&str, (Headers, Vec<String>)), ParseError> {
+    leading(input, crate::headers_skipping_malformed)
+}
+
+/// Parses a status line like `HTTP/1.1 200 OK` and returns the rest of `input`
+/// with it.
+pub fn status_line(input: &str) -> Result<(&str, StatusLine), ParseError> {
+    leading(input, crate::response::status_line)
+}
+
+/// Decodes a chunked body and returns the rest of `input` with it.
+pub fn chunked_body(input: &str) -> Result<(&str, ChunkedBody), ParseError> {
+    leading(input, chunked::chunked_body)
+}
+
+// the chunks and the trailer section
+type Chunks = (Vec<Chunk>, Headers);
+
+/// Like `chunked_body`, but keeps the chunks apart, along with their extensions.
+pub fn chunks<'a>(input: &'a str, options: &ParseOptions) -> Result<(&'a str, Chunks), ParseError> {
+    leading(input, |input| chunked::chunks(input, options))
+}
+
+// runs `parser` on the start of `input`, leaving the rest to the caller
+fn leading<'a, O, F>(input: &'a str, mut parser: F) -> Result<(&'a str, O), ParseError>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    parser(input).map_err(|e| ParseError::from_nom(input, e))
+}
+
+/// Parses a header field value like `text/html; charset=utf-8` into `T`.
+/// Whitespace around the value is ignored, anything else left over is an error.
+pub fn header_value<T: FromHeaderValue>(value: &str) -> Result<T, ParseError> {
+    T::from_header_value(value)
+}
+
+impl FromHeaderValue for MediaType {
+    fn from_header_value(value: &str) -> Result<Self, ParseError> {
+        parse_value(value, media_type)
+    }
+}
+
+impl FromHeaderValue for ContentDisposition {
+    fn from_header_value(value: &str) -> Result<Self, ParseError> {
+        parse_value(value, content_disposition)
+    }
+}
+
+impl FromHeaderValue for ContentRange {
+    fn from_header_value(value: &str) -> Result<Self, ParseError> {
+        parse_value(value, content_range)
+    }
+}
+
+impl FromHeaderValue for EntityTag {
+    fn from_header_value(value: &str) -> Result<Self, ParseError> {
+        parse_value(value, entity_tag)
+    }
+}
+
+impl FromHeaderValue for Credentials {
+    fn from_header_value(value: &str) -> Result<Self, ParseError> {
+        parse_value(value, credentials)
+    }
+}
+
+impl FromHeaderValue for Origin {
+    fn from_header_value(value: &str) -> Result<Self, ParseError> {
+        parse_value(value, origin)
+    }
+}
+
+impl FromHeaderValue for AllowOrigin {
+    fn from_header_value(value: &str) -> Result<Self, ParseError> {
+        parse_value(value, allow_origin)
+    }
+}
+
+impl FromHeaderValue for AltSvc {
+    fn from_header_value(value: &str) -> Result<Self, ParseError> {
+        parse_value(value, alt_svc)
+    }
+}
+
+impl FromHeaderValue for Method {
+    fn from_header_value(value: &str) -> Result<Self, ParseError> {
+        parse_value(value, request_method)
+    }
+}
@@ -0,0 +1,138 @@
+//! Percent-encoding: https://tools.ietf.org/html/rfc3986#section-2.1
+
+use crate::{ParseError, Position};
+use nom::error::ErrorKind;
+use std::fmt::Write;
+
+/// The characters a URI component allows unencoded besides the unreserved ones
+/// (`A-Z a-z 0-9 - . _ ~`): https://tools.ietf.org/html/rfc3986#section-2.2
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AllowedSet(&'static str);
+
+/// path: https://tools.ietf.org/html/rfc3986#section-3.3
+pub const PATH: AllowedSet = AllowedSet("!$&'()*+,;=:@/");
+/// A key or value of a query pair: https://tools.ietf.org/html/rfc3986#section-3.4
+/// `&` and `=` delimit pairs and `+` stands for a space in forms, so they are
+/// encoded.
+pub const QUERY: AllowedSet = AllowedSet("!$'()*,;:@/?");
+/// fragment: https://tools.ietf.org/html/rfc3986#section-3.5
+pub const FRAGMENT: AllowedSet = AllowedSet("!$&'()*+,;=:@/?");
+/// userinfo: https://tools.ietf.org/html/rfc3986#section-3.2.1
+pub const USERINFO: AllowedSet = AllowedSet("!$&'()*+,;=:");
+
+/// Encodes the UTF-8 bytes of `input` that are neither unreserved nor in
+/// `allowed` as `%XX`.
+pub fn encode(input: &str, allowed: AllowedSet) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        let chr = char::from(byte);
+        if byte.is_ascii_alphanumeric() || "-._~".contains(chr) || allowed.0.contains(chr) {
+            encoded.push(chr);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+/// Decodes `%XX` escapes. A `%` not followed by two hex digits fails with
+/// `ErrorKind::HexDigit` at its offset, decoded bytes that are not UTF-8 with
+/// `ErrorKind::Char`.
+pub fn decode(input: &str) -> Result<String, ParseError> {
+    let decoded = decode_bytes(input, true).map_err(|offset| ParseError::Syntax {
+        kind: ErrorKind::HexDigit,
+        position: Position::new(input, offset),
+    })?;
+    String::from_utf8(decoded).map_err(|e| {
+        // back from the decoded bytes to the escape the invalid one came from,
+        // all escapes are well-formed by now
+        let bytes = input.as_bytes();
+        let mut offset = 0;
+        for _ in 0..e.utf8_error().valid_up_to() {
+            offset += if bytes[offset] == b'%' { 3 } else { 1 };
+        }
+        ParseError::Syntax {
+            kind: ErrorKind::Char,
+            position: Position::new(input, offset),
+        }
+    })
+}
+
+/// Decodes `%XX` escapes, leaving malformed ones as they are.
+pub(crate) fn decode_lossy(input: &str) -> String {
+    let decoded = decode_bytes(input, false).unwrap_or_default();
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// the decoded bytes, or with `strict` the offset of the first malformed escape
+fn decode_bytes(input: &str, strict: bool) -> Result<Vec<u8>, usize> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' => bytes.get(i + 1..i + 3).and_then(hex),
+            _ => None,
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None if strict && bytes[i] == b'%' => return Err(i),
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    Ok(decoded)
+}
+
+fn hex(digits: &[u8]) -> Option<u8> {
+    let high = char::from(digits[0]).to_digit(16)?;
+    let low = char::from(digits[1]).to_digit(16)?;
+    Some((high * 16 + low) as u8)
+}
+
+#[test]
+fn test_encode_decode() {
+    let input = "grüße & küsse/100%?";
+    for allowed in [PATH, QUERY, FRAGMENT, USERINFO].iter() {
+        assert_eq!(decode(&encode(input, *allowed)).as_deref(), Ok(input));
+    }
+    assert_eq!(
+        encode(input, PATH),
+        "gr%C3%BC%C3%9Fe%20&%20k%C3%BCsse/100%25%3F"
+    );
+    assert_eq!(
+        encode(input, QUERY),
+        "gr%C3%BC%C3%9Fe%20%26%20k%C3%BCsse/100%25?"
+    );
+    assert_eq!(encode("user:pass@host", USERINFO), "user:pass%40host");
+    assert_eq!(encode("a+b=c", QUERY), "a%2Bb%3Dc");
+}
+
+#[test]
+fn test_decode_malformed() {
+    assert_eq!(
+        decode("100%"),
+        Err(ParseError::Syntax {
+            kind: ErrorKind::HexDigit,
+            position: Position::new("100%", 3)
+        })
+    );
+    assert_eq!(decode("a%+Fb").map_err(|e| e.offset()), Err(Some(1)));
+    assert_eq!(
+        decode("ok-%C3%28"),
+        Err(ParseError::Syntax {
+            kind: ErrorKind::Char,
+            position: Position {
+                offset: 3,
+                line: 1,
+                column: 4
+            }
+        })
+    );
+    assert_eq!(decode_lossy("100%-%41"), "100%-A");
+}
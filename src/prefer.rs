@@ -0,0 +1,148 @@
+use crate::{list, ows, parameters, token, token_or_quoted_string, Headers, Parameters};
+use nom::{
+    bytes::complete::tag,
+    combinator::{all_consuming, opt},
+    sequence::{preceded, tuple},
+    IResult,
+};
+
+/// preference: https://tools.ietf.org/html/rfc7240#section-2
+/// Names are case-insensitive and stored in lowercase.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Preference {
+    pub name: String,
+    pub value: Option<String>,
+    pub params: Parameters,
+}
+
+fn preference(input: &str) -> IResult<&str, Preference> {
+    tuple((
+        token,
+        opt(preceded(
+            tuple((ows, tag("="), ows)),
+            token_or_quoted_string,
+        )),
+        parameters,
+    ))(input)
+    .map(|(next_input, res)| {
+        (
+            next_input,
+            Preference {
+                name: res.0.to_lowercase(),
+                value: res.1,
+                params: res.2,
+            },
+        )
+    })
+}
+
+impl Headers {
+    /// Prefer: https://tools.ietf.org/html/rfc7240#section-2
+    /// Field lines that fail to parse are ignored, like unknown preferences. Only
+    /// the first of several preferences with the same name is returned.
+    pub fn prefer(&self) -> Option<Vec<Preference>> {
+        self.preferences("Prefer")
+    }
+
+    /// Whether the client sent the named preference, like `respond-async`.
+    pub fn prefers(&self, name: &str) -> bool {
+        self.preference(name).is_some()
+    }
+
+    /// The value of the named preference, like the seconds of `wait=10`.
+    pub fn preference_value(&self, name: &str) -> Option<String> {
+        self.preference(name)?.value
+    }
+
+    fn preference(&self, name: &str) -> Option<Preference> {
+        self.prefer()?
+            .into_iter()
+            .find(|preference| preference.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Preference-Applied: https://tools.ietf.org/html/rfc7240#section-3
+    pub fn preference_applied(&self) -> Option<Vec<Preference>> {
+        self.preferences("Preference-Applied")
+    }
+
+    fn preferences(&self, name: &str) -> Option<Vec<Preference>> {
+        if !self.contains(name) {
+            return None;
+        }
+        let mut preferences: Vec<Preference> = vec![];
+        for preference in self
+            .get_all(name)
+            .filter_map(|value| all_consuming(list(preference))(value).ok())
+            .flat_map(|(_, res)| res)
+        {
+            if !preferences.iter().any(|p| p.name == preference.name) {
+                preferences.push(preference);
+            }
+        }
+        Some(preferences)
+    }
+}
+
+#[test]
+fn test_prefer() {
+    let headers = |n: &str, v: &str| Headers::from(vec![(n.to_string(), v.to_string())]);
+
+    assert_eq!(
+        headers("Prefer", "return=minimal").prefer(),
+        Some(vec![Preference {
+            name: "return".to_string(),
+            value: Some("minimal".to_string()),
+            params: vec![],
+        }])
+    );
+    assert_eq!(
+        headers("Prefer", "wait=100, handling=lenient; scope=\"a, b\"").prefer(),
+        Some(vec![
+            Preference {
+                name: "wait".to_string(),
+                value: Some("100".to_string()),
+                params: vec![],
+            },
+            Preference {
+                name: "handling".to_string(),
+                value: Some("lenient".to_string()),
+                params: vec![("scope".to_string(), "a, b".to_string())],
+            }
+        ])
+    );
+    assert_eq!(
+        headers("Preference-Applied", "respond-async").preference_applied(),
+        Some(vec![Preference {
+            name: "respond-async".to_string(),
+            value: None,
+            params: vec![],
+        }])
+    );
+    assert_eq!(headers("Prefer", "wait=100").preference_applied(), None);
+}
+
+#[test]
+fn test_prefers() {
+    let headers = Headers::from(vec![
+        (
+            "Prefer".to_string(),
+            "return=minimal, WAIT=10, respond-async".to_string(),
+        ),
+        ("Prefer".to_string(), "wait=100".to_string()),
+    ]);
+    assert_eq!(
+        headers
+            .prefer()
+            .unwrap()
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["return", "wait", "respond-async"]
+    );
+    assert!(headers.prefers("respond-async"));
+    assert!(headers.prefers("Return"));
+    assert!(!headers.prefers("handling"));
+    assert_eq!(headers.preference_value("wait"), Some("10".to_string()));
+    assert_eq!(headers.preference_value("respond-async"), None);
+    assert!(!Headers::new().prefers("wait"));
+}
@@ -0,0 +1,203 @@
+use crate::{ows, quoted_string, Headers};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while, take_while1},
+    character::complete::{char, digit1},
+    combinator::{all_consuming, map, opt, recognize},
+    multi::{many0, separated_list0},
+    sequence::{delimited, pair, preceded, tuple},
+    IResult,
+};
+
+/// Priority: https://www.rfc-editor.org/rfc/rfc9218#section-5
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Priority {
+    /// From 0 (most urgent) to 7.
+    pub urgency: u8,
+    pub incremental: bool,
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Priority {
+            urgency: 3,
+            incremental: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PriorityError {
+    /// The value is not a structured field dictionary.
+    Invalid(String),
+    /// The urgency is not an integer from 0 to 7.
+    Urgency(String),
+}
+
+// the bare items of https://www.rfc-editor.org/rfc/rfc8941#section-3.3, kept as
+// their serialization apart from booleans
+#[derive(Debug, PartialEq)]
+enum Item {
+    Boolean(bool),
+    Other(String),
+}
+
+fn key(input: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        take_while1(|chr: char| chr.is_ascii_lowercase() || chr == '*'),
+        take_while(|chr: char| {
+            chr.is_ascii_lowercase() || chr.is_ascii_digit() || "_-.*".contains(chr)
+        }),
+    ))(input)
+}
+
+fn bare_item(input: &str) -> IResult<&str, Item> {
+    alt((
+        map(tag("?1"), |_| Item::Boolean(true)),
+        map(tag("?0"), |_| Item::Boolean(false)),
+        map(
+            recognize(tuple((
+                opt(char('-')),
+                digit1,
+                opt(pair(char('.'), digit1)),
+            ))),
+            |number: &str| Item::Other(number.to_owned()),
+        ),
+        map(quoted_string, Item::Other),
+        map(
+            recognize(delimited(
+                char(':'),
+                take_while(|chr| chr != ':'),
+                char(':'),
+            )),
+            |bytes: &str| Item::Other(bytes.to_owned()),
+        ),
+        map(
+            recognize(pair(
+                take_while1(|chr: char| chr.is_ascii_alphabetic() || chr == '*'),
+                take_while(|chr: char| {
+                    chr.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~:/".contains(chr)
+                }),
+            )),
+            |token: &str| Item::Other(token.to_owned()),
+        ),
+    ))(input)
+}
+
+fn params(input: &str) -> IResult<&str, ()> {
+    map(
+        many0(tuple((
+            char(';'),
+            ows,
+            key,
+            opt(preceded(char('='), bare_item)),
+        ))),
+        |_| (),
+    )(input)
+}
+
+fn inner_list(input: &str) -> IResult<&str, Item> {
+    map(
+        recognize(delimited(
+            char('('),
+            many0(preceded(ows, pair(bare_item, params))),
+            preceded(ows, char(')')),
+        )),
+        |list: &str| Item::Other(list.to_owned()),
+    )(input)
+}
+
+// dict-member = member-key ( parameters / ( "=" member-value ))
+// a member without a value is the boolean true
+fn member(input: &str) -> IResult<&str, (&str, Item)> {
+    tuple((
+        key,
+        opt(preceded(char('='), alt((inner_list, bare_item)))),
+        params,
+    ))(input)
+    .map(|(next_input, (key, item, _))| (next_input, (key, item.unwrap_or(Item::Boolean(true)))))
+}
+
+fn dictionary(input: &str) -> IResult<&str, Vec<(&str, Item)>> {
+    delimited(
+        ows,
+        separated_list0(tuple((ows, char(','), ows)), member),
+        ows,
+    )(input)
+}
+
+impl Headers {
+    /// Priority: https://www.rfc-editor.org/rfc/rfc9218#section-5
+    /// Sent by clients on requests and by servers on responses to override the
+    /// client's priority. Unknown members are ignored, as are `i` values that
+    /// are not booleans. Several field lines are combined into one dictionary,
+    /// where the last occurrence of a member wins.
+    pub fn priority(&self) -> Option<Result<Priority, PriorityError>> {
+        if !self.contains("Priority") {
+            return None;
+        }
+        let value = self.get_all("Priority").collect::<Vec<&str>>().join(",");
+        let members = match all_consuming(dictionary)(&value) {
+            Ok((_, members)) => members,
+            Err(_) => return Some(Err(PriorityError::Invalid(value.clone()))),
+        };
+        let mut priority = Priority::default();
+        for (key, item) in members {
+            match (key, item) {
+                ("u", Item::Other(urgency)) => match urgency.parse() {
+                    Ok(urgency) if urgency <= 7 => priority.urgency = urgency,
+                    _ => return Some(Err(PriorityError::Urgency(urgency))),
+                },
+                ("u", Item::Boolean(boolean)) => {
+                    let boolean = if boolean { "?1" } else { "?0" };
+                    return Some(Err(PriorityError::Urgency(boolean.to_owned())));
+                }
+                ("i", Item::Boolean(incremental)) => priority.incremental = incremental,
+                _ => (),
+            }
+        }
+        Some(Ok(priority))
+    }
+}
+
+#[test]
+fn test_priority() {
+    let priority =
+        |value: &str| Headers::from(vec![("Priority".to_string(), value.to_string())]).priority();
+
+    assert_eq!(
+        priority("u=5, i"),
+        Some(Ok(Priority {
+            urgency: 5,
+            incremental: true,
+        }))
+    );
+    assert_eq!(
+        priority("i=?0, u=0"),
+        Some(Ok(Priority {
+            urgency: 0,
+            incremental: false,
+        }))
+    );
+    assert_eq!(priority(""), Some(Ok(Priority::default())));
+    assert_eq!(Headers::new().priority(), None);
+    assert_eq!(
+        priority("u=8"),
+        Some(Err(PriorityError::Urgency("8".to_string())))
+    );
+    assert_eq!(
+        priority("u=-1"),
+        Some(Err(PriorityError::Urgency("-1".to_string())))
+    );
+    assert_eq!(
+        priority("u=1, x-vendor=\"a, b\";p=1, y=(1 2), i"),
+        Some(Ok(Priority {
+            urgency: 1,
+            incremental: true,
+        }))
+    );
+    assert!(matches!(
+        priority("U=1"),
+        Some(Err(PriorityError::Invalid(_)))
+    ));
+}
@@ -0,0 +1,113 @@
+use crate::streaming::from_body_error;
+use crate::{head_len, parse_head_bytes, BodyReader, ParseError, ParseOptions, Request};
+use std::io::{self, Read};
+
+/// The longest head (request line and headers) `parse_request_from_reader`
+/// buffers before giving up with `ParseError::HeadTooLarge`.
+const MAX_HEAD_LEN: usize = 64 * 1024;
+
+const READ_SIZE: usize = 4096;
+
+// reads once into the buffer, retrying interrupted reads, and returns how many
+// bytes were added, 0 at the end of the input
+fn fill(reader: &mut impl Read, buf: &mut Vec<u8>) -> Result<usize, ParseError> {
+    let mut chunk = [0; READ_SIZE];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(n) => {
+                buf.extend_from_slice(&chunk[..n]);
+                return Ok(n);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(ParseError::Io(e.kind())),
+        }
+    }
+}
+
+/// Parses a request read from a socket or any other reader with the default
+/// options. The head is read up to the empty line, then the body is framed by
+/// Content-Length or the chunked transfer coding like in `parse_http`. Reads
+/// may return any number of bytes, but input read past the end of the body,
+/// such as a pipelined request, is discarded. The body is read right away,
+/// as there is no way to answer `Expect: 100-continue` here, see
+/// `BodyReader::for_request`.
+pub fn parse_request_from_reader<R: Read>(mut reader: R) -> Result<Request, ParseError> {
+    let options = ParseOptions::default();
+    let mut buf = Vec::new();
+    let head_len = loop {
+        // the empty line may start in the bytes that were already searched
+        let start = buf.len().saturating_sub(2);
+        if fill(&mut reader, &mut buf)? == 0 {
+            return Err(ParseError::Incomplete);
+        }
+        if let Some(len) = head_len(&buf[start..]) {
+            break start + len;
+        }
+        if buf.len() > MAX_HEAD_LEN {
+            return Err(ParseError::HeadTooLarge);
+        }
+    };
+    if head_len > MAX_HEAD_LEN {
+        return Err(ParseError::HeadTooLarge);
+    }
+    let mut request = parse_head_bytes(&buf[..head_len], &options)?;
+    let mut body = BodyReader::new(&request.headers, &options)?;
+    buf.drain(..head_len);
+    while !body.is_done() {
+        if buf.is_empty() && fill(&mut reader, &mut buf)? == 0 {
+            return Err(ParseError::Incomplete);
+        }
+        let progress = body.push(&buf).map_err(from_body_error)?;
+        request.body.extend(progress.data);
+        buf.clear();
+    }
+    Ok(request)
+}
+
+#[test]
+fn test_parse_request_from_reader() {
+    use std::io::Cursor;
+
+    // returns a single byte per read
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((first, rest)) if !buf.is_empty() => {
+                    buf[0] = *first;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    let input = b"POST /upload HTTP/1.1\r\nHost: example.org\r\nContent-Length: 5\r\n\r\nhelloGET";
+    let request = parse_request_from_reader(Cursor::new(&input[..])).unwrap();
+    assert_eq!(request.request_line.uri.path, Some("/upload".to_string()));
+    assert_eq!(request.body, b"hello".to_vec());
+    assert_eq!(parse_request_from_reader(Trickle(input)), Ok(request));
+
+    let chunked = b"POST / HTTP/1.1\nTransfer-Encoding: chunked\n\n3\r\nabc\r\n0\r\n\r\n";
+    let request = parse_request_from_reader(Trickle(chunked)).unwrap();
+    assert_eq!(request.body, b"abc".to_vec());
+
+    assert_eq!(
+        parse_request_from_reader(Cursor::new(&input[..input.len() - 6])),
+        Err(ParseError::Incomplete)
+    );
+    assert_eq!(
+        parse_request_from_reader(Cursor::new(&b"GET / HTTP/1.1\r\nHost: a"[..])),
+        Err(ParseError::Incomplete)
+    );
+    let huge = format!(
+        "GET / HTTP/1.1\r\nX-Big: {}\r\n\r\n",
+        "a".repeat(MAX_HEAD_LEN)
+    );
+    assert_eq!(
+        parse_request_from_reader(huge.as_bytes()),
+        Err(ParseError::HeadTooLarge)
+    );
+}
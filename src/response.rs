@@ -0,0 +1,137 @@
+use crate::content::validate_content_length;
+use crate::{
+    check_body_len, end_of_headers, headers, line_ending, message_body, version, GrammarError,
+    Headers, ParseError, ParseOptions, Version,
+};
+use nom::{
+    bytes::complete::{tag, take_while, take_while_m_n},
+    combinator::map,
+    error::context,
+    sequence::{terminated, tuple},
+    IResult,
+};
+
+/// status-line: https://tools.ietf.org/html/rfc7230#section-3.1.2
+#[derive(Debug, PartialEq, Eq)]
+pub struct StatusLine {
+    pub version: Version,
+    pub status: u16,
+    pub reason: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Response {
+    pub status_line: StatusLine,
+    pub headers: Headers,
+    pub body: Vec<u8>,
+    /// The trailer section of a chunked body.
+    pub trailers: Headers,
+}
+
+pub(crate) fn status_line<'a, E: GrammarError<'a>>(
+    input: &'a str,
+) -> IResult<&'a str, StatusLine, E> {
+    context(
+        "status line",
+        tuple((
+            terminated(version, tag(" ")),
+            terminated(
+                // three digits always fit
+                map(
+                    take_while_m_n(3, 3, |chr: char| chr.is_ascii_digit()),
+                    |digits: &str| digits.parse().unwrap_or_default(),
+                ),
+                tag(" "),
+            ),
+            terminated(take_while(|chr| chr != '\r' && chr != '\n'), line_ending),
+        )),
+    )(input)
+    .map(|(next_input, res)| {
+        (
+            next_input,
+            StatusLine {
+                version: res.0,
+                status: res.1,
+                reason: res.2.to_owned(),
+            },
+        )
+    })
+}
+
+/// Parses a response. The body is framed like the one of a request, see
+/// `parse_http`. Without Content-Length and Transfer-Encoding, the body of
+/// a response is delimited by the server closing the connection:
+/// https://tools.ietf.org/html/rfc7230#section-3.3.3 item 7. With
+/// `ParseOptions::read_body_to_end`, `input` is taken to be everything received
+/// until then, so all of the remaining input becomes the body.
+pub fn parse_response(input: &str, options: &ParseOptions) -> Result<Response, ParseError> {
+    let (rest, (status_line, headers, _)) = tuple((status_line, headers, end_of_headers))(input)
+        .map_err(|e| ParseError::from_head(input, e))?;
+    validate_content_length(&headers, options)?;
+    let close_delimited =
+        !headers.contains("Content-Length") && !headers.contains("Transfer-Encoding");
+    let (body, trailers) = if !close_delimited {
+        check_body_len(rest, &headers, options)?;
+        message_body(rest, &headers, options)
+            .map(|(_, res)| res)
+            .map_err(|e| ParseError::from_body(input, &headers, e))?
+    } else if options.read_body_to_end {
+        if let Some(limit) = options
+            .max_body_len
+            .filter(|&limit| rest.len() as u64 > limit)
+        {
+            return Err(ParseError::BodyTooLarge {
+                limit,
+                len: rest.len() as u64,
+            });
+        }
+        (rest.as_bytes().to_vec(), Headers::new())
+    } else {
+        (Vec::new(), Headers::new())
+    };
+    Ok(Response {
+        status_line,
+        headers,
+        body,
+        trailers,
+    })
+}
+
+#[test]
+fn test_status_line() {
+    assert_eq!(
+        status_line::<nom::error::Error<&str>>("HTTP/1.1 404 Not Found\r\n"),
+        Ok((
+            "",
+            StatusLine {
+                version: Version::HTTP11,
+                status: 404,
+                reason: "Not Found".to_string(),
+            }
+        ))
+    );
+    assert_eq!(
+        status_line::<nom::error::Error<&str>>("HTTP/1.0 204 \r\n").map(|(_, line)| line.reason),
+        Ok("".to_string())
+    );
+    assert!(status_line::<nom::error::Error<&str>>("HTTP/1.1 20 OK\r\n").is_err());
+}
+
+#[test]
+fn test_read_body_to_end() {
+    let input = "HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\n\r\nuntil the\r\nconnection closes";
+    let options = ParseOptions {
+        read_body_to_end: true,
+        ..ParseOptions::default()
+    };
+    let response = parse_response(input, &options).unwrap();
+    assert_eq!(response.status_line.status, 200);
+    assert_eq!(response.body, b"until the\r\nconnection closes".to_vec());
+
+    let response = parse_response(input, &ParseOptions::default()).unwrap();
+    assert!(response.body.is_empty());
+
+    let chunked = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nok\r\n0\r\n\r\n";
+    let response = parse_response(chunked, &options).unwrap();
+    assert_eq!(response.body, b"ok".to_vec());
+}
@@ -0,0 +1,166 @@
+use crate::{
+    head_len, parse_head_bytes, request_line_in, request_line_utf8, BodyError, BodyReader,
+    ParseError, ParseMode, ParseOptions, Request, RequestLine,
+};
+use std::convert::TryFrom;
+
+/// The result of parsing input that may have been received only in part, for
+/// example from a socket. A truncated but so far valid message is
+/// `Incomplete`, only input that can never become valid is an error.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseOutcome<T> {
+    /// The parsed value and how many bytes of the input it took up.
+    Complete(T, usize),
+    /// More input is needed. `needed` is the number of missing bytes when it is
+    /// known, like for the rest of a body framed by Content-Length.
+    Incomplete { needed: Option<usize> },
+}
+
+impl<T> ParseOutcome<T> {
+    pub fn is_complete(&self) -> bool {
+        matches!(self, ParseOutcome::Complete(..))
+    }
+}
+
+pub(crate) fn from_body_error(e: BodyError) -> ParseError {
+    match e {
+        BodyError::TooLarge { limit, len } => ParseError::BodyTooLarge { limit, len },
+        e => ParseError::Body(e),
+    }
+}
+
+/// Parses the request line once its line ending was received, in the default
+/// `ParseMode`.
+pub fn parse_request_line_partial(input: &[u8]) -> Result<ParseOutcome<RequestLine>, ParseError> {
+    request_line_partial(input, ParseMode::default())
+}
+
+fn request_line_partial(
+    input: &[u8],
+    mode: ParseMode,
+) -> Result<ParseOutcome<RequestLine>, ParseError> {
+    let len = match input.iter().position(|&b| b == b'\n') {
+        Some(i) => i + 1,
+        None => return Ok(ParseOutcome::Incomplete { needed: None }),
+    };
+    let line = &input[..len];
+    if line.contains(&0) {
+        return Err(ParseError::NulByte);
+    }
+    let line = request_line_utf8(line)?;
+    request_line_in(line, mode)
+        .map(|(_, res)| ParseOutcome::Complete(res, len))
+        .map_err(|e| ParseError::from_head(line, e))
+}
+
+/// Parses the request line and headers once the empty line after them was
+/// received, like `parse_request_head`. A malformed request line is reported
+/// as soon as it is complete.
+pub fn parse_head_partial(
+    input: &[u8],
+    options: &ParseOptions,
+) -> Result<ParseOutcome<Request>, ParseError> {
+    match head_len(input) {
+        Some(len) => {
+            parse_head_bytes(&input[..len], options).map(|head| ParseOutcome::Complete(head, len))
+        }
+        None => request_line_partial(input, options.mode)
+            .map(|_| ParseOutcome::Incomplete { needed: None }),
+    }
+}
+
+/// Parses a request that may have been received only in part, with the body
+/// framed like in `parse_http`. Returns `Incomplete` until the whole body is
+/// there, so a server can keep buffering and call it again with more input.
+/// The trailer section of a chunked body is skipped, see `BodyReader`.
+pub fn parse_request_partial(
+    input: &[u8],
+    options: &ParseOptions,
+) -> Result<ParseOutcome<Request>, ParseError> {
+    let (mut request, head_len) = match parse_head_partial(input, options)? {
+        ParseOutcome::Complete(request, len) => (request, len),
+        ParseOutcome::Incomplete { needed } => return Ok(ParseOutcome::Incomplete { needed }),
+    };
+    let mut body = BodyReader::new(&request.headers, options)?;
+    let progress = body.push(&input[head_len..]).map_err(from_body_error)?;
+    if !progress.done {
+        let needed = match request.headers.content_length() {
+            Some(Ok(len)) if !request.headers.is_chunked() => usize::try_from(len)
+                .ok()
+                .map(|len| len - progress.data.len()),
+            _ => None,
+        };
+        return Ok(ParseOutcome::Incomplete { needed });
+    }
+    request.body = progress.data;
+    Ok(ParseOutcome::Complete(
+        request,
+        head_len + progress.consumed,
+    ))
+}
+
+#[test]
+fn test_parse_request_partial() {
+    let options = ParseOptions::default();
+    let inputs: [&[u8]; 3] = [
+        b"POST /upload HTTP/1.1\r\nHost: example.org\r\nContent-Length: 5\r\n\r\nhello",
+        b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\nX: y\r\n\r\n",
+        b"GET /a?b=c HTTP/1.0\n\n",
+    ];
+    for input in inputs.iter() {
+        for end in 0..input.len() {
+            assert!(
+                !parse_request_partial(&input[..end], &options)
+                    .unwrap()
+                    .is_complete(),
+                "{:?}",
+                String::from_utf8_lossy(&input[..end])
+            );
+        }
+        let request = crate::parse_http(std::str::from_utf8(input).unwrap())
+            .unwrap()
+            .1;
+        match parse_request_partial(input, &options) {
+            Ok(ParseOutcome::Complete(partial, len)) => {
+                assert_eq!(len, input.len());
+                assert_eq!(partial.request_line, request.request_line);
+                assert_eq!(partial.body, request.body);
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    let input = b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhelloGET";
+    assert_eq!(
+        parse_request_partial(&input[..40], &options),
+        Ok(ParseOutcome::Incomplete { needed: Some(3) })
+    );
+    assert!(matches!(
+        parse_request_partial(input, &options),
+        Ok(ParseOutcome::Complete(_, 43))
+    ));
+}
+
+#[test]
+fn test_malformed_partial() {
+    let options = ParseOptions::default();
+    assert!(matches!(
+        parse_request_partial(b"GET / HTTP/2.0\r\nHost: exa", &options),
+        Err(ParseError::InvalidVersion { .. })
+    ));
+    assert_eq!(
+        parse_request_partial(b"GET / HTTP/2.0", &options),
+        Ok(ParseOutcome::Incomplete { needed: None })
+    );
+    assert!(matches!(
+        parse_request_partial(b"GET / HTTP/1.1\r\nBad Header\r\n\r\n", &options),
+        Err(ParseError::InvalidHeader { .. })
+    ));
+    assert!(matches!(
+        parse_request_partial(
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nzz",
+            &options
+        ),
+        Err(ParseError::Body(BodyError::InvalidChunk(b'z')))
+    ));
+}
@@ -0,0 +1,169 @@
+use crate::chunked::validate_transfer_encoding;
+use crate::content::validate_content_length;
+use crate::{HostError, Method, ParseError, ParseOptions, Request, TargetForm, Version};
+
+/// A violation found by `Request::validate`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// An HTTP/1.1 request without a Host header: https://tools.ietf.org/html/rfc7230#section-5.4
+    MissingHost,
+    /// More than one Host header.
+    DuplicateHost,
+    /// The Host header is malformed or names another authority than the target.
+    Host(HostError),
+    /// The target form is not allowed with the method: authority-form is only
+    /// used by CONNECT, which requires it, and asterisk-form only by OPTIONS:
+    /// https://tools.ietf.org/html/rfc7230#section-5.3
+    TargetForm { method: Method, form: TargetForm },
+    /// Content-Length is malformed or given more than once, see
+    /// `ParseError::DuplicateContentLength`.
+    ContentLength(ParseError),
+    /// Both Content-Length and Transfer-Encoding are present:
+    /// https://tools.ietf.org/html/rfc7230#section-3.3.2
+    ContentLengthWithTransferEncoding,
+    /// The chunked coding is not the final one or applied more than once.
+    InvalidTransferEncoding,
+    /// The body is not as long as Content-Length announces.
+    BodyLengthMismatch { announced: u64, len: u64 },
+    /// The body is longer than `ParseOptions::max_body_len`.
+    BodyTooLarge { limit: u64, len: u64 },
+    /// A TRACE request has a body: https://tools.ietf.org/html/rfc7231#section-4.3.8
+    TraceBody,
+}
+
+impl Request {
+    /// Checks a parsed request against the rules of the protocol and the limits
+    /// of `options`, collecting every violation instead of stopping at the
+    /// first. Requests parsed leniently can be validated like this before they
+    /// are acted upon.
+    pub fn validate(&self, options: &ParseOptions) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let method = &self.request_line.method;
+
+        match self.headers.get_all("Host").count() {
+            0 if self.request_line.version == Version::HTTP11 => {
+                errors.push(ValidationError::MissingHost)
+            }
+            0 | 1 => {}
+            _ => errors.push(ValidationError::DuplicateHost),
+        }
+        if let Err(e) = self.validate_host() {
+            errors.push(ValidationError::Host(e));
+        }
+
+        let form = self.request_line.target_form();
+        let form_allowed = match form {
+            TargetForm::Authority => *method == Method::CONNECT,
+            TargetForm::Asterisk => *method == Method::OPTIONS,
+            _ => *method != Method::CONNECT,
+        };
+        if !form_allowed {
+            errors.push(ValidationError::TargetForm {
+                method: method.clone(),
+                form,
+            });
+        }
+
+        let len = self.body.len() as u64;
+        if let Err(e) = validate_content_length(&self.headers, options) {
+            errors.push(ValidationError::ContentLength(e));
+        }
+        if self.headers.contains("Content-Length") && self.headers.contains("Transfer-Encoding") {
+            errors.push(ValidationError::ContentLengthWithTransferEncoding);
+        }
+        if validate_transfer_encoding(&self.headers).is_err() {
+            errors.push(ValidationError::InvalidTransferEncoding);
+        }
+        if let (Some(Ok(announced)), false) =
+            (self.headers.content_length(), self.headers.is_chunked())
+        {
+            if announced != len {
+                errors.push(ValidationError::BodyLengthMismatch { announced, len });
+            }
+        }
+        if let Some(limit) = options.max_body_len.filter(|&limit| len > limit) {
+            errors.push(ValidationError::BodyTooLarge { limit, len });
+        }
+
+        let announces_body = self.headers.is_chunked()
+            || matches!(self.headers.content_length(), Some(Ok(len)) if len > 0);
+        if *method == Method::TRACE && (len > 0 || announces_body) {
+            errors.push(ValidationError::TraceBody);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[test]
+fn test_validate() {
+    let options = ParseOptions::default();
+    let request = |input: &str| crate::parse_http(input).unwrap().1;
+
+    let valid = request("POST /items HTTP/1.1\r\nHost: example.org\r\nContent-Length: 2\r\n\r\nok");
+    assert_eq!(valid.validate(&options), Ok(()));
+    assert_eq!(
+        request("OPTIONS * HTTP/1.1\r\nHost: example.org\r\n\r\n").validate(&options),
+        Ok(())
+    );
+    assert_eq!(
+        request("CONNECT example.org:443 HTTP/1.1\r\nHost: example.org:443\r\n\r\n")
+            .validate(&options),
+        Ok(())
+    );
+
+    let trace = request("TRACE * HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc");
+    assert_eq!(
+        trace.validate(&options),
+        Err(vec![
+            ValidationError::MissingHost,
+            ValidationError::TargetForm {
+                method: Method::TRACE,
+                form: TargetForm::Asterisk
+            },
+            ValidationError::TraceBody,
+        ])
+    );
+
+    let mut mismatch =
+        request("GET http://example.org/ HTTP/1.1\r\nHost: other.org\r\nHost: example.org\r\n\r\n");
+    mismatch.headers.append(
+        crate::StandardHeader::ContentLength.into(),
+        crate::HeaderValue::new("5").unwrap(),
+    );
+    mismatch.headers.append(
+        crate::StandardHeader::TransferEncoding.into(),
+        crate::HeaderValue::new("chunked").unwrap(),
+    );
+    assert_eq!(
+        mismatch.validate(&ParseOptions {
+            max_body_len: Some(0),
+            ..ParseOptions::default()
+        }),
+        Err(vec![
+            ValidationError::DuplicateHost,
+            ValidationError::Host(HostError::Mismatch),
+            ValidationError::ContentLengthWithTransferEncoding,
+        ])
+    );
+
+    let mut long = valid;
+    long.body = b"too long".to_vec();
+    assert_eq!(
+        long.validate(&ParseOptions {
+            max_body_len: Some(4),
+            ..ParseOptions::default()
+        }),
+        Err(vec![
+            ValidationError::BodyLengthMismatch {
+                announced: 2,
+                len: 8
+            },
+            ValidationError::BodyTooLarge { limit: 4, len: 8 },
+        ])
+    );
+}
@@ -0,0 +1,125 @@
+use crate::{message_body, request_head, ParseMode, ParseOptions, Request};
+use nom::{
+    error::{convert_error, VerboseError, VerboseErrorKind},
+    Err as NomErr, IResult,
+};
+
+// runs one of the crate's parsers that is not generic over the error type, like
+// the one of the body, and names the rule that failed, keeping the error kind of
+// the failure itself
+fn labeled<'a, O, F>(
+    label: &'static str,
+    mut parser: F,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O, VerboseError<&'a str>>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    move |input| {
+        parser(input).map_err(|err| {
+            err.map(|e| VerboseError {
+                errors: vec![
+                    (e.input, VerboseErrorKind::Nom(e.code)),
+                    (input, VerboseErrorKind::Context(label)),
+                ],
+            })
+        })
+    }
+}
+
+/// Parses a request like `parse_http`, with the same grammar, but a failure
+/// carries the chain of grammar rules that were attempted, for `explain_error`.
+pub fn parse_http_verbose(input: &str) -> IResult<&str, Request, VerboseError<&str>> {
+    let (input, mut request) = request_head(input, ParseMode::default())?;
+    let (input, (body, trailers)) = labeled("body", |input| {
+        message_body(input, &request.headers, &ParseOptions::default())
+    })(input)?;
+    request.body = body;
+    request.trailers = trailers;
+    Ok((input, request))
+}
+
+/// Describes a failure of `parse_http_verbose` for humans: each rule that
+/// failed with its line of the input and a caret at the position.
+pub fn explain_error(input: &str, err: NomErr<VerboseError<&str>>) -> String {
+    match err {
+        NomErr::Incomplete(_) => "incomplete input".to_owned(),
+        NomErr::Error(e) | NomErr::Failure(e) => convert_error(input, e),
+    }
+}
+
+#[test]
+fn test_explain_error() {
+    let input = "GET / HTTP/1.1\r\nHost: example.org\r\nBad Header\r\n\r\n";
+    let err = parse_http_verbose(input).map(|_| ()).unwrap_err();
+    let explanation = explain_error(input, err);
+    assert!(explanation.contains("in header colon"));
+    assert!(explanation.contains("in header:"));
+    assert!(explanation.contains("Bad Header"));
+    assert!(!explanation.contains("request line"));
+
+    let input = "GET / HTTP/2.0\r\n\r\n";
+    let explanation = explain_error(input, parse_http_verbose(input).map(|_| ()).unwrap_err());
+    assert!(explanation.contains("in version"));
+    assert!(explanation.contains("in request line"));
+
+    let input = "GET  /a  HTTP/1.1\r\n\r\n";
+    assert!(parse_http_verbose(input).is_ok());
+
+    let input = "GET /a HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello";
+    let (_, request) = parse_http_verbose(input).unwrap();
+    assert_eq!(request, crate::parse_http(input).unwrap().1);
+}
+
+#[cfg(test)]
+fn contexts(input: &str) -> Vec<&'static str> {
+    match parse_http_verbose(input) {
+        Err(NomErr::Error(e)) | Err(NomErr::Failure(e)) => e
+            .errors
+            .into_iter()
+            .filter_map(|(_, kind)| match kind {
+                VerboseErrorKind::Context(label) => Some(label),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[test]
+fn test_context_labels() {
+    assert_eq!(
+        contexts("GET http://example.org:x/ HTTP/1.1\r\n\r\n"),
+        vec!["port", "target", "request line"]
+    );
+    assert_eq!(
+        contexts("GET http://[::g]/ HTTP/1.1\r\n\r\n"),
+        vec!["ip", "target", "request line"]
+    );
+    assert_eq!(
+        contexts("GET /a#? HTTP/1.1\r\n\r\n"),
+        vec!["fragment", "target", "request line"]
+    );
+    assert_eq!(
+        contexts("GET / HTTP/1.1\r\n: value\r\n\r\n"),
+        vec!["header name", "header"]
+    );
+    assert_eq!(
+        contexts("GET / HTTP/3\r\n\r\n"),
+        vec!["version", "request line"]
+    );
+
+    for input in &[
+        "GET http://user:pw@example.org:8080/a/b?x=1#top HTTP/1.1\r\n\r\n",
+        "GET http://[::1]/ HTTP/1.1\r\n\r\n",
+        "OPTIONS * HTTP/1.1\r\n\r\n",
+        "CONNECT example.org:443 HTTP/1.1\r\n\r\n",
+        "GET  /  HTTP/1.1\r\n\r\n",
+        "GET http://example.org:99999/ HTTP/1.1\r\n\r\n",
+        "GET /a|b HTTP/1.1\r\n\r\n",
+    ] {
+        assert_eq!(
+            parse_http_verbose(input).map(|(_, request)| request).ok(),
+            crate::parse_http(input).map(|(_, request)| request).ok()
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use crate::base64;
+use crate::{list, parameters, token, Headers, Parameters};
+use nom::{combinator::all_consuming, sequence::pair, IResult};
+
+/// The GUID appended to Sec-WebSocket-Key: https://tools.ietf.org/html/rfc6455#section-1.3
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum WebSocketKeyError {
+    /// The key is not base64 or does not decode to 16 bytes.
+    Invalid(String),
+}
+
+/// extension: https://tools.ietf.org/html/rfc6455#section-9.1
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WebSocketExtension {
+    pub name: String,
+    pub params: Parameters,
+}
+
+fn extension(input: &str) -> IResult<&str, WebSocketExtension> {
+    pair(token, parameters)(input).map(|(next_input, res)| {
+        (
+            next_input,
+            WebSocketExtension {
+                name: res.0.to_owned(),
+                params: res.1,
+            },
+        )
+    })
+}
+
+impl Headers {
+    /// Sec-WebSocket-Key: https://tools.ietf.org/html/rfc6455#section-11.3.1
+    /// The key is returned as sent, after checking it is the base64 of 16 bytes.
+    pub fn sec_websocket_key(&self) -> Option<Result<&str, WebSocketKeyError>> {
+        self.get("Sec-WebSocket-Key").map(|value| {
+            let key = value.trim();
+            match base64::decode(key) {
+                Some(decoded) if decoded.len() == 16 => Ok(key),
+                _ => Err(WebSocketKeyError::Invalid(value.to_owned())),
+            }
+        })
+    }
+
+    /// Sec-WebSocket-Version: https://tools.ietf.org/html/rfc6455#section-11.3.5
+    /// A single version on requests, a list of supported ones on a 426 response.
+    /// Versions that are not a number from 0 to 255 are skipped.
+    pub fn sec_websocket_version(&self) -> Option<Vec<u8>> {
+        self.names_list("Sec-WebSocket-Version").map(|versions| {
+            versions
+                .iter()
+                .filter_map(|version| version.parse().ok())
+                .collect()
+        })
+    }
+
+    /// Sec-WebSocket-Protocol: https://tools.ietf.org/html/rfc6455#section-11.3.4
+    pub fn sec_websocket_protocol(&self) -> Option<Vec<String>> {
+        if !self.contains("Sec-WebSocket-Protocol") {
+            return None;
+        }
+        Some(
+            self.get_all("Sec-WebSocket-Protocol")
+                .filter_map(|value| all_consuming(list(token))(value).ok())
+                .flat_map(|(_, res)| res)
+                .map(|protocol| protocol.to_owned())
+                .collect(),
+        )
+    }
+
+    /// The first subprotocol offered by the client that the server supports as
+    /// well, to be echoed in the Sec-WebSocket-Protocol of the response.
+    pub fn select_websocket_protocol(&self, supported: &[&str]) -> Option<String> {
+        self.sec_websocket_protocol()?
+            .into_iter()
+            .find(|protocol| supported.contains(&protocol.as_str()))
+    }
+
+    /// Sec-WebSocket-Extensions: https://tools.ietf.org/html/rfc6455#section-11.3.2
+    /// Field lines that fail to parse are ignored.
+    pub fn sec_websocket_extensions(&self) -> Option<Vec<WebSocketExtension>> {
+        if !self.contains("Sec-WebSocket-Extensions") {
+            return None;
+        }
+        Some(
+            self.get_all("Sec-WebSocket-Extensions")
+                .filter_map(|value| all_consuming(list(extension))(value).ok())
+                .flat_map(|(_, res)| res)
+                .collect(),
+        )
+    }
+}
+
+/// The Sec-WebSocket-Accept value answering a Sec-WebSocket-Key:
+/// https://tools.ietf.org/html/rfc6455#section-4.2.2
+pub fn websocket_accept(key: &str) -> String {
+    base64::encode(&sha1(
+        format!("{}{}", key.trim(), WEBSOCKET_GUID).as_bytes(),
+    ))
+}
+
+/// SHA-1 as specified in https://tools.ietf.org/html/rfc3174, only used for the
+/// WebSocket handshake where it is not relied on for security.
+fn sha1(input: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xEFCD_AB89,
+        0x98BA_DCFE,
+        0x1032_5476,
+        0xC3D2_E1F0,
+    ];
+
+    let mut message = input.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(input.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e].iter()) {
+            *h = h.wrapping_add(*v);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (chunk, h) in digest.chunks_mut(4).zip(h.iter()) {
+        chunk.copy_from_slice(&h.to_be_bytes());
+    }
+    digest
+}
+
+#[test]
+fn test_websocket_accept() {
+    let headers = Headers::from(vec![
+        (
+            "Sec-WebSocket-Key".to_string(),
+            "dGhlIHNhbXBsZSBub25jZQ==".to_string(),
+        ),
+        ("Sec-WebSocket-Version".to_string(), "13".to_string()),
+    ]);
+    let key = headers.sec_websocket_key().unwrap().unwrap();
+    assert_eq!(websocket_accept(key), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    assert_eq!(headers.sec_websocket_version(), Some(vec![13]));
+
+    let headers = Headers::from(vec![(
+        "Sec-WebSocket-Key".to_string(),
+        "c2hvcnQ=".to_string(),
+    )]);
+    assert_eq!(
+        headers.sec_websocket_key(),
+        Some(Err(WebSocketKeyError::Invalid("c2hvcnQ=".to_string())))
+    );
+}
+
+#[test]
+fn test_websocket_negotiation() {
+    let headers = Headers::from(vec![
+        (
+            "Sec-WebSocket-Protocol".to_string(),
+            "chat, superchat".to_string(),
+        ),
+        (
+            "Sec-WebSocket-Extensions".to_string(),
+            "permessage-deflate; client_max_window_bits, x-webkit-deflate-frame".to_string(),
+        ),
+        ("Sec-WebSocket-Version".to_string(), "13, 8, 7".to_string()),
+    ]);
+    assert_eq!(
+        headers.sec_websocket_protocol(),
+        Some(vec!["chat".to_string(), "superchat".to_string()])
+    );
+    assert_eq!(
+        headers.select_websocket_protocol(&["superchat", "chat"]),
+        Some("chat".to_string())
+    );
+    assert_eq!(headers.select_websocket_protocol(&["mqtt"]), None);
+    assert_eq!(
+        headers.sec_websocket_extensions(),
+        Some(vec![
+            WebSocketExtension {
+                name: "permessage-deflate".to_string(),
+                params: vec![("client_max_window_bits".to_string(), "".to_string())],
+            },
+            WebSocketExtension {
+                name: "x-webkit-deflate-frame".to_string(),
+                params: vec![],
+            },
+        ])
+    );
+    assert_eq!(headers.sec_websocket_version(), Some(vec![13, 8, 7]));
+}
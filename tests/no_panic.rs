@@ -0,0 +1,385 @@
+use rust_parser_example::parse::{self, FromHeaderValue};
+use rust_parser_example::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const VALID: [&str; 5] = [
+    "POST http://user:pw@example.org:8080/a/b?x=1&y[]=2#top HTTP/1.1\r\nHost: example.org:8080\r\nContent-Type: multipart/form-data; boundary=xyz\r\nContent-Length: 5\r\nCookie: a=b; c=d\r\n\r\nhello",
+    "PUT /upload HTTP/1.1\r\nTransfer-Encoding: gzip, chunked\r\nTrailer: Expires\r\nExpect: 100-continue\r\n\r\n5;ext=1\r\nhello\r\n0\r\nExpires: Thu, 01 Jan 1970 00:00:00 GMT\r\n\r\n",
+    "GET http://[::ffff:192.168.0.1]:80/ HTTP/1.0\nRange: bytes=0-9\nIf-None-Match: W/\"a\", \"b\"\nAuthorization: Basic dXNlcjpwdw==\n\n",
+    "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-9/100\r\nSet-Cookie: id=1; Max-Age=60; SameSite=Lax\r\nAlt-Svc: h2=\":443\"; ma=60\r\nRetry-After: 120\r\nStrict-Transport-Security: max-age=31536000; includeSubDomains\r\n\r\n",
+    // a multipart body on its own, with the boundary `B`
+    "--B\r\nContent-Disposition: form-data; name=\"a[b][]\"\r\nContent-Range: bytes 0-4/13\r\n\r\nhello\r\n--B--\r\n",
+];
+
+// deterministic garbage, there is no randomness crate to depend on
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+fn corpus() -> Vec<Vec<u8>> {
+    let mut corpus: Vec<Vec<u8>> = Vec::new();
+    for valid in VALID.iter() {
+        // truncated at every byte
+        for end in 0..=valid.len() {
+            corpus.push(valid.as_bytes()[..end].to_vec());
+        }
+        // with single bytes replaced by delimiters, digits and non-ASCII
+        for (i, replacement) in valid.bytes().enumerate().flat_map(|(i, _)| {
+            [b':', b'9', b'\n', b'%', b'[', 0xff, b' ']
+                .iter()
+                .map(move |b| (i, *b))
+        }) {
+            let mut input = valid.as_bytes().to_vec();
+            input[i] = replacement;
+            corpus.push(input);
+        }
+    }
+    let mut state = 0x2545_f491_4f6c_dd1d;
+    for len in 0..512 {
+        corpus.push((0..len).map(|_| xorshift(&mut state) as u8).collect());
+        let printable = (0..len)
+            .map(|_| {
+                b" \r\n\t:;,=/?#[]@%\"\\*-.0123456789abcxyzHTP"[xorshift(&mut state) as usize % 39]
+            })
+            .collect();
+        corpus.push(printable);
+    }
+    let big = "9".repeat(64);
+    let huge = "a".repeat(1 << 20);
+    let deep = "[x]".repeat(200_000);
+    for input in [
+        format!("a{}=1", deep),
+        format!("GET /?a{}=1 HTTP/1.1\r\n\r\n", deep.replace('[', "%5B").replace(']', "%5D")),
+        format!(
+            "POST / HTTP/1.1\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\n\r\na{}=1",
+            deep.len() + 3,
+            deep
+        ),
+        "--B\r\nContent-Range: bytes 0-18446744073709551615/*\r\n\r\nhello\r\n--B--".to_string(),
+        "--B\r\nContent-Range: bytes 18446744073709551615-18446744073709551615/*\r\n\r\nh\r\n--B--".to_string(),
+        "POST / HTTP/1.1\r\nContent-Type: multipart/byteranges; boundary=B\r\nContent-Length: 66\r\n\r\n--B\r\nContent-Range: bytes 0-18446744073709551615/*\r\n\r\nhello\r\n--B--".to_string(),
+        format!("GET /{} HTTP/1.1\r\n\r\n", "a/".repeat(100_000)),
+        format!("GET / HTTP/1.1\r\nX: {}\r\n\r\n", huge),
+        format!("GET / HTTP/1.1\r\n{}: x\r\n\r\n", huge),
+        format!("GET / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", big),
+        format!(
+            "GET / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{}\r\n",
+            big
+        ),
+        "GET / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nffffffffffffffff\r\nx".to_string(),
+        format!("GET http://example.org:{}/ HTTP/1.1\r\n\r\n", big),
+        format!(
+            "GET http://{}.{}.{}.{}/ HTTP/1.1\r\n\r\n",
+            big, big, big, big
+        ),
+        format!("GET http://[{}]/ HTTP/1.1\r\n\r\n", ":".repeat(1000)),
+        format!("GET /?{} HTTP/1.1\r\n\r\n", "a[0]".repeat(10_000)),
+        "GET /\u{1F600}/\u{202E}é HTTP/1.1\r\nHost: é:80\r\n\r\n".to_string(),
+        "GET / HTTP/1.1\r\nHé\u{301}: v\u{FEFF}\r\n \u{1F600}\r\n\r\n".to_string(),
+        "\u{FEFF}GET / HTTP/1.1\r\n\r\n".to_string(),
+        "GET / HTTP/1.1\r\n\r\n\u{0}\u{7F}\u{80}".to_string(),
+        "\r\n".repeat(10_000),
+        " ".repeat(10_000),
+    ]
+    .iter()
+    {
+        corpus.push(input.as_bytes().to_vec());
+    }
+    corpus
+}
+
+fn header_values() -> Vec<String> {
+    let mut values = vec![
+        String::new(),
+        ";".to_string(),
+        ",,,".to_string(),
+        "\"".to_string(),
+        "\"\\".to_string(),
+        "bytes".to_string(),
+        "bytes 9-0/1".to_string(),
+        "bytes */".to_string(),
+        "18446744073709551616".to_string(),
+        "-1".to_string(),
+        "max-age=99999999999999999999".to_string(),
+        "Basic ####".to_string(),
+        "W/".to_string(),
+        "a/b; c".to_string(),
+        "u=9, i".to_string(),
+        "Thu, 31 Feb 2100 25:61:61 GMT".to_string(),
+        "h2=\":99999\"; ma=-1".to_string(),
+        "http://[::1".to_string(),
+        "é\u{1F600};=\u{FEFF}".to_string(),
+        "a".repeat(100_000),
+    ];
+    values.extend(
+        VALID
+            .iter()
+            .flat_map(|v| v.split("\r\n"))
+            .map(str::to_owned),
+    );
+    values
+}
+
+const HEADER_NAMES: [&str; 42] = [
+    "Alt-Svc",
+    "Authorization",
+    "Proxy-Authorization",
+    "WWW-Authenticate",
+    "Proxy-Authenticate",
+    "Age",
+    "Cache-Control",
+    "Pragma",
+    "Warning",
+    "Expires",
+    "Transfer-Encoding",
+    "Trailer",
+    "If-Match",
+    "If-None-Match",
+    "If-Unmodified-Since",
+    "If-Range",
+    "Connection",
+    "Keep-Alive",
+    "Content-Encoding",
+    "Content-Type",
+    "Content-Length",
+    "Content-Disposition",
+    "Content-Range",
+    "Accept-Ranges",
+    "Cookie",
+    "Set-Cookie",
+    "Origin",
+    "Access-Control-Request-Method",
+    "Access-Control-Request-Headers",
+    "Access-Control-Allow-Origin",
+    "Access-Control-Max-Age",
+    "Content-Security-Policy",
+    "Last-Modified",
+    "Retry-After",
+    "Allow",
+    "Host",
+    "Strict-Transport-Security",
+    "Accept-Language",
+    "Prefer",
+    "Priority",
+    "Referer",
+    "Sec-WebSocket-Extensions",
+];
+
+fn use_headers(headers: &Headers) {
+    let _ = (
+        headers.alt_svc(),
+        headers.authorization(),
+        headers.proxy_authorization(),
+        headers.www_authenticate(),
+        headers.proxy_authenticate(),
+        headers.age(),
+        headers.cache_control(),
+        headers.pragma(),
+        headers.no_cache(),
+        headers.warnings(),
+        headers.expires(),
+        headers.transfer_encoding(),
+        headers.is_chunked(),
+        headers.trailer(),
+    );
+    let _ = (
+        headers.if_match(),
+        headers.if_none_match(),
+        headers.if_unmodified_since(),
+        headers.if_range(),
+        headers.connection(),
+        headers.keep_alive(),
+        headers.content_encoding(),
+        headers.is_identity_encoded(),
+        headers.content_type(),
+        headers.content_length(),
+        headers.content_disposition(),
+        headers.content_range(),
+        headers.accept_ranges(),
+        headers.cookies(),
+        headers.set_cookies(),
+    );
+    let _ = (
+        headers.origin(),
+        headers.origins(),
+        headers.access_control_request_method(),
+        headers.access_control_request_headers(),
+        headers.access_control_allow_origin(),
+        headers.access_control_allow_methods(),
+        headers.access_control_allow_headers(),
+        headers.access_control_max_age(),
+        headers.access_control_allow_credentials(),
+        headers.content_security_policy(),
+        headers.last_modified(),
+        headers.retry_after(),
+        headers.allow(),
+        headers.host(),
+    );
+    let _ = (
+        headers.strict_transport_security(),
+        headers.accept_language(),
+        headers.prefer(),
+        headers.preference_applied(),
+        headers.priority(),
+        headers.sec_websocket_key(),
+        headers.sec_websocket_version(),
+        headers.sec_websocket_protocol(),
+        headers.sec_websocket_extensions(),
+        headers.validate_trailers(headers),
+        headers.referer(),
+        headers.referer_origin_only(),
+    );
+}
+
+fn use_request(request: &Request) {
+    use_headers(&request.headers);
+    let uri = &request.request_line.uri;
+    let _ = (
+        request.expects_continue(),
+        request.keep_alive(),
+        request.content_type(),
+        request.is_cors_preflight(),
+        request.form(),
+        request.validate_host(),
+        request.request_line.target_form(),
+        request.body_str(),
+        request.to_pseudo_headers(),
+        request.validate_trailers(),
+        uri.to_string(),
+        uri.query_string(),
+        uri.query_structured(),
+        uri.path_segments().count(),
+    );
+    let _ = BodyReader::for_request(request, &ParseOptions::default());
+    if let Ok(pairs) = request.form() {
+        let _ = parse_nested(&pairs);
+    }
+    if let Some(Ok(media_type)) = request.content_type() {
+        if let Some(boundary) = media_type.param("boundary") {
+            let _ = parse_multipart(&request.body, boundary);
+            let _ = parse_byteranges(&request.body, boundary);
+        }
+    }
+}
+
+fn use_input(input: &[u8]) {
+    let options = [
+        ParseOptions::default(),
+        ParseOptions::strict(),
+        ParseOptions {
+            skip_malformed_headers: true,
+            read_body_to_end: true,
+            max_body_len: Some(3),
+            max_chunk_extension_len: 1,
+            max_path_segments: 1,
+            allowed_schemes: Some(vec!["https".to_string()]),
+            ..ParseOptions::default()
+        },
+    ];
+    for options in options.iter() {
+        if let Ok(request) = parse_request_bytes(input, options) {
+            use_request(&request);
+        }
+        let _ = parse_request_partial(input, options);
+        let _ = parse_head_partial(input, options);
+    }
+    let _ = parse_request_line_partial(input);
+    let _ = parse_request_from_reader(input);
+    let mut reader = BodyReader::new(&Headers::new(), &ParseOptions::default()).ok();
+    if let Some(reader) = reader.as_mut() {
+        let _ = reader.push(input);
+    }
+
+    // the body parsers, fed directly rather than only from parsed requests
+    let _ = (parse_multipart(input, "B"), parse_byteranges(input, "B"));
+
+    let input = String::from_utf8_lossy(input);
+    let input = input.as_ref();
+    if let Ok(pairs) = parse_form_body(input) {
+        let _ = parse_nested(&pairs);
+    }
+    for options in options.iter() {
+        if let Ok(request) = parse_request(input, options) {
+            use_request(&request);
+        }
+        let _ = parse_request_head(input, options);
+        let _ = parse_request_with_warnings(input, options);
+        if let Ok(response) = parse_response(input, options) {
+            use_headers(&response.headers);
+        }
+    }
+    if let Ok((_, request)) = parse_http(input) {
+        use_request(&request);
+    }
+    if let Err(e) = parse_http_verbose(input) {
+        let _ = explain_error(input, e);
+    }
+    let _ = (parse::request(input), parse::response(input));
+    if let Ok(uri) = parse::uri(input) {
+        let _ = uri.resolve(&uri);
+        let _ = uri.to_string();
+    }
+    let _ = (
+        parse::status_line(input),
+        parse::chunked_body(input),
+        parse::chunks(input, &ParseOptions::strict()),
+        parse::headers(input),
+        parse::header_slices(input),
+        parse::headers_map(input),
+        parse::headers_skipping_malformed(input),
+        parse_form_body(input),
+        percent::decode(input),
+        parse_set_cookie(input),
+        parse_http_date(input),
+    );
+}
+
+fn use_header_value(value: &str) {
+    for name in HEADER_NAMES.iter() {
+        let headers = Headers::from(vec![(name.to_string(), value.to_string())]);
+        use_headers(&headers);
+    }
+    let _ = (
+        MediaType::from_header_value(value),
+        ContentDisposition::from_header_value(value),
+        ContentRange::from_header_value(value),
+        EntityTag::from_header_value(value),
+        Credentials::from_header_value(value),
+        Origin::from_header_value(value),
+        AllowOrigin::from_header_value(value),
+        AltSvc::from_header_value(value),
+        Method::from_header_value(value),
+    );
+}
+
+fn panics<F: FnOnce()>(f: F) -> bool {
+    catch_unwind(AssertUnwindSafe(f)).is_err()
+}
+
+#[test]
+fn test_no_panic_on_requests() {
+    let panicking: Vec<String> = corpus()
+        .iter()
+        .filter(|input| panics(|| use_input(input)))
+        .map(|input| String::from_utf8_lossy(&input[..input.len().min(80)]).into_owned())
+        .collect();
+    assert!(panicking.is_empty(), "{:#?}", panicking);
+}
+
+#[test]
+fn test_no_panic_on_header_values() {
+    let panicking: Vec<String> = header_values()
+        .into_iter()
+        .chain(
+            corpus()
+                .iter()
+                .map(|input| String::from_utf8_lossy(input).into_owned()),
+        )
+        .filter(|value| value.len() < 2048 || value.bytes().all(|b| b == b'a'))
+        .filter(|value| panics(|| use_header_value(value)))
+        .collect();
+    assert!(panicking.is_empty(), "{:#?}", panicking);
+}
@@ -0,0 +1,97 @@
+use rust_parser_example::parse::{self, FromHeaderValue};
+use rust_parser_example::{
+    ContentRange, EntityTag, Host, MediaType, Method, Origin, ParseError, Scheme,
+};
+
+#[test]
+fn test_request() {
+    let request = parse::request(
+        "POST /items?page=2 HTTP/1.1\r\nHost: example.org\r\nContent-Length: 2\r\n\r\nok",
+    )
+    .unwrap();
+    assert_eq!(request.request_line.method, Method::POST);
+    assert_eq!(request.request_line.uri.path, Some("/items".to_string()));
+    assert_eq!(request.headers.get("host"), Some("example.org"));
+    assert_eq!(request.body, b"ok".to_vec());
+
+    let err = parse::request("GET / HTTP/1.1\r\nBad Header\r\n\r\n").unwrap_err();
+    assert!(matches!(err, ParseError::InvalidHeader { .. }));
+    assert_eq!(err.line(), Some(2));
+    assert_eq!(
+        parse::request("GET / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhel"),
+        Err(ParseError::Incomplete)
+    );
+}
+
+#[test]
+fn test_response() {
+    let response =
+        parse::response("HTTP/1.1 404 Not Found\r\nContent-Length: 4\r\n\r\ngone").unwrap();
+    assert_eq!(response.status_line.status, 404);
+    assert_eq!(response.status_line.reason, "Not Found");
+    assert_eq!(response.body, b"gone".to_vec());
+    assert!(matches!(
+        parse::response("HTTP/1.1 20 OK\r\n\r\n"),
+        Err(ParseError::InvalidStatusLine { .. })
+    ));
+}
+
+#[test]
+fn test_uri() {
+    let uri = parse::uri("https://example.org:8443/a/b?x=1#top").unwrap();
+    assert_eq!(uri.scheme, Some(Scheme::HTTPS));
+    assert_eq!(uri.host, Some(Host::HOST("example.org".to_string())));
+    assert_eq!(uri.port, Some(8443));
+    assert_eq!(uri.query, Some(vec![("x".to_string(), "1".to_string())]));
+    assert_eq!(uri.fragment, Some("top".to_string()));
+    assert!(parse::uri("/a b").is_err());
+}
+
+#[test]
+fn test_sections() {
+    let (rest, headers) = parse::headers("Host: example.org\r\nAccept: */*\r\n\r\nbody").unwrap();
+    assert_eq!(rest, "\r\nbody");
+    assert_eq!(headers.get("accept"), Some("*/*"));
+
+    let (rest, status_line) = parse::status_line("HTTP/1.1 204 No Content\r\n").unwrap();
+    assert_eq!((rest, status_line.status), ("", 204));
+    assert!(parse::status_line("HTTP/1.1 2xx\r\n").is_err());
+
+    let (rest, body) = parse::chunked_body("3\r\nabc\r\n0\r\n\r\nnext").unwrap();
+    assert_eq!((rest, body.data), ("next", b"abc".to_vec()));
+    assert_eq!(parse::chunked_body("5\r\nab"), Err(ParseError::Incomplete));
+}
+
+#[test]
+fn test_header_value() {
+    let media_type: MediaType = parse::header_value(" text/html; charset=utf-8 ").unwrap();
+    assert_eq!(media_type.type_, "text");
+    assert_eq!(media_type.subtype, "html");
+
+    assert_eq!(
+        parse::header_value::<ContentRange>("bytes 0-9/100"),
+        Ok(ContentRange::Satisfied {
+            unit: "bytes".to_string(),
+            first: 0,
+            last: 9,
+            total: Some(100),
+        })
+    );
+    assert_eq!(
+        parse::header_value::<EntityTag>("W/\"v1\""),
+        Ok(EntityTag {
+            weak: true,
+            tag: "v1".to_string(),
+        })
+    );
+    assert_eq!(
+        Origin::from_header_value("https://example.org"),
+        Ok(Origin::Tuple(
+            Scheme::HTTPS,
+            Host::HOST("example.org".to_string()),
+            None
+        ))
+    );
+    assert_eq!(parse::header_value::<Method>("PATCH"), Ok(Method::PATCH));
+    assert!(parse::header_value::<MediaType>("text/html extra").is_err());
+}